@@ -0,0 +1,265 @@
+//! Reference models of arbiters with 4-state configuration
+//!
+//! Weights and priorities are 4-state values like CSRs sampled through DPI.
+//! A grant depending on x/z configuration or requests is
+//! [`Grant::Unknown`].
+
+use crate::diag::Diag;
+use crate::{Bit4, Sv4State, SvLogic};
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Result of an arbitration
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Grant {
+    /// No port is requesting
+    Idle,
+    /// The port is granted
+    Port(usize),
+    /// The grant depends on x/z inputs
+    Unknown,
+}
+
+impl Grant {
+    /// Diagnostic of an unknown grant, which is `None` for the other grants
+    pub fn diag(&self) -> Option<Diag> {
+        match self {
+            Grant::Unknown => Some(Diag::error("grant depends on x/z inputs")),
+            _ => None,
+        }
+    }
+}
+
+/// Fixed-priority arbiter with programmable priorities
+///
+/// The requesting port with the largest priority is granted, and ties are
+/// granted to the lower port. An x/z priority bit may be 0 or 1, and an x/z
+/// request may be either, so the grant is unknown unless one port wins for
+/// all of them.
+#[derive(Clone, Debug)]
+pub struct PriorityArbiter<T: Copy + Debug> {
+    priorities: Vec<Sv4State<T>>,
+}
+
+impl<T: Copy + Debug + PrimInt> PriorityArbiter<T> {
+    /// Create an arbiter of `ports` ports with priority 0
+    pub fn new(ports: usize) -> Self {
+        PriorityArbiter {
+            priorities: vec![Sv4State::zeros(); ports],
+        }
+    }
+
+    pub fn ports(&self) -> usize {
+        self.priorities.len()
+    }
+
+    /// Set the priority of `port`
+    pub fn set_priority(&mut self, port: usize, priority: Sv4State<T>) {
+        if let Some(x) = self.priorities.get_mut(port) {
+            *x = priority.normalize();
+        }
+    }
+
+    /// Grant one of `requests`, which has a bit for each port
+    pub fn arbitrate(&self, requests: &[Bit4]) -> Grant {
+        // (port, lowest and highest possible priority, certainly requesting)
+        let contenders: Vec<_> = self
+            .priorities
+            .iter()
+            .zip(requests)
+            .enumerate()
+            .filter(|(_, (_, r))| **r != SvLogic::Zero)
+            .map(|(i, (p, r))| (i, p.v, p.v | p.z | p.x, *r == SvLogic::One))
+            .collect();
+        if contenders.is_empty() {
+            return Grant::Idle;
+        }
+
+        let winner = contenders.iter().find(|(i, lo, _, certain)| {
+            *certain
+                && contenders
+                    .iter()
+                    .all(|(j, _, hi, _)| i == j || lo > hi || (i < j && lo == hi))
+        });
+        winner.map_or(Grant::Unknown, |(i, _, _, _)| Grant::Port(*i))
+    }
+}
+
+/// Weighted round-robin arbiter
+///
+/// The granted port keeps the grant while it is requesting, up to its weight
+/// of consecutive grants, and then the next requesting port in round-robin
+/// order is granted. Ports with weight 0 are never granted.
+///
+/// A grant depending on an x/z weight or request is unknown, and then the
+/// arbiter state is unknown, so all grants are unknown until
+/// [`WeightedRoundRobin::reset`].
+#[derive(Clone, Debug)]
+pub struct WeightedRoundRobin<T: Copy + Debug> {
+    weights: Vec<Sv4State<T>>,
+    /// port with the grant and its remaining credits
+    current: Option<(usize, u64)>,
+    /// search start of the next port
+    next: usize,
+    known: bool,
+}
+
+impl<T: Copy + Debug + PrimInt> WeightedRoundRobin<T> {
+    /// Create an arbiter of `ports` ports with weight 1
+    pub fn new(ports: usize) -> Self {
+        WeightedRoundRobin {
+            weights: vec![Sv4State::known(T::one()); ports],
+            current: None,
+            next: 0,
+            known: true,
+        }
+    }
+
+    pub fn ports(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Set the weight of `port`
+    pub fn set_weight(&mut self, port: usize, weight: Sv4State<T>) {
+        if let Some(x) = self.weights.get_mut(port) {
+            *x = weight.normalize();
+        }
+    }
+
+    /// Return to the reset state, where port 0 is searched first
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.next = 0;
+        self.known = true;
+    }
+
+    /// Whether the arbiter state is known
+    pub fn is_known(&self) -> bool {
+        self.known
+    }
+
+    /// Diagnostic of an unknown arbiter state, which is `None` if the
+    /// state is known
+    pub fn diag(&self) -> Option<Diag> {
+        if self.known {
+            None
+        } else {
+            Some(Diag::error(
+                "arbiter state is unknown after an x/z grant until reset",
+            ))
+        }
+    }
+
+    /// Grant one of `requests`, which has a bit for each port
+    pub fn arbitrate(&mut self, requests: &[Bit4]) -> Grant {
+        let ports = self.ports();
+        let request = |i: usize| requests.get(i).copied().unwrap_or(SvLogic::Zero);
+        if !self.known {
+            return Grant::Unknown;
+        }
+        if (0..ports).all(|i| request(i) == SvLogic::Zero) {
+            return Grant::Idle;
+        }
+
+        if let Some((port, credits)) = self.current {
+            match request(port) {
+                SvLogic::One if credits > 0 => {
+                    self.current = Some((port, credits - 1));
+                    return Grant::Port(port);
+                }
+                SvLogic::Zero | SvLogic::One => (),
+                _ => return self.poison(),
+            }
+        }
+
+        for k in 0..ports {
+            let port = (self.next + k) % ports;
+            let weight = self.weights[port];
+            match request(port) {
+                SvLogic::Zero => continue,
+                SvLogic::One if weight.is_known() => {
+                    let weight = weight.v.to_u64().unwrap_or(u64::MAX);
+                    if weight == 0 {
+                        continue;
+                    }
+                    self.current = Some((port, weight - 1));
+                    self.next = (port + 1) % ports;
+                    return Grant::Port(port);
+                }
+                _ => {
+                    // a zero weight still skips the port
+                    if weight.v == T::zero() && (weight.z | weight.x) == T::zero() {
+                        continue;
+                    }
+                    return self.poison();
+                }
+            }
+        }
+        self.current = None;
+        Grant::Idle
+    }
+
+    fn poison(&mut self) -> Grant {
+        self.known = false;
+        Grant::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SvLogic::{One, Zero, X};
+
+    #[test]
+    fn priority() {
+        let mut arb = PriorityArbiter::<u8>::new(3);
+        arb.set_priority(0, Sv4State::known(1));
+        arb.set_priority(1, Sv4State::known(3));
+        arb.set_priority(2, Sv4State::new(0, 0, 0x2));
+
+        assert_eq!(arb.arbitrate(&[Zero, Zero, Zero]), Grant::Idle);
+        assert_eq!(arb.arbitrate(&[One, One, Zero]), Grant::Port(1));
+        assert_eq!(arb.arbitrate(&[One, X, Zero]), Grant::Unknown);
+        assert_eq!(arb.arbitrate(&[X, One, Zero]), Grant::Port(1));
+        assert_eq!(arb.arbitrate(&[One, Zero, One]), Grant::Unknown);
+        assert_eq!(arb.arbitrate(&[Zero, One, One]), Grant::Port(1));
+
+        arb.set_priority(2, Sv4State::known(3));
+        assert_eq!(arb.arbitrate(&[Zero, One, One]), Grant::Port(1));
+    }
+
+    #[test]
+    fn weighted_round_robin() {
+        let mut arb = WeightedRoundRobin::<u8>::new(3);
+        arb.set_weight(0, Sv4State::known(2));
+        arb.set_weight(2, Sv4State::known(0));
+
+        let grants: Vec<_> = (0..5).map(|_| arb.arbitrate(&[One, One, One])).collect();
+        assert_eq!(
+            grants,
+            [
+                Grant::Port(0),
+                Grant::Port(0),
+                Grant::Port(1),
+                Grant::Port(0),
+                Grant::Port(0)
+            ]
+        );
+        assert_eq!(arb.arbitrate(&[Zero, Zero, One]), Grant::Idle);
+
+        arb.set_weight(1, Sv4State::all_x());
+        assert_eq!(arb.arbitrate(&[Zero, One, Zero]), Grant::Unknown);
+        assert!(!arb.is_known());
+        assert_eq!(
+            Grant::Unknown.diag().unwrap().to_string(),
+            "[ERROR] grant depends on x/z inputs"
+        );
+        assert_eq!(Grant::Port(0).diag(), None);
+        assert!(arb.diag().is_some());
+        assert_eq!(arb.arbitrate(&[One, Zero, Zero]), Grant::Unknown);
+
+        arb.reset();
+        assert_eq!(arb.diag(), None);
+        assert_eq!(arb.arbitrate(&[One, Zero, Zero]), Grant::Port(0));
+    }
+}
@@ -0,0 +1,233 @@
+//! Bit manipulation applied to all planes
+//!
+//! Permutations move x/z bits with the value bits. Operations whose bit
+//! positions depend on an unknown operand give all-x.
+
+use crate::Sv4State;
+use core::fmt::Debug;
+use num_traits::PrimInt;
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Apply `f` to each plane of the normalized value
+    fn map_planes<F: Fn(T) -> T>(&self, f: F) -> Self {
+        let value = self.normalize();
+        Sv4State::new(f(value.v), f(value.z), f(value.x))
+    }
+
+    /// Reverse the order of the lower `width` bits
+    ///
+    /// Bits above `width` are 0.
+    pub fn bit_reverse(&self, width: u32) -> Self {
+        let width = width.clamp(1, T::zero().count_zeros());
+        let shift = T::zero().count_zeros() - width;
+        let mask = Sv4State::<T>::width_mask(width);
+        self.map_planes(|x| (x & mask).reverse_bits().unsigned_shr(shift))
+    }
+
+    /// Reverse the order of the lower `width / 8` bytes (rounded up)
+    ///
+    /// Bits above the bytes are 0.
+    pub fn byte_swap(&self, width: u32) -> Self {
+        let bytes = width.div_ceil(8).clamp(1, T::zero().count_zeros() / 8);
+        let shift = T::zero().count_zeros() - bytes * 8;
+        let mask = Sv4State::<T>::width_mask(bytes * 8);
+        self.map_planes(|x| (x & mask).swap_bytes().unsigned_shr(shift))
+    }
+
+    /// Number of 1 bits in the lower `width` bits, like `cpop`
+    ///
+    /// The result is all-x if any of the bits is x/z.
+    pub fn popcount(&self, width: u32) -> Self {
+        let value = self.normalize().zero_extend(width.max(1));
+        if !value.is_known() {
+            return Sv4State::all_x();
+        }
+        Sv4State::known(T::from(value.v.count_ones()).unwrap_or_else(T::zero))
+    }
+
+    /// Transpose the bit matrix of `rows` x `cols` bits, like `bmatflip` for
+    /// 8 x 8
+    ///
+    /// Bit `r * cols + c` moves to bit `c * rows + r`, and bits beyond the
+    /// matrix are 0.
+    pub fn transpose(&self, rows: u32, cols: u32) -> Self {
+        let payload_width = T::zero().count_zeros() as usize;
+        let (rows, cols) = (rows as usize, cols as usize);
+        let mut ret = Sv4State::zeros();
+        for r in 0..rows {
+            for c in 0..cols {
+                if r * cols + c < payload_width && c * rows + r < payload_width {
+                    ret.set_bit(c * rows + r, self.bit(r * cols + c));
+                }
+            }
+        }
+        ret
+    }
+
+    /// Gather the bits selected by `mask` into the lower bits, like `pext`
+    ///
+    /// The result is all-x if `mask` has x/z bits.
+    pub fn pext(&self, mask: Self) -> Self {
+        let mask = mask.normalize();
+        if !mask.is_known() {
+            return Sv4State::all_x();
+        }
+        let mut ret = Sv4State::zeros();
+        let mut j = 0;
+        for i in 0..T::zero().count_zeros() as usize {
+            if (mask.v >> i) & T::one() != T::zero() {
+                ret.set_bit(j, self.bit(i));
+                j += 1;
+            }
+        }
+        ret
+    }
+
+    /// Scatter the lower bits to the bits selected by `mask`, like `pdep`
+    ///
+    /// The result is all-x if `mask` has x/z bits.
+    pub fn pdep(&self, mask: Self) -> Self {
+        let mask = mask.normalize();
+        if !mask.is_known() {
+            return Sv4State::all_x();
+        }
+        let mut ret = Sv4State::zeros();
+        let mut j = 0;
+        for i in 0..T::zero().count_zeros() as usize {
+            if (mask.v >> i) & T::one() != T::zero() {
+                ret.set_bit(i, self.bit(j));
+                j += 1;
+            }
+        }
+        ret
+    }
+
+    /// Rotate the lower `width` bits to the left by `amount`
+    ///
+    /// As a barrel shifter, each bit of `amount` selects a stage rotating by
+    /// its weight. An x/z bit of `amount` merges both inputs of the stage, so
+    /// result bits are kept only where all the candidates agree, and are x
+    /// otherwise.
+    pub fn rotate_left(&self, amount: Self, width: u32) -> Self {
+        self.rotate(amount, width, true)
+    }
+
+    /// Rotate the lower `width` bits to the right by `amount`
+    ///
+    /// An unknown `amount` is handled like [`Sv4State::rotate_left`].
+    pub fn rotate_right(&self, amount: Self, width: u32) -> Self {
+        self.rotate(amount, width, false)
+    }
+
+    fn rotate(&self, amount: Self, width: u32, left: bool) -> Self {
+        let width = width.clamp(1, T::zero().count_zeros());
+        let mask = Sv4State::<T>::width_mask(width);
+        let amount = amount.normalize();
+        let rot = |x: T, n: u32| {
+            let x = x & mask;
+            let n = if left { n } else { (width - n) % width };
+            if n == 0 {
+                x
+            } else {
+                ((x << n as usize) | x.unsigned_shr(width - n)) & mask
+            }
+        };
+
+        let mut ret = self.normalize();
+        let mut weight = 1 % width;
+        for i in 0..T::zero().count_zeros() as usize {
+            let b = |x: T| (x >> i) & T::one() != T::zero();
+            let rotated = Sv4State::new(rot(ret.v, weight), rot(ret.z, weight), rot(ret.x, weight));
+            if b(amount.z | amount.x) {
+                let agree = !(ret.z | ret.x | rotated.z | rotated.x) & !(ret.v ^ rotated.v);
+                ret = Sv4State::new(ret.v & agree, T::zero(), !agree & mask);
+            } else if b(amount.v) {
+                ret = rotated;
+            }
+            weight = (weight * 2) % width;
+        }
+        ret.zero_extend(width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u32> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn reverse() {
+        let a = sv("32'b1111_0000_1x0z_0011");
+
+        assert_eq!(a.bit_reverse(16), sv("32'b1100_z0x1_0000_1111"));
+        assert_eq!(a.bit_reverse(5), sv("32'b1100_z"));
+        assert_eq!(
+            a.bit_reverse(32),
+            sv("32'b1100_z0x1_0000_1111_0000_0000_0000_0000")
+        );
+        assert_eq!(a.byte_swap(12), sv("32'b1x0z_0011_1111_0000"));
+        assert_eq!(
+            a.byte_swap(32),
+            sv("32'b1x0z_0011_1111_0000_0000_0000_0000_0000")
+        );
+
+        // the sign bit of signed payloads is not filled
+        assert_eq!(Sv4State::<i8>::known(1).bit_reverse(4), Sv4State::known(8));
+        assert_eq!(
+            Sv4State::<i16>::known(0x80).byte_swap(8),
+            Sv4State::known(0x80)
+        );
+    }
+
+    #[test]
+    fn bitmanip() {
+        let a = sv("32'b1111_0000_1x0z_0011");
+
+        assert_eq!(a.popcount(8), Sv4State::all_x());
+        assert_eq!(a.popcount(2), Sv4State::known(2));
+        assert_eq!(a.popcount(32).popcount(0), Sv4State::all_x());
+        assert_eq!(
+            Sv4State::<u32>::known(0xf0f0).popcount(32),
+            Sv4State::known(8)
+        );
+
+        assert_eq!(sv("32'b0011_1x01").transpose(2, 4), sv("32'b010x_1011"));
+        assert_eq!(
+            Sv4State::<u64>::known(0x0000_0000_0000_80ff).transpose(8, 8),
+            Sv4State::known(0x0301_0101_0101_0101)
+        );
+
+        let mask = sv("32'b1100_1010");
+        assert_eq!(a.pext(mask), sv("32'b1x01"));
+        assert_eq!(sv("32'b1x0z").pdep(mask), sv("32'b1x00_00z0"));
+        assert_eq!(a.pdep(sv("32'b1z")), Sv4State::all_x());
+    }
+
+    #[test]
+    fn rotate() {
+        let a = sv("32'b1000_0x01");
+
+        assert_eq!(a.rotate_left(Sv4State::known(1), 8), sv("32'b0000_x011"));
+        assert_eq!(a.rotate_right(Sv4State::known(2), 8), sv("32'b0110_000x"));
+        assert_eq!(
+            a.rotate_left(Sv4State::known(11), 8),
+            a.rotate_left(Sv4State::known(3), 8)
+        );
+        assert_eq!(a.rotate_left(Sv4State::known(3), 5), sv("32'b0100x"));
+
+        let b = sv("32'b1111_0000");
+        assert_eq!(b.rotate_left(sv("32'b0x0"), 8), sv("32'b11xx_00xx"));
+        assert_eq!(
+            sv("32'hff").rotate_right(Sv4State::all_x(), 8),
+            sv("32'hff")
+        );
+
+        // the sign bit of signed payloads is not filled
+        let c = Sv4State::<i8>::known(-128);
+        assert_eq!(c.rotate_left(Sv4State::known(1), 8), Sv4State::known(1));
+        assert_eq!(c.rotate_right(Sv4State::known(7), 8), Sv4State::known(1));
+    }
+}
@@ -0,0 +1,94 @@
+//! Conversion to/from bit vectors of the `bitvec` crate (`bitvec` feature)
+
+use crate::{Bit4, Sv4Vec, SvLogic};
+use bitvec_rs::order::BitOrder;
+use bitvec_rs::slice::BitSlice;
+use bitvec_rs::store::BitStore;
+use bitvec_rs::vec::BitVec;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+impl<T: Copy + Debug + PrimInt> Sv4Vec<T> {
+    /// Create from value, x and z planes, where bit `i` is bit `i` of the
+    /// planes
+    ///
+    /// The width is the length of `value`, and bits beyond shorter masks are
+    /// not x/z. x takes precedence over z.
+    pub fn from_bitvec<S: BitStore, O: BitOrder>(
+        value: &BitSlice<S, O>,
+        x_mask: &BitSlice<S, O>,
+        z_mask: &BitSlice<S, O>,
+    ) -> Self {
+        let mut ret = Sv4Vec::zeros(value.len());
+        for (i, v) in value.iter().enumerate() {
+            let bit = if x_mask.get(i).is_some_and(|x| *x) {
+                SvLogic::X
+            } else if z_mask.get(i).is_some_and(|z| *z) {
+                SvLogic::Z
+            } else if *v {
+                SvLogic::One
+            } else {
+                SvLogic::Zero
+            };
+            ret.set_bit(i, bit);
+        }
+        ret
+    }
+
+    /// Value, x and z planes of `width` bits
+    ///
+    /// Value bits of x/z bits are 0.
+    pub fn to_bitvec(&self) -> (BitVec, BitVec, BitVec) {
+        let mut value = BitVec::with_capacity(self.width());
+        let mut x_mask = BitVec::with_capacity(self.width());
+        let mut z_mask = BitVec::with_capacity(self.width());
+        for i in 0..self.width() {
+            let bit = self.bit(i);
+            value.push(bit == SvLogic::One);
+            x_mask.push(bit == SvLogic::X);
+            z_mask.push(bit == SvLogic::Z);
+        }
+        (value, x_mask, z_mask)
+    }
+
+    /// Set bits where `mask` is 1 to `bit`
+    ///
+    /// Bits of `mask` beyond the width are ignored.
+    pub fn apply_mask<S: BitStore, O: BitOrder>(&mut self, mask: &BitSlice<S, O>, bit: Bit4) {
+        for i in mask.iter_ones() {
+            self.set_bit(i, bit);
+        }
+    }
+
+    /// Mask of bits which are 0 or 1
+    pub fn known_mask(&self) -> BitVec {
+        (0..self.width()).map(|i| self.bit(i).is_known()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitvec_rs::prelude::*;
+
+    #[test]
+    fn bitvec() {
+        let value = bitvec![1, 0, 1, 1, 0, 0, 1, 0, 1, 1];
+        let x_mask = bits![0, 1, 0, 0, 0, 0, 0, 0, 0, 1];
+        let z_mask = bits![0, 1, 1];
+        let sv = Sv4Vec::<u8>::from_bitvec(&value, x_mask, z_mask);
+        assert_eq!(sv.width(), 10);
+        assert_eq!(format!("{:b}", sv), "x101001zx1");
+
+        let (v, x, z) = sv.to_bitvec();
+        assert_eq!(v, bits![1, 0, 0, 1, 0, 0, 1, 0, 1, 0]);
+        assert_eq!(x, x_mask);
+        assert_eq!(z, bits![0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(Sv4Vec::from_bitvec(&v, &x, &z), sv);
+
+        let mut sv = sv;
+        sv.apply_mask(bits![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1], SvLogic::Z);
+        assert_eq!(format!("{:b}", sv), "x101001zxz");
+        assert_eq!(sv.known_mask().count_ones(), 6);
+    }
+}
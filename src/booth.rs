@@ -0,0 +1,136 @@
+//! Radix-4 (Booth-2) partial product generation
+//!
+//! Each row selects `0`, `±1` or `±2` times the multiplicand from three
+//! overlapping multiplier bits. A negative multiple is the inverted
+//! multiplicand with a `negate` bit added at the least significant bit of the
+//! row, as usual hardware implementations. If any of the three multiplier bits
+//! is x/z, the row is poisoned: the digit is unknown, and the multiple and the
+//! negate bit are x.
+
+use crate::{Bit4, Sv4State};
+use num_traits::{PrimInt, WrappingAdd};
+use std::fmt::Debug;
+
+/// A row of partial products
+#[derive(Copy, Clone, Debug)]
+pub struct BoothRow<T: Copy + Debug> {
+    /// Booth digit in `-2..=2`, or `None` if unknown
+    pub digit: Option<i8>,
+    /// Selected multiple before the shift, inverted for negative digits
+    pub multiple: Sv4State<T>,
+    /// Bit added at bit `shift` to complete the negation
+    pub negate: Bit4,
+    /// Weight of the row
+    pub shift: u32,
+}
+
+impl<T: Copy + Debug + PrimInt> PartialEq for BoothRow<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digit == other.digit
+            && self.multiple == other.multiple
+            && self.negate == other.negate
+            && self.shift == other.shift
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + WrappingAdd> BoothRow<T> {
+    /// Partial product of the row including the negate bit
+    pub fn partial_product(&self) -> Sv4State<T> {
+        let shift = self.shift as usize;
+        let m = self.multiple;
+        let shifted = Sv4State::new(m.v << shift, m.z << shift, m.x << shift);
+        let mut negate = Sv4State::known(T::zero());
+        negate.set_bit(shift, self.negate);
+        shifted + negate
+    }
+}
+
+/// Booth-2 rows of signed `width`-bit operands
+///
+/// The operands are sign-extended to the payload width, so the sum of the
+/// partial products is the signed product wrapped to the payload width. There
+/// are `width / 2` rows (rounded up).
+pub fn booth2<T: Copy + Debug + PrimInt>(
+    multiplicand: Sv4State<T>,
+    multiplier: Sv4State<T>,
+    width: u32,
+) -> Vec<BoothRow<T>> {
+    let width = width.clamp(1, T::zero().count_zeros());
+    let m = multiplicand.normalize().sign_extend(width);
+    let b = multiplier.normalize().sign_extend(width);
+    let bit = |i: i64| {
+        if i < 0 {
+            Bit4::Zero
+        } else {
+            b.bit(i as usize)
+        }
+    };
+
+    let mut ret = Vec::new();
+    for row in 0..width.div_ceil(2) {
+        let i = i64::from(row) * 2;
+        let digit: Option<i8> = match [bit(i + 1), bit(i), bit(i - 1)] {
+            [Bit4::Zero, Bit4::Zero, Bit4::Zero] | [Bit4::One, Bit4::One, Bit4::One] => Some(0),
+            [Bit4::Zero, Bit4::Zero, Bit4::One] | [Bit4::Zero, Bit4::One, Bit4::Zero] => Some(1),
+            [Bit4::Zero, Bit4::One, Bit4::One] => Some(2),
+            [Bit4::One, Bit4::Zero, Bit4::Zero] => Some(-2),
+            [Bit4::One, Bit4::Zero, Bit4::One] | [Bit4::One, Bit4::One, Bit4::Zero] => Some(-1),
+            _ => None,
+        };
+
+        let (multiple, negate) = match digit {
+            Some(0) => (Sv4State::known(T::zero()), Bit4::Zero),
+            Some(d) => {
+                let multiple = if d.abs() == 2 {
+                    Sv4State::new(m.v << 1, m.z << 1, m.x << 1)
+                } else {
+                    m
+                };
+                if d < 0 {
+                    (!multiple, Bit4::One)
+                } else {
+                    (multiple, Bit4::Zero)
+                }
+            }
+            None => (Sv4State::all_x(), Bit4::X),
+        };
+        ret.push(BoothRow {
+            digit,
+            multiple,
+            negate,
+            shift: row * 2,
+        });
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u16> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn booth2_rows() {
+        let rows = booth2(sv("16'h00fd"), sv("16'h0007"), 8);
+        let digits: Vec<_> = rows.iter().map(|r| r.digit).collect();
+        assert_eq!(digits, [Some(-1), Some(2), Some(0), Some(0)]);
+        assert_eq!(rows[0].multiple, sv("16'h0002"));
+        assert_eq!(rows[0].negate, Bit4::One);
+        assert_eq!(rows[1].partial_product(), sv("16'hffe8"));
+
+        let sum = rows
+            .iter()
+            .fold(Sv4State::known(0), |acc, r| acc + r.partial_product());
+        assert_eq!(sum, Sv4State::known((-21i16) as u16));
+
+        let rows = booth2(sv("16'h0003"), sv("16'b0000_0000_01x0_0101"), 8);
+        let digits: Vec<_> = rows.iter().map(|r| r.digit).collect();
+        assert_eq!(digits, [Some(1), Some(1), None, None]);
+        assert_eq!(rows[1].multiple, sv("16'h0003"));
+        assert_eq!(rows[2].multiple, Sv4State::all_x());
+        assert_eq!(rows[3].negate, Bit4::X);
+    }
+}
@@ -0,0 +1,192 @@
+//! Composite cache-line formats like tag + state + data + ECC
+
+use crate::field::FieldMap;
+use crate::Sv4Vec;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Codec of cache lines whose sub-fields are declared by a [`FieldMap`]
+///
+/// Unlike [`FieldMap::decode`], sub-fields may be wider than the payload
+/// width, so a data field can hold a whole line.
+///
+/// ```
+/// use sv4state::cache_line::CacheLineCodec;
+/// use sv4state::field::FieldMap;
+/// use sv4state::Sv4Vec;
+///
+/// let codec = CacheLineCodec::new(
+///     FieldMap::new()
+///         .field("data", 0, 64)
+///         .field("state", 64, 2)
+///         .field("tag", 66, 20),
+/// );
+/// let line = codec.pack(&[
+///     Sv4Vec::<u32>::zeros(64),
+///     Sv4Vec::all_x(2),
+///     Sv4Vec::zeros(20),
+/// ]);
+/// let fields = codec.unpack(&line);
+/// assert_eq!(fields.unknown().collect::<Vec<_>>(), ["state"]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheLineCodec {
+    map: FieldMap,
+}
+
+impl CacheLineCodec {
+    pub fn new(map: FieldMap) -> Self {
+        CacheLineCodec { map }
+    }
+
+    pub fn map(&self) -> &FieldMap {
+        &self.map
+    }
+
+    /// Line width up to the most significant bit of the fields
+    pub fn width(&self) -> usize {
+        self.map
+            .fields()
+            .iter()
+            .map(|f| f.lsb + f.width as usize)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Pack sub-field values given in field order into a line
+    ///
+    /// Values are truncated or zero-extended to the field width, and bits not
+    /// covered by any field are 0.
+    pub fn pack<T: Copy + Debug + PrimInt>(&self, values: &[Sv4Vec<T>]) -> Sv4Vec<T> {
+        let mut line = Sv4Vec::zeros(self.width());
+        for (f, value) in self.map.fields().iter().zip(values) {
+            if f.width > 0 {
+                let value = Sv4Vec::new(value.words(), f.width as usize);
+                line.insert(f.lsb + f.width as usize - 1, f.lsb, &value);
+            }
+        }
+        line
+    }
+
+    /// Unpack the sub-fields of `line`
+    ///
+    /// Bits beyond the line are x.
+    pub fn unpack<T: Copy + Debug + PrimInt>(&self, line: &Sv4Vec<T>) -> CacheLine<'_, T> {
+        let fields = self
+            .map
+            .fields()
+            .iter()
+            .map(|f| match f.width {
+                0 => Sv4Vec::zeros(0),
+                w => line.select(f.lsb + w as usize - 1, f.lsb),
+            })
+            .collect();
+        CacheLine {
+            codec: self,
+            fields,
+        }
+    }
+}
+
+/// Sub-field values unpacked by [`CacheLineCodec::unpack`]
+#[derive(Clone, Debug)]
+pub struct CacheLine<'a, T: Copy + Debug> {
+    codec: &'a CacheLineCodec,
+    fields: Vec<Sv4Vec<T>>,
+}
+
+impl<'a, T: Copy + Debug + PrimInt> CacheLine<'a, T> {
+    /// Value of the sub-field named `name`
+    pub fn get(&self, name: &str) -> Option<&Sv4Vec<T>> {
+        self.codec.map.index_of(name).map(|i| &self.fields[i])
+    }
+
+    /// Replace the value of the sub-field named `name`
+    ///
+    /// Returns `false` if there is no such field.
+    pub fn set(&mut self, name: &str, value: Sv4Vec<T>) -> bool {
+        match self.codec.map.index_of(name) {
+            Some(i) => {
+                self.fields[i] = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the sub-field named `name` has no x/z bits
+    pub fn is_known(&self, name: &str) -> Option<bool> {
+        self.get(name).map(|x| x.is_known())
+    }
+
+    /// Names of sub-fields with x/z bits in field order
+    pub fn unknown(&self) -> impl Iterator<Item = &str> + '_ {
+        self.codec
+            .map
+            .fields()
+            .iter()
+            .zip(&self.fields)
+            .filter(|(_, v)| !v.is_known())
+            .map(|(f, _)| f.name.as_str())
+    }
+
+    /// Values in field order
+    pub fn fields(&self) -> &[Sv4Vec<T>] {
+        &self.fields
+    }
+
+    /// Pack into a line
+    pub fn pack(&self) -> Sv4Vec<T> {
+        self.codec.pack(&self.fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sv4State;
+
+    #[test]
+    fn pack_unpack() {
+        let codec = CacheLineCodec::new(
+            FieldMap::new()
+                .field("tag", 0, 12)
+                .field("state", 12, 2)
+                .field("data", 16, 64)
+                .field("ecc", 80, 8),
+        );
+        assert_eq!(codec.width(), 88);
+
+        let data = Sv4Vec::new(
+            &[
+                Sv4State::known(0x0123_4567),
+                Sv4State::new(0x89ab_cd00, 0, 0xff),
+            ],
+            64,
+        );
+        let line = codec.pack(&[
+            Sv4Vec::from_value(Sv4State::<u32>::known(0xabc), 12),
+            Sv4Vec::from_value(Sv4State::known(0x2), 2),
+            data.clone(),
+            Sv4Vec::from_value(Sv4State::known(0x5a), 8),
+        ]);
+        assert_eq!(
+            line.select(15, 0),
+            Sv4Vec::from_value(Sv4State::known(0x2abc), 16)
+        );
+
+        let mut fields = codec.unpack(&line);
+        assert_eq!(fields.get("data"), Some(&data));
+        assert_eq!(fields.is_known("ecc"), Some(true));
+        assert_eq!(fields.unknown().collect::<Vec<_>>(), ["data"]);
+
+        assert!(fields.set("state", Sv4Vec::all_x(2)));
+        assert!(!fields.set("none", Sv4Vec::all_x(2)));
+        let line = fields.pack();
+        assert_eq!(format!("{:b}", line.select(15, 12)), "00xx");
+        assert_eq!(
+            codec.unpack(&line.select(39, 0)).is_known("ecc"),
+            Some(false)
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Metadata headers of captures
+//!
+//! A header is one line like
+//! `sv4state-capture crate=0.2.1 encoding=1 simulator=verilator seed=42 timescale=1ns`,
+//! which is the first line of a text capture or a `$comment` of a VCD file.
+//! [`CaptureMeta::read_header`] reads only the lines before the body, so
+//! archived captures are identified without loading them.
+
+use crate::time::{SimTime, TimeUnit};
+use std::io::{self, BufRead};
+
+/// Version of the value encoding written by this crate
+pub const ENCODING_VERSION: u32 = 1;
+
+const MAGIC: &str = "sv4state-capture";
+
+/// Error of parsing a header line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaptureMetaError {
+    /// The line doesn't start with `sv4state-capture`
+    NotHeader,
+    /// The field is not `key=value` or has an invalid value
+    InvalidField(String),
+    /// The required field is missing
+    MissingField(&'static str),
+}
+
+impl std::fmt::Display for CaptureMetaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureMetaError::NotHeader => write!(f, "not a capture header"),
+            CaptureMetaError::InvalidField(x) => write!(f, "invalid header field `{}`", x),
+            CaptureMetaError::MissingField(x) => write!(f, "missing header field `{}`", x),
+        }
+    }
+}
+
+impl std::error::Error for CaptureMetaError {}
+
+/// Metadata of a capture
+///
+/// Unknown fields of the header are ignored, so headers of later versions
+/// are readable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CaptureMeta {
+    /// version of the crate which wrote the capture
+    pub crate_version: String,
+    /// [`ENCODING_VERSION`] of the crate which wrote the capture
+    pub encoding_version: u32,
+    pub simulator: Option<String>,
+    /// random seed of the test
+    pub seed: Option<u64>,
+    pub timescale: Option<SimTime>,
+}
+
+impl Default for CaptureMeta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaptureMeta {
+    /// Metadata of this crate
+    pub fn new() -> Self {
+        CaptureMeta {
+            crate_version: String::from(env!("CARGO_PKG_VERSION")),
+            encoding_version: ENCODING_VERSION,
+            simulator: None,
+            seed: None,
+            timescale: None,
+        }
+    }
+
+    pub fn simulator(mut self, simulator: &str) -> Self {
+        self.simulator = Some(String::from(simulator));
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn timescale(mut self, timescale: SimTime) -> Self {
+        self.timescale = Some(timescale);
+        self
+    }
+
+    /// Whether the value encoding is readable by this crate
+    pub fn is_supported(&self) -> bool {
+        self.encoding_version <= ENCODING_VERSION
+    }
+
+    /// Header line without a line break
+    ///
+    /// `%`, `$` and whitespaces in values are percent-encoded.
+    pub fn to_line(&self) -> String {
+        let mut ret = format!(
+            "{} crate={} encoding={}",
+            MAGIC,
+            escape(&self.crate_version),
+            self.encoding_version
+        );
+        if let Some(x) = &self.simulator {
+            ret.push_str(&format!(" simulator={}", escape(x)));
+        }
+        if let Some(x) = self.seed {
+            ret.push_str(&format!(" seed={}", x));
+        }
+        if let Some(x) = self.timescale {
+            ret.push_str(&format!(" timescale={}", x));
+        }
+        ret
+    }
+
+    /// Parse the line of [`CaptureMeta::to_line`]
+    pub fn parse_line(line: &str) -> Result<Self, CaptureMetaError> {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some(MAGIC) {
+            return Err(CaptureMetaError::NotHeader);
+        }
+        let (mut crate_version, mut encoding_version) = (None, None);
+        let mut ret = CaptureMeta::new();
+        for field in fields {
+            let invalid = || CaptureMetaError::InvalidField(String::from(field));
+            let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+            let value = unescape(value).ok_or_else(invalid)?;
+            match key {
+                "crate" => crate_version = Some(value),
+                "encoding" => encoding_version = Some(value.parse().map_err(|_| invalid())?),
+                "simulator" => ret.simulator = Some(value),
+                "seed" => ret.seed = Some(value.parse().map_err(|_| invalid())?),
+                "timescale" => ret.timescale = Some(parse_time(&value).ok_or_else(invalid)?),
+                _ => (),
+            }
+        }
+        ret.crate_version = crate_version.ok_or(CaptureMetaError::MissingField("crate"))?;
+        ret.encoding_version =
+            encoding_version.ok_or(CaptureMetaError::MissingField("encoding"))?;
+        Ok(ret)
+    }
+
+    /// Read the header of a text capture or a VCD file
+    ///
+    /// Lines are read up to the header, so the body is not loaded. `None` is
+    /// returned if the first line of a text capture is not a header, or if a
+    /// VCD file has no header before `$enddefinitions`.
+    pub fn read_header<R: BufRead>(reader: R) -> io::Result<Option<Self>> {
+        let mut vcd = false;
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.starts_with(MAGIC) {
+                return Ok(CaptureMeta::parse_line(line).ok());
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with("$enddefinitions") {
+                return Ok(None);
+            }
+            vcd |= line.starts_with('$');
+            if !vcd {
+                return Ok(None);
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut ret = String::new();
+    for c in s.chars() {
+        if c == '%' || c == '$' || c.is_whitespace() {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                ret.push_str(&format!("%{:02X}", b));
+            }
+        } else {
+            ret.push(c);
+        }
+    }
+    ret
+}
+
+fn unescape(s: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Parse a time like `10ps`
+fn parse_time(s: &str) -> Option<SimTime> {
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let unit = match &s[split..] {
+        "fs" => TimeUnit::Fs,
+        "ps" => TimeUnit::Ps,
+        "ns" => TimeUnit::Ns,
+        "us" => TimeUnit::Us,
+        "ms" => TimeUnit::Ms,
+        "s" => TimeUnit::S,
+        _ => return None,
+    };
+    Some(SimTime::new(s[..split].parse().ok()?, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header() {
+        let meta = CaptureMeta::new()
+            .simulator("Xcelium 23.09")
+            .seed(42)
+            .timescale(SimTime::new(10, TimeUnit::Ps));
+        let line = meta.to_line();
+        assert_eq!(
+            line,
+            format!(
+                "sv4state-capture crate={} encoding=1 simulator=Xcelium%2023.09 seed=42 timescale=10ps",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        assert_eq!(CaptureMeta::parse_line(&line), Ok(meta.clone()));
+        assert!(meta.is_supported());
+
+        let later =
+            CaptureMeta::parse_line("sv4state-capture crate=9.0 encoding=7 compression=zstd");
+        assert_eq!(later.as_ref().map(|x| x.is_supported()), Ok(false));
+        assert_eq!(
+            CaptureMeta::parse_line("sv4state-capture crate=0.1"),
+            Err(CaptureMetaError::MissingField("encoding"))
+        );
+        assert_eq!(
+            CaptureMeta::parse_line("sv4state-capture crate=0.1 encoding=1 seed=x"),
+            Err(CaptureMetaError::InvalidField(String::from("seed=x")))
+        );
+
+        let text = format!("{}\n0 8'hff\n", line);
+        assert_eq!(
+            CaptureMeta::read_header(text.as_bytes()).unwrap(),
+            Some(meta.clone())
+        );
+        let vcd = format!(
+            "$timescale 10ps $end\n$comment\n    {}\n$end\n$enddefinitions $end\n",
+            line
+        );
+        assert_eq!(
+            CaptureMeta::read_header(vcd.as_bytes()).unwrap(),
+            Some(meta)
+        );
+        assert_eq!(CaptureMeta::read_header(&b"0 8'hff\n"[..]).unwrap(), None);
+        assert_eq!(
+            CaptureMeta::read_header(&b"$enddefinitions $end\n#0\n"[..]).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Carry-save (redundant) representation
+//!
+//! A carry-save value is a pair of a sum vector and a carry vector whose sum is
+//! the represented value. The 3:2 compression works per bit with the bitwise
+//! operators, so an x bit only affects the sum and carry bits it reaches, as
+//! a compressor tree of full adders.
+
+use crate::Sv4State;
+use num_traits::{PrimInt, WrappingAdd};
+use std::fmt::Debug;
+
+/// Value represented as `sum + carry`
+///
+/// `carry` is aligned to the same bit weights as `sum`, so the carry out of
+/// bit `i` is at bit `i + 1`.
+#[derive(Copy, Clone, Debug)]
+pub struct CarrySave<T: Copy + Debug> {
+    pub sum: Sv4State<T>,
+    pub carry: Sv4State<T>,
+}
+
+impl<T: Copy + Debug + PrimInt> PartialEq for CarrySave<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.sum == other.sum && self.carry == other.carry
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> CarrySave<T> {
+    pub fn new(sum: Sv4State<T>, carry: Sv4State<T>) -> Self {
+        CarrySave { sum, carry }
+    }
+
+    /// Redundant form of a binary value with zero carry
+    pub fn from_binary(value: Sv4State<T>) -> Self {
+        CarrySave {
+            sum: value,
+            carry: Sv4State::known(T::zero()),
+        }
+    }
+
+    /// Add `value` by a row of full adders (3:2 compression)
+    ///
+    /// The carry out of the most significant bit is dropped.
+    pub fn accumulate(self, value: Sv4State<T>) -> Self {
+        let (a, b, c) = (self.sum, self.carry, value);
+        let majority = (a & b) | (a & c) | (b & c);
+        CarrySave {
+            sum: a ^ b ^ c,
+            carry: Sv4State::new(majority.v << 1, majority.z << 1, majority.x << 1),
+        }
+    }
+
+    /// Whether both vectors are fully known
+    pub fn is_known(&self) -> bool {
+        self.sum.is_known() && self.carry.is_known()
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + WrappingAdd> CarrySave<T> {
+    /// Binary value by the final carry-propagate addition
+    pub fn resolve(self) -> Sv4State<T> {
+        self.sum + self.carry
+    }
+}
+
+/// Compress `values` into a carry-save value by chained 3:2 compression
+pub fn compress<T: Copy + Debug + PrimInt>(values: &[Sv4State<T>]) -> CarrySave<T> {
+    let mut ret = CarrySave::from_binary(Sv4State::known(T::zero()));
+    for value in values {
+        ret = ret.accumulate(*value);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn carry_save() {
+        let cs = CarrySave::from_binary(sv("8'd13")).accumulate(sv("8'd7"));
+        assert_eq!(cs, CarrySave::new(sv("8'd10"), sv("8'd10")));
+        assert_eq!(cs.resolve(), sv("8'd20"));
+
+        let cs = compress(&[sv("8'd100"), sv("8'd50"), sv("8'd25"), sv("8'd5")]);
+        assert!(cs.is_known());
+        assert_eq!(cs.resolve(), sv("8'd180"));
+
+        let cs = CarrySave::from_binary(sv("8'b0000_0101")).accumulate(sv("8'b0000_x100"));
+        assert_eq!(cs, CarrySave::new(sv("8'b0000_x001"), sv("8'b0000_1000")));
+        assert_eq!(cs.resolve(), Sv4State::all_x());
+    }
+}
@@ -0,0 +1,211 @@
+//! Constant values and masks per payload type
+//!
+//! Each submodule holds `const` items for the payload type of its name, and
+//! `const fn` counterparts of basic constructors and bitwise operators of
+//! [`Sv4State`](crate::Sv4State) to build expected-value tables at compile
+//! time.
+
+macro_rules! consts {
+    ($t:ident, $bits:expr) => {
+        #[doc = concat!("Constants of `Sv4State<", stringify!($t), ">`")]
+        pub mod $t {
+            use crate::Sv4State;
+
+            /// Payload width
+            pub const WIDTH: u32 = $bits;
+
+            /// All bits are x
+            pub const ALL_X: Sv4State<$t> = Sv4State {
+                v: 0,
+                z: 0,
+                x: <$t>::MAX,
+            };
+
+            /// All bits are z
+            pub const ALL_Z: Sv4State<$t> = Sv4State {
+                v: 0,
+                z: <$t>::MAX,
+                x: 0,
+            };
+
+            /// All bits are 0
+            pub const ZEROS: Sv4State<$t> = Sv4State { v: 0, z: 0, x: 0 };
+
+            /// All bits are 1
+            pub const ONES: Sv4State<$t> = Sv4State {
+                v: <$t>::MAX,
+                z: 0,
+                x: 0,
+            };
+
+            /// `WIDTH_MASKS[n]` has the lower `n` bits set
+            pub const WIDTH_MASKS: [$t; $bits + 1] = {
+                let mut ret = [0; $bits + 1];
+                let mut i = 1;
+                while i <= $bits {
+                    ret[i] = <$t>::MAX >> ($bits - i);
+                    i += 1;
+                }
+                ret
+            };
+
+            /// `BYTE_MASKS[n]` selects byte `n`
+            pub const BYTE_MASKS: [$t; $bits / 8] = {
+                let mut ret = [0; $bits / 8];
+                let mut i = 0;
+                while i < $bits / 8 {
+                    ret[i] = 0xff << (i * 8);
+                    i += 1;
+                }
+                ret
+            };
+
+            /// `WALKING_ONES[n]` has only bit `n` set
+            pub const WALKING_ONES: [Sv4State<$t>; $bits] = {
+                let mut ret = [ZEROS; $bits];
+                let mut i = 0;
+                while i < $bits {
+                    ret[i].v = 1 << i;
+                    i += 1;
+                }
+                ret
+            };
+
+            /// `WALKING_ZEROS[n]` has only bit `n` cleared
+            pub const WALKING_ZEROS: [Sv4State<$t>; $bits] = {
+                let mut ret = [ZEROS; $bits];
+                let mut i = 0;
+                while i < $bits {
+                    ret[i].v = !(1 << i);
+                    i += 1;
+                }
+                ret
+            };
+
+            /// `const` version of [`Sv4State::new`]
+            pub const fn new(v: $t, z: $t, x: $t) -> Sv4State<$t> {
+                normalize(Sv4State { v, z, x })
+            }
+
+            /// `const` version of [`Sv4State::known`]
+            pub const fn known(v: $t) -> Sv4State<$t> {
+                Sv4State { v, z: 0, x: 0 }
+            }
+
+            /// `const` version of [`Sv4State::normalize`]
+            pub const fn normalize(a: Sv4State<$t>) -> Sv4State<$t> {
+                Sv4State {
+                    v: a.v & !(a.z | a.x),
+                    z: a.z & !a.x,
+                    x: a.x,
+                }
+            }
+
+            /// `const` version of [`Sv4State::width_mask`]
+            pub const fn width_mask(width: u32) -> $t {
+                if width >= WIDTH {
+                    <$t>::MAX
+                } else {
+                    !(<$t>::MAX << width)
+                }
+            }
+
+            /// `const` version of [`Sv4State::all_x_width`]
+            pub const fn all_x_width(width: u32) -> Sv4State<$t> {
+                Sv4State {
+                    v: 0,
+                    z: 0,
+                    x: width_mask(width),
+                }
+            }
+
+            /// `const` version of [`Sv4State::all_z_width`]
+            pub const fn all_z_width(width: u32) -> Sv4State<$t> {
+                Sv4State {
+                    v: 0,
+                    z: width_mask(width),
+                    x: 0,
+                }
+            }
+
+            /// `const` version of [`Sv4State::ones_width`]
+            pub const fn ones_width(width: u32) -> Sv4State<$t> {
+                known(width_mask(width))
+            }
+
+            /// `const` version of `&`, which isn't counted by
+            /// [`xcount`](crate::xcount)
+            pub const fn and(a: Sv4State<$t>, b: Sv4State<$t>) -> Sv4State<$t> {
+                let (a, b) = (normalize(a), normalize(b));
+                let (ua, ub) = (a.z | a.x, b.z | b.x);
+                let zero = (!a.v & !ua) | (!b.v & !ub);
+                new(a.v & b.v, 0, (ua | ub) & !zero)
+            }
+
+            /// `const` version of `|`, which isn't counted by
+            /// [`xcount`](crate::xcount)
+            pub const fn or(a: Sv4State<$t>, b: Sv4State<$t>) -> Sv4State<$t> {
+                let (a, b) = (normalize(a), normalize(b));
+                let one = a.v | b.v;
+                new(one, 0, (a.z | a.x | b.z | b.x) & !one)
+            }
+
+            /// `const` version of `^`, which isn't counted by
+            /// [`xcount`](crate::xcount)
+            pub const fn xor(a: Sv4State<$t>, b: Sv4State<$t>) -> Sv4State<$t> {
+                let (a, b) = (normalize(a), normalize(b));
+                new(a.v ^ b.v, 0, a.z | a.x | b.z | b.x)
+            }
+
+            /// `const` version of `!`, which isn't counted by
+            /// [`xcount`](crate::xcount)
+            pub const fn not(a: Sv4State<$t>) -> Sv4State<$t> {
+                let a = normalize(a);
+                new(!a.v, 0, a.z | a.x)
+            }
+        }
+    };
+}
+
+consts!(u8, 8);
+consts!(u16, 16);
+consts!(u32, 32);
+consts!(u64, 64);
+consts!(u128, 128);
+
+#[cfg(test)]
+mod tests {
+    use crate::Sv4State;
+
+    #[test]
+    fn consts() {
+        assert_eq!(super::u8::ALL_X, Sv4State::<u8>::all_x());
+        assert_eq!(super::u16::ALL_Z, Sv4State::<u16>::all_z());
+        assert_eq!(super::u32::ONES, Sv4State::<u32>::ones());
+        assert_eq!(super::u64::WIDTH_MASKS[12], 0xfff);
+        assert_eq!(super::u64::WIDTH_MASKS[64], !0);
+        assert_eq!(super::u32::BYTE_MASKS[2], 0x00ff_0000);
+        assert_eq!(super::u128::WALKING_ONES[100].v, 1 << 100);
+        assert_eq!(super::u8::WALKING_ZEROS[3], Sv4State::known(0xf7));
+    }
+
+    #[test]
+    fn const_fn() {
+        use super::u8::*;
+
+        const A: Sv4State<u8> = new(0b1100_0110, 0b0000_0001, 0b0000_1000);
+        const B: Sv4State<u8> = known(0b1010_1010);
+        const TABLE: [Sv4State<u8>; 4] = [and(A, B), or(A, B), xor(A, B), not(A)];
+        assert_eq!(TABLE, [A & B, A | B, A ^ B, !A]);
+
+        assert_eq!(width_mask(3), Sv4State::<u8>::width_mask(3));
+        assert_eq!(width_mask(9), 0xff);
+        assert_eq!(all_x_width(4), Sv4State::all_x_width(4));
+        assert_eq!(all_z_width(8), Sv4State::all_z());
+        assert_eq!(ones_width(0), Sv4State::zeros());
+        assert_eq!(
+            super::u128::width_mask(100),
+            Sv4State::<u128>::width_mask(100)
+        );
+    }
+}
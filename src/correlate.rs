@@ -0,0 +1,211 @@
+//! Correlation of transactions across traces by ID fields
+//!
+//! Each sample of a trace is an entry, and its ID is extracted by a field of
+//! a [`FieldMap`]. The n-th entry of an ID in each trace belongs to the n-th
+//! flow of the ID, so IDs can be reused after completing.
+
+use crate::field::FieldMap;
+use crate::trace::Sv4Trace;
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Entry of a flow in a trace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hop {
+    /// Name of the trace
+    pub trace: String,
+    pub time: u64,
+}
+
+/// Entries of an ID through the traces
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Flow<T> {
+    pub id: T,
+    /// Entries in the order of the traces, or `None` if missing
+    pub hops: Vec<Option<Hop>>,
+}
+
+impl<T: Copy + Debug> Flow<T> {
+    /// Whether the flow has entries in all traces
+    pub fn is_complete(&self) -> bool {
+        self.hops.iter().all(|x| x.is_some())
+    }
+
+    /// Time from the first entry to the last entry
+    pub fn latency(&self) -> Option<u64> {
+        let times = || self.hops.iter().flatten().map(|x| x.time);
+        Some(times().max()? - times().min()?)
+    }
+}
+
+/// `id 0x5: req@10 -> rsp@30`, where missing entries are `?` as the trace
+/// names are not known
+impl<T: Copy + Debug + std::fmt::LowerHex> std::fmt::Display for Flow<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "id {:#x}:", self.id)?;
+        for (i, hop) in self.hops.iter().enumerate() {
+            f.write_str(if i == 0 { " " } else { " -> " })?;
+            match hop {
+                Some(hop) => write!(f, "{}@{}", hop.trace, hop.time)?,
+                None => f.write_str("?")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entry skipped because its ID has x/z bits
+#[derive(Clone, Debug)]
+pub struct UnknownId<T: Copy + Debug> {
+    pub hop: Hop,
+    pub id: Sv4State<T>,
+}
+
+impl<T: Copy + Debug + PrimInt> PartialEq for UnknownId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hop == other.hop && self.id == other.id
+    }
+}
+
+/// Result of [`Correlator::correlate`]
+#[derive(Clone, Debug)]
+pub struct Correlation<T: Copy + Debug> {
+    /// Flows in the order of their first entries
+    pub flows: Vec<Flow<T>>,
+    /// Entries with unknown IDs in time order
+    pub unknown: Vec<UnknownId<T>>,
+}
+
+impl<T: Copy + Debug> Correlation<T> {
+    /// Flows missing entries in some traces
+    pub fn incomplete(&self) -> impl Iterator<Item = &Flow<T>> + '_ {
+        self.flows.iter().filter(|x| !x.is_complete())
+    }
+}
+
+/// Correlator of traces in the order of the flow, e.g. request, grant and
+/// response
+#[derive(Clone, Debug)]
+pub struct Correlator<'a, T: Copy + Debug + PrimInt> {
+    lsb: usize,
+    width: u32,
+    traces: Vec<&'a Sv4Trace<T>>,
+}
+
+impl<'a, T: Copy + Debug + PrimInt + Hash> Correlator<'a, T> {
+    /// Create a correlator of IDs in the field `id` of `map`
+    ///
+    /// Returns `None` if `map` has no such field.
+    pub fn new(map: &FieldMap, id: &str) -> Option<Self> {
+        let field = &map.fields()[map.index_of(id)?];
+        Some(Correlator {
+            lsb: field.lsb,
+            width: field.width,
+            traces: Vec::new(),
+        })
+    }
+
+    /// Add the next trace of the flow
+    pub fn trace(mut self, trace: &'a Sv4Trace<T>) -> Self {
+        self.traces.push(trace);
+        self
+    }
+
+    /// Link entries of all traces
+    pub fn correlate(&self) -> Correlation<T> {
+        let mut ret = Correlation {
+            flows: Vec::new(),
+            unknown: Vec::new(),
+        };
+        // flows of each ID in order, and the next flow per trace
+        let mut flows: HashMap<T, (Vec<usize>, Vec<usize>)> = HashMap::new();
+        let mut entries: Vec<(u64, usize, Sv4State<T>)> = Vec::new();
+        for (i, trace) in self.traces.iter().enumerate() {
+            for s in trace.samples() {
+                let lsb = self.lsb.min(u32::MAX as usize) as u32;
+                let id = s.value.view(lsb..lsb.saturating_add(self.width)).get();
+                entries.push((s.time, i, id));
+            }
+        }
+        // stable, so ties keep the order of traces
+        entries.sort_by_key(|x| x.0);
+
+        for (time, i, id) in entries {
+            let hop = Hop {
+                trace: self.traces[i].name.clone(),
+                time,
+            };
+            if !id.is_known() {
+                ret.unknown.push(UnknownId { hop, id });
+                continue;
+            }
+            let (list, next) = flows
+                .entry(id.v)
+                .or_insert_with(|| (Vec::new(), vec![0; self.traces.len()]));
+            let index = match list.get(next[i]) {
+                Some(index) => *index,
+                None => {
+                    ret.flows.push(Flow {
+                        id: id.v,
+                        hops: vec![None; self.traces.len()],
+                    });
+                    list.push(ret.flows.len() - 1);
+                    ret.flows.len() - 1
+                }
+            };
+            next[i] += 1;
+            ret.flows[index].hops[i] = Some(hop);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correlate() {
+        let map = FieldMap::new().field("id", 4, 4).field("data", 0, 4);
+        let mut req = Sv4Trace::<u8>::new("req");
+        req.push(10, Sv4State::known(0x1a));
+        req.push(20, Sv4State::known(0x2b));
+        req.push(30, Sv4State::new(0x00, 0, 0x10));
+        req.push(40, Sv4State::known(0x1c));
+        let mut rsp = Sv4Trace::<u8>::new("rsp");
+        rsp.push(25, Sv4State::known(0x1f));
+        rsp.push(35, Sv4State::known(0x2f));
+
+        assert!(Correlator::<u8>::new(&map, "tag").is_none());
+        let result = Correlator::new(&map, "id")
+            .unwrap()
+            .trace(&req)
+            .trace(&rsp)
+            .correlate();
+
+        let flows: Vec<String> = result.flows.iter().map(|x| x.to_string()).collect();
+        assert_eq!(
+            flows,
+            [
+                "id 0x1: req@10 -> rsp@25",
+                "id 0x2: req@20 -> rsp@35",
+                "id 0x1: req@40 -> ?"
+            ]
+        );
+        assert_eq!(result.flows[1].latency(), Some(15));
+        assert_eq!(result.incomplete().count(), 1);
+        assert_eq!(
+            result.unknown,
+            [UnknownId {
+                hop: Hop {
+                    trace: String::from("req"),
+                    time: 30
+                },
+                id: Sv4State::all_x_width(1),
+            }]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Comparison of decoded fields against C struct memory images
+
+use crate::field::FieldValues;
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Integer member of a C struct
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CField {
+    pub name: String,
+    /// byte offset from the start of the struct
+    pub offset: usize,
+    /// size in bytes
+    pub size: usize,
+}
+
+/// Memory layout of a C struct
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CLayout {
+    fields: Vec<CField>,
+    big_endian: bool,
+}
+
+impl CLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a member
+    pub fn field(mut self, name: &str, offset: usize, size: usize) -> Self {
+        self.fields.push(CField {
+            name: String::from(name),
+            offset,
+            size,
+        });
+        self
+    }
+
+    /// Byte order of members (little endian by default)
+    pub fn big_endian(mut self, big_endian: bool) -> Self {
+        self.big_endian = big_endian;
+        self
+    }
+
+    pub fn fields(&self) -> &[CField] {
+        &self.fields
+    }
+
+    /// Read the member `field` from `image`
+    ///
+    /// Returns `None` if the member exceeds `image` or the payload width.
+    pub fn read<T: PrimInt>(&self, field: &CField, image: &[u8]) -> Option<T> {
+        if field.size * 8 > T::zero().count_zeros() as usize {
+            return None;
+        }
+        let bytes = image.get(field.offset..field.offset.checked_add(field.size)?)?;
+        let mut ret = T::zero();
+        for (i, b) in bytes.iter().enumerate() {
+            let shift = if self.big_endian {
+                (field.size - 1 - i) * 8
+            } else {
+                i * 8
+            };
+            ret = ret | (T::from(*b)? << shift);
+        }
+        Some(ret)
+    }
+}
+
+/// Field-level difference reported by [`diff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldDiff<T: Copy + Debug + PrimInt> {
+    /// The values differ
+    Mismatch {
+        name: String,
+        expected: Sv4State<T>,
+        actual: T,
+    },
+    /// The 4-state value contains x/z
+    Unknown { name: String, value: Sv4State<T> },
+    /// The field exists only in the 4-state view
+    MissingInLayout { name: String },
+    /// The member exists only in the C layout
+    MissingInView { name: String },
+    /// The member exceeds the image or the payload width
+    OutOfBounds { name: String },
+}
+
+/// Compare `values` against the C struct `image` described by `layout`
+///
+/// Fields and members are matched by name.
+pub fn diff<T: Copy + Debug + PrimInt>(
+    values: &FieldValues<'_, T>,
+    image: &[u8],
+    layout: &CLayout,
+) -> Vec<FieldDiff<T>> {
+    let mut ret = Vec::new();
+    for (name, expected) in values.iter() {
+        let name = String::from(name);
+        let field = match layout.fields().iter().find(|f| f.name == name) {
+            Some(x) => x,
+            None => {
+                ret.push(FieldDiff::MissingInLayout { name });
+                continue;
+            }
+        };
+        if !expected.is_known() {
+            ret.push(FieldDiff::Unknown {
+                name,
+                value: expected,
+            });
+            continue;
+        }
+        match layout.read::<T>(field, image) {
+            Some(actual) if actual == expected.v => (),
+            Some(actual) => ret.push(FieldDiff::Mismatch {
+                name,
+                expected,
+                actual,
+            }),
+            None => ret.push(FieldDiff::OutOfBounds { name }),
+        }
+    }
+    for field in layout.fields() {
+        if values.get(&field.name).is_none() {
+            ret.push(FieldDiff::MissingInView {
+                name: field.name.clone(),
+            });
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldMap;
+    use crate::svLogicVecVal;
+
+    #[test]
+    fn diff_image() {
+        let buf = [svLogicVecVal {
+            aval: 0x1234_5678,
+            bval: 0x0000_0000,
+        }];
+        let map = FieldMap::new()
+            .field("a", 0, 16)
+            .field("b", 16, 8)
+            .field("c", 24, 8);
+        let values = map.decode::<u32>(&buf);
+
+        let layout = CLayout::new()
+            .field("a", 0, 2)
+            .field("b", 4, 4)
+            .field("d", 8, 1);
+        let image = [0x78, 0x56, 0x00, 0x00, 0x35, 0x00, 0x00, 0x00, 0x00];
+
+        assert_eq!(
+            diff(&values, &image, &layout),
+            vec![
+                FieldDiff::Mismatch {
+                    name: String::from("b"),
+                    expected: Sv4State::known(0x34),
+                    actual: 0x35,
+                },
+                FieldDiff::MissingInLayout {
+                    name: String::from("c")
+                },
+                FieldDiff::MissingInView {
+                    name: String::from("d")
+                },
+            ]
+        );
+
+        let layout = CLayout::new().field("a", 0, 2).big_endian(true);
+        let image = [0x56, 0x78];
+        assert_eq!(
+            layout.read::<u32>(&layout.fields()[0], &image),
+            Some(0x5678)
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Diagnostics of checkers
+//!
+//! Errors of checkers convert into [`Diag`], which is emitted to a
+//! [`DiagSink`] such as a `Vec<Diag>`, [`StderrSink`], a closure, or a
+//! [`TestReport`].
+
+use crate::correlate::UnknownId;
+use crate::fsm::FsmError;
+use crate::packet::{Qualifier, SegmentIssue};
+use crate::report::TestReport;
+use crate::scoreboard::{Leak, ScoreboardError};
+use crate::temporal::{Violation, ViolationKind};
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Severity of [`Diag`] in ascending order
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Error => "ERROR",
+            Severity::Fatal => "FATAL",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Diagnostic message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diag {
+    pub severity: Severity,
+    pub message: String,
+    pub signal: Option<String>,
+    pub time: Option<u64>,
+    /// Labeled values rendered by the formatting profile of the current thread
+    pub values: Vec<(String, String)>,
+}
+
+impl Diag {
+    pub fn new(severity: Severity, message: &str) -> Self {
+        Diag {
+            severity,
+            message: String::from(message),
+            signal: None,
+            time: None,
+            values: Vec::new(),
+        }
+    }
+
+    pub fn info(message: &str) -> Self {
+        Self::new(Severity::Info, message)
+    }
+
+    pub fn warning(message: &str) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn error(message: &str) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn fatal(message: &str) -> Self {
+        Self::new(Severity::Fatal, message)
+    }
+
+    /// Signal which the message is about
+    pub fn signal(mut self, signal: &str) -> Self {
+        self.signal = Some(String::from(signal));
+        self
+    }
+
+    /// Time at which the message is reported
+    pub fn time(mut self, time: u64) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Attach `value` rendered with `label`
+    pub fn value<T: Copy + Debug + PrimInt>(mut self, label: &str, value: &Sv4State<T>) -> Self {
+        self.values.push((String::from(label), value.to_string()));
+        self
+    }
+}
+
+/// `[SEVERITY] @time signal: message (label=value, ...)`
+impl std::fmt::Display for Diag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.severity)?;
+        if let Some(time) = self.time {
+            write!(f, " @{}", time)?;
+        }
+        if let Some(signal) = &self.signal {
+            write!(f, " {}:", signal)?;
+        }
+        write!(f, " {}", self.message)?;
+        if !self.values.is_empty() {
+            let values: Vec<String> = self
+                .values
+                .iter()
+                .map(|(l, v)| format!("{}={}", l, v))
+                .collect();
+            write!(f, " ({})", values.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Destination of [`Diag`]
+pub trait DiagSink {
+    fn emit(&mut self, diag: Diag);
+}
+
+/// Collect diagnostics
+impl DiagSink for Vec<Diag> {
+    fn emit(&mut self, diag: Diag) {
+        self.push(diag);
+    }
+}
+
+impl<F: FnMut(Diag)> DiagSink for F {
+    fn emit(&mut self, diag: Diag) {
+        self(diag)
+    }
+}
+
+/// Print diagnostics to stderr
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StderrSink;
+
+impl DiagSink for StderrSink {
+    fn emit(&mut self, diag: Diag) {
+        eprintln!("{}", diag);
+    }
+}
+
+/// Register each diagnostic as a check, which fails if the severity is error
+/// or fatal
+impl DiagSink for TestReport {
+    fn emit(&mut self, diag: Diag) {
+        let name = diag.signal.clone().unwrap_or_default();
+        let passed = diag.severity < Severity::Error;
+        self.add("diag", &name, passed, &diag.to_string());
+    }
+}
+
+impl<T: Copy + Debug + PrimInt, D: Debug> From<&ScoreboardError<T, D>> for Diag {
+    fn from(x: &ScoreboardError<T, D>) -> Self {
+        Diag::error(&x.to_string())
+    }
+}
+
+/// Reported at the time when the transaction was expected
+impl<T: Copy + Debug + PrimInt, D: Debug> From<&Leak<T, D>> for Diag {
+    fn from(x: &Leak<T, D>) -> Self {
+        let message = format!("unmatched transaction with data {:?}", x.data);
+        Diag::error(&message).time(x.time).value("tag", &x.tag)
+    }
+}
+
+/// Reported at the cycle of the issue, with a dropped beat as an error
+impl From<&SegmentIssue> for Diag {
+    fn from(x: &SegmentIssue) -> Self {
+        match x {
+            SegmentIssue::UnknownQualifier { cycle, qualifier } => {
+                let (name, severity) = match qualifier {
+                    Qualifier::Valid => ("valid", Severity::Error),
+                    Qualifier::Start => ("start", Severity::Warning),
+                    Qualifier::End => ("end", Severity::Warning),
+                };
+                Diag::new(severity, "qualifier contains x/z")
+                    .signal(name)
+                    .time(*cycle)
+            }
+            SegmentIssue::MissingEnd { cycle } => {
+                Diag::error("packet started before the previous one ended").time(*cycle)
+            }
+            SegmentIssue::MissingStart { cycle } => {
+                Diag::error("valid beat outside packets dropped").time(*cycle)
+            }
+        }
+    }
+}
+
+/// Reported at the entry in the trace named by the signal
+impl<T: Copy + Debug + PrimInt> From<&UnknownId<T>> for Diag {
+    fn from(x: &UnknownId<T>) -> Self {
+        Diag::error("entry skipped as the ID contains x/z")
+            .signal(&x.hop.trace)
+            .time(x.hop.time)
+            .value("id", &x.id)
+    }
+}
+
+impl<S: Debug, T: Copy + Debug + PrimInt> From<&FsmError<S, T>> for Diag {
+    fn from(x: &FsmError<S, T>) -> Self {
+        let input = match x {
+            FsmError::UnknownInput { input, .. } | FsmError::NoTransition { input, .. } => input,
+        };
+        Diag::error(&x.to_string()).value("input", input)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> From<&Violation<T>> for Diag {
+    fn from(x: &Violation<T>) -> Self {
+        let message = match &x.kind {
+            ViolationKind::MissingTrace(name) => format!("trace {} is missing", name),
+            ViolationKind::NotKnownInTime => String::from("not known in time"),
+            ViolationKind::UnknownAfter => String::from("unknown"),
+        };
+        let ret = Diag::error(&message).signal(&x.signal);
+        match x.kind {
+            ViolationKind::MissingTrace(_) => ret,
+            _ => ret.time(x.time).value("value", &x.value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temporal::TemporalChecker;
+    use crate::trace::Sv4Trace;
+
+    #[test]
+    fn diag() {
+        let d = Diag::warning("glitch")
+            .signal("data")
+            .time(10)
+            .value("actual", &Sv4State::<u8>::new(0x0f, 0, 0x30));
+        assert_eq!(d.to_string(), "[WARNING] @10 data: glitch (actual=Xf)");
+        assert_eq!(Diag::info("done").to_string(), "[INFO] done");
+        assert!(Severity::Fatal > Severity::Error);
+    }
+
+    #[test]
+    fn sink() {
+        let mut trace = Sv4Trace::<u8>::new("data");
+        trace.push(0, Sv4State::all_x());
+        let violations = TemporalChecker::new()
+            .no_unknown_after("data", 5)
+            .evaluate(&[&trace]);
+
+        let mut diags: Vec<Diag> = Vec::new();
+        let mut report = TestReport::new("test");
+        let mut count = 0;
+        for v in &violations {
+            diags.emit(v.into());
+            report.emit(v.into());
+            (|_| count += 1).emit(v.into());
+        }
+        diags.emit(Diag::from(&ScoreboardError::<u8, u32>::UnknownTag(
+            Sv4State::all_z(),
+        )));
+
+        assert_eq!(count, 1);
+        assert_eq!(diags[0].to_string(), "[ERROR] @5 data: unknown (value=xx)");
+        assert_eq!(diags[1].to_string(), "[ERROR] tag contains x/z: zz");
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn from_checkers() {
+        let leak = Leak {
+            tag: Sv4State::<u8>::new(0x10, 0, 0x01),
+            data: 7u32,
+            time: 40,
+        };
+        assert_eq!(
+            Diag::from(&leak).to_string(),
+            "[ERROR] @40 unmatched transaction with data 7 (tag=1X)"
+        );
+
+        let issue = SegmentIssue::UnknownQualifier {
+            cycle: 3,
+            qualifier: Qualifier::End,
+        };
+        assert_eq!(
+            Diag::from(&issue).to_string(),
+            "[WARNING] @3 end: qualifier contains x/z"
+        );
+        assert_eq!(
+            Diag::from(&SegmentIssue::MissingStart { cycle: 5 }).severity,
+            Severity::Error
+        );
+
+        let unknown = UnknownId {
+            hop: crate::correlate::Hop {
+                trace: String::from("rsp"),
+                time: 25,
+            },
+            id: Sv4State::<u8>::new(0x01, 0x10, 0),
+        };
+        assert_eq!(
+            Diag::from(&unknown).to_string(),
+            "[ERROR] @25 rsp: entry skipped as the ID contains x/z (id=Z1)"
+        );
+    }
+}
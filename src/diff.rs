@@ -0,0 +1,181 @@
+//! Per-bit differences of 4-state values
+
+use crate::{Bit4, Sv4State, Sv4Vec, SvLogic};
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Kind of a differing bit
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MismatchKind {
+    /// 0 and 1
+    Value,
+    /// x and 0/1
+    XVsKnown,
+    /// z and 0/1
+    ZVsKnown,
+    /// x and z
+    XVsZ,
+}
+
+impl MismatchKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MismatchKind::Value => "value",
+            MismatchKind::XVsKnown => "x vs known",
+            MismatchKind::ZVsKnown => "z vs known",
+            MismatchKind::XVsZ => "x vs z",
+        }
+    }
+}
+
+/// Differing bit
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub bit: usize,
+    pub left: Bit4,
+    pub right: Bit4,
+}
+
+impl Mismatch {
+    pub fn kind(&self) -> MismatchKind {
+        match (self.left, self.right) {
+            (SvLogic::X, SvLogic::Z) | (SvLogic::Z, SvLogic::X) => MismatchKind::XVsZ,
+            (SvLogic::X, _) | (_, SvLogic::X) => MismatchKind::XVsKnown,
+            (SvLogic::Z, _) | (_, SvLogic::Z) => MismatchKind::ZVsKnown,
+            _ => MismatchKind::Value,
+        }
+    }
+}
+
+/// Differing bits of two values
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sv4Diff {
+    mismatches: Vec<Mismatch>,
+}
+
+impl Sv4Diff {
+    fn new(width: usize, bit: impl Fn(usize) -> (Bit4, Bit4)) -> Self {
+        let mismatches = (0..width)
+            .filter_map(|i| {
+                let (left, right) = bit(i);
+                (left != right).then_some(Mismatch {
+                    bit: i,
+                    left,
+                    right,
+                })
+            })
+            .collect();
+        Sv4Diff { mismatches }
+    }
+
+    /// Whether the values are case-equal
+    pub fn is_empty(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Number of differing bits
+    pub fn len(&self) -> usize {
+        self.mismatches.len()
+    }
+
+    /// Differing bits from the least significant one
+    pub fn mismatches(&self) -> &[Mismatch] {
+        &self.mismatches
+    }
+
+    /// Number of differing bits of `kind`
+    pub fn count(&self, kind: MismatchKind) -> usize {
+        self.mismatches.iter().filter(|x| x.kind() == kind).count()
+    }
+}
+
+/// Summary like `2 bits differ (1 value, 1 x vs known): [9] 1/0, [3] x/1`,
+/// listing bits from the most significant one
+impl std::fmt::Display for Sv4Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no bits differ");
+        }
+        write!(
+            f,
+            "{} bit{} differ (",
+            self.len(),
+            if self.len() == 1 { "" } else { "s" }
+        )?;
+        let kinds = [
+            MismatchKind::Value,
+            MismatchKind::XVsKnown,
+            MismatchKind::ZVsKnown,
+            MismatchKind::XVsZ,
+        ];
+        let mut first = true;
+        for kind in kinds {
+            let n = self.count(kind);
+            if n > 0 {
+                write!(f, "{}{} {}", if first { "" } else { ", " }, n, kind.name())?;
+                first = false;
+            }
+        }
+        f.write_str("):")?;
+        for (i, x) in self.mismatches.iter().rev().enumerate() {
+            write!(
+                f,
+                "{} [{}] {}/{}",
+                if i == 0 { "" } else { "," },
+                x.bit,
+                x.left,
+                x.right
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Bits differing from `other`
+    pub fn diff(&self, other: &Self) -> Sv4Diff {
+        let width = T::zero().count_zeros() as usize;
+        Sv4Diff::new(width, |i| (self.bit(i), other.bit(i)))
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4Vec<T> {
+    /// Bits differing from `other`, where bits beyond the width are 0
+    pub fn diff(&self, other: &Self) -> Sv4Diff {
+        let width = self.width().max(other.width());
+        Sv4Diff::new(width, |i| (self.bit(i), other.bit(i)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff() {
+        let a: Sv4State<u16> = "16'b10_1100_xz10".parse().unwrap();
+        let b: Sv4State<u16> = "16'b00_1100_1xz0".parse().unwrap();
+        let diff = a.diff(&b);
+        assert_eq!(diff.len(), 4);
+        assert_eq!(
+            diff.mismatches()[0],
+            Mismatch {
+                bit: 1,
+                left: SvLogic::One,
+                right: SvLogic::Z
+            }
+        );
+        assert_eq!(diff.count(MismatchKind::XVsZ), 1);
+        assert_eq!(
+            diff.to_string(),
+            "4 bits differ (1 value, 1 x vs known, 1 z vs known, 1 x vs z): \
+             [9] 1/0, [3] x/1, [2] z/x, [1] 1/z"
+        );
+        assert!(a.diff(&a).is_empty());
+
+        let v = Sv4Vec::new(&[Sv4State::<u8>::zeros(), Sv4State::all_x()], 12);
+        let w = Sv4Vec::zeros(16);
+        assert_eq!(v.diff(&w).count(MismatchKind::XVsKnown), 4);
+        assert_eq!(v.diff(&w).mismatches()[0].bit, 8);
+    }
+}
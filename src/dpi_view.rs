@@ -0,0 +1,156 @@
+//! Borrowed views of DPI canonical buffers
+
+use crate::format::unknown_chars;
+use crate::{dpi_eq, dpi_is_known, read_dpi_bits, svLogicVecVal, unknown_digit, Sv4State, SvLogic};
+use core::fmt::{Debug, Write};
+use core::ops::Range;
+use num_traits::{FromPrimitive, PrimInt};
+
+/// `width`-bit value in a borrowed DPI canonical buffer
+///
+/// Queries decode only the bits they need, and formatting writes digits
+/// without allocation. Bits of `width` beyond the buffer are x.
+#[derive(Copy, Clone)]
+pub struct Sv4DpiView<'a> {
+    data: &'a [svLogicVecVal],
+    width: usize,
+}
+
+impl<'a> Sv4DpiView<'a> {
+    pub fn new(data: &'a [svLogicVecVal], width: usize) -> Self {
+        Sv4DpiView { data, width }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Bit `i`
+    ///
+    /// Bits beyond the width are 0.
+    pub fn bit(&self, i: usize) -> SvLogic {
+        if i >= self.width {
+            return SvLogic::Zero;
+        }
+        let (a, b) = self.data.get(i / 32).map_or((1, 1), |w| {
+            ((w.aval >> (i % 32)) & 1, (w.bval >> (i % 32)) & 1)
+        });
+        match (a, b) {
+            (0, 0) => SvLogic::Zero,
+            (1, 0) => SvLogic::One,
+            (0, _) => SvLogic::Z,
+            _ => SvLogic::X,
+        }
+    }
+
+    /// `i`-th payload-width word, as [`Sv4State::from_dpi`]
+    pub fn word<T: Copy + Debug + PrimInt + FromPrimitive>(&self, i: usize) -> Sv4State<T> {
+        let payload_width = T::zero().count_zeros() as usize;
+        self.get(i * payload_width..(i + 1) * payload_width)
+    }
+
+    /// Bits of `range` moved to bit 0
+    ///
+    /// The range is clipped to the width and saturated to the payload width.
+    pub fn get<T: Copy + Debug + PrimInt + FromPrimitive>(
+        &self,
+        range: Range<usize>,
+    ) -> Sv4State<T> {
+        let end = range.end.min(self.width);
+        if range.start >= end {
+            return Sv4State::zeros();
+        }
+        read_dpi_bits(self.data, range.start, (end - range.start) as u32)
+    }
+
+    /// Whether all bits are 0 or 1
+    pub fn is_known(&self) -> bool {
+        dpi_is_known(self.data, self.width)
+    }
+}
+
+impl<'a> PartialEq for Sv4DpiView<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && dpi_eq(self.data, other.data, self.width)
+    }
+}
+
+impl<'a> Debug for Sv4DpiView<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sv4DpiView({}'b{:b})", self.width, self)
+    }
+}
+
+/// `width` digits from the most significant bit
+impl<'a> core::fmt::Binary for Sv4DpiView<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let chars = unknown_chars();
+        for i in (0..self.width).rev() {
+            f.write_char(chars.get(self.bit(i).to_char()))?;
+        }
+        Ok(())
+    }
+}
+
+/// `width / 4` digits (rounded up) from the most significant digit
+///
+/// Digits are classified as `LowerHex` of [`Sv4State`], where z takes
+/// precedence over x.
+impl<'a> core::fmt::LowerHex for Sv4DpiView<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let chars = unknown_chars();
+        for i in (0..self.width.div_ceil(4)).rev() {
+            let n = (self.width - i * 4).min(4);
+            let digit: Sv4State<u8> = self.get(i * 4..i * 4 + n);
+            let mask = Sv4State::<u8>::width_mask(n as u32);
+            let c = match unknown_digit(digit.z, digit.x, mask) {
+                Some(c) => chars.get(c),
+                None => core::char::from_digit(u32::from(digit.v), 16).unwrap_or('?'),
+            };
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dpi_view() {
+        let data = [
+            svLogicVecVal {
+                aval: 0x1234_5678,
+                bval: 0x0000_00f0,
+            },
+            svLogicVecVal {
+                aval: 0xf,
+                bval: 0xc,
+            },
+        ];
+        let view = Sv4DpiView::new(&data, 36);
+
+        assert_eq!(view.bit(0), SvLogic::Zero);
+        assert_eq!(view.bit(4), SvLogic::X);
+        assert_eq!(view.bit(7), SvLogic::Z);
+        assert_eq!(view.bit(35), SvLogic::X);
+        assert!(!view.is_known());
+        assert_eq!(view.word::<u8>(0), "8'bzxxx_1000".parse().unwrap());
+        assert_eq!(view.word::<u32>(1), Sv4State::new(0x3, 0, 0xc));
+        assert_eq!(view.get::<u16>(8..16), Sv4State::known(0x56));
+        assert_eq!(format!("{:x}", view), "X123456Z8");
+        assert_eq!(format!("{:b}", Sv4DpiView::new(&data, 6)), "xx1000");
+
+        let wide = Sv4DpiView::new(&data[..1], 40);
+        assert_eq!(format!("{:x}", wide), "xx123456Z8");
+        assert_ne!(wide, view);
+        assert_eq!(Sv4DpiView::new(&data, 4), Sv4DpiView::new(&data[..1], 4));
+
+        // formatters agree on digits with both x and z bits
+        let word = Sv4State::<u32>::from_dpi(&data[..1])[0];
+        let view = Sv4DpiView::new(&data[..1], 32);
+        assert_eq!(format!("{:x}", view), format!("{:x}", word));
+        assert_eq!(format!("{:x}", view), format!("{}", word));
+    }
+}
@@ -0,0 +1,159 @@
+//! BCD and sign-magnitude interpretations
+
+use crate::Sv4State;
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+
+/// Error of interpreting a value by an encoding
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EncodingError {
+    /// The value has x/z bits
+    Unknown,
+    /// A BCD digit is larger than 9
+    InvalidDigit { position: u32, digit: u8 },
+    /// The value doesn't fit in the width
+    Overflow,
+}
+
+impl std::fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodingError::Unknown => write!(f, "value contains x/z"),
+            EncodingError::InvalidDigit { position, digit } => {
+                write!(f, "invalid BCD digit {} at digit {}", digit, position)
+            }
+            EncodingError::Overflow => write!(f, "value overflows the width"),
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Interpret the lower `digits` nibbles as a BCD number
+    pub fn to_bcd(&self, digits: u32) -> Result<u128, EncodingError> {
+        let width = (digits * 4).min(T::zero().count_zeros());
+        let value = self.zero_extend(width);
+        if !value.is_known() {
+            return Err(EncodingError::Unknown);
+        }
+        let mut ret = 0u128;
+        for position in (0..width.div_ceil(4)).rev() {
+            let digit = ((value.v >> (position as usize * 4)) & Sv4State::<T>::width_mask(4))
+                .to_u8()
+                .unwrap_or(0);
+            if digit > 9 {
+                return Err(EncodingError::InvalidDigit { position, digit });
+            }
+            ret = ret * 10 + digit as u128;
+        }
+        Ok(ret)
+    }
+
+    /// Interpret the lower `width` bits as a sign-magnitude number
+    ///
+    /// The most significant bit is the sign, and negative zero is 0.
+    pub fn to_sign_magnitude(&self, width: u32) -> Result<i128, EncodingError> {
+        let width = width.clamp(1, T::zero().count_zeros().min(128));
+        let value = self.zero_extend(width);
+        if !value.is_known() {
+            return Err(EncodingError::Unknown);
+        }
+        let sign = T::one() << (width as usize - 1);
+        let magnitude = (value.v & !sign).to_i128().ok_or(EncodingError::Overflow)?;
+        if value.v & sign != T::zero() {
+            Ok(-magnitude)
+        } else {
+            Ok(magnitude)
+        }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Encode `value` as a BCD number of `digits` nibbles
+    pub fn from_bcd(value: u128, digits: u32) -> Result<Self, EncodingError> {
+        if digits * 4 > T::zero().count_zeros() {
+            return Err(EncodingError::Overflow);
+        }
+        let mut ret = T::zero();
+        let mut rest = value;
+        for position in 0..digits {
+            let digit = T::from_u128(rest % 10).ok_or(EncodingError::Overflow)?;
+            ret = ret | (digit << (position as usize * 4));
+            rest /= 10;
+        }
+        if rest != 0 {
+            return Err(EncodingError::Overflow);
+        }
+        Ok(Sv4State::known(ret))
+    }
+
+    /// Encode `value` as a sign-magnitude number of `width` bits
+    pub fn from_sign_magnitude(value: i128, width: u32) -> Result<Self, EncodingError> {
+        if width == 0 || width > T::zero().count_zeros() {
+            return Err(EncodingError::Overflow);
+        }
+        let magnitude = value.unsigned_abs();
+        if width <= 128 && magnitude >> (width - 1) != 0 {
+            return Err(EncodingError::Overflow);
+        }
+        let mut ret = T::from_u128(magnitude).ok_or(EncodingError::Overflow)?;
+        if value < 0 {
+            ret = ret | (T::one() << (width as usize - 1));
+        }
+        Ok(Sv4State::known(ret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bcd() {
+        let sv = Sv4State::<u16>::known(0x1290);
+
+        assert_eq!(sv.to_bcd(4), Ok(1290));
+        assert_eq!(sv.to_bcd(2), Ok(90));
+        assert_eq!(
+            Sv4State::<u16>::known(0x1a90).to_bcd(4),
+            Err(EncodingError::InvalidDigit {
+                position: 2,
+                digit: 10
+            })
+        );
+        assert_eq!(
+            Sv4State::<u16>::new(0x1290, 0, 0x1000).to_bcd(4),
+            Err(EncodingError::Unknown)
+        );
+        assert_eq!(Sv4State::<u16>::new(0x1290, 0, 0x1000).to_bcd(3), Ok(290));
+        assert_eq!(Sv4State::<u16>::from_bcd(1290, 4), Ok(sv));
+        assert_eq!(
+            Sv4State::<u16>::from_bcd(12345, 4),
+            Err(EncodingError::Overflow)
+        );
+    }
+
+    #[test]
+    fn sign_magnitude() {
+        assert_eq!(Sv4State::<u8>::known(0x85).to_sign_magnitude(8), Ok(-5));
+        assert_eq!(Sv4State::<u8>::known(0x0d).to_sign_magnitude(4), Ok(-5));
+        assert_eq!(Sv4State::<u8>::known(0x08).to_sign_magnitude(4), Ok(0));
+        assert_eq!(
+            Sv4State::<u8>::all_z().to_sign_magnitude(8),
+            Err(EncodingError::Unknown)
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_sign_magnitude(-5, 4),
+            Ok(Sv4State::known(0x0d))
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_sign_magnitude(8, 4),
+            Err(EncodingError::Overflow)
+        );
+        assert_eq!(
+            Sv4State::<u128>::known(1 << 127 | 3).to_sign_magnitude(128),
+            Ok(-3)
+        );
+    }
+}
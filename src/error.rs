@@ -0,0 +1,34 @@
+//! Error of fallible conversions
+
+/// Error of converting DPI buffers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The buffer of `len` words doesn't split into `payload_width`-bit values
+    BadLength { len: usize, payload_width: u32 },
+    /// The payload width is not a multiple of 8, so values don't split into
+    /// bytes of DPI buffers
+    UnsupportedType { payload_width: u32 },
+    /// The buffer has `found` words while the width needs `expected` words
+    WidthMismatch { expected: usize, found: usize },
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::BadLength { len, payload_width } => write!(
+                f,
+                "{} words don't split into {}-bit values",
+                len, payload_width
+            ),
+            Error::UnsupportedType { payload_width } => {
+                write!(f, "unsupported {}-bit payload type", payload_width)
+            }
+            Error::WidthMismatch { expected, found } => {
+                write!(f, "expected {} words, found {}", expected, found)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,126 @@
+//! Width conversion like SystemVerilog assignments
+
+use crate::logic::SvLogic;
+use crate::Sv4State;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::fmt::Debug;
+use num_traits::PrimInt;
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Extend the value of `width` bits by copying bit `width - 1`
+    ///
+    /// An x/z sign bit is extended as x/z.
+    pub fn sign_extend(&self, width: u32) -> Self {
+        self.resize(width, T::zero().count_zeros(), true)
+    }
+
+    /// Extend the value of `width` bits with 0
+    pub fn zero_extend(&self, width: u32) -> Self {
+        self.resize(width, T::zero().count_zeros(), false)
+    }
+
+    /// Convert the value of `from` bits to `to` bits
+    ///
+    /// Narrowing truncates the upper bits, and widening extends with the bit
+    /// `from - 1` if `signed`, or with 0 otherwise. Bits above `to` are 0.
+    pub fn resize(&self, from: u32, to: u32, signed: bool) -> Self {
+        let mut ret = [Sv4State::zeros()];
+        Self::resize_into(core::slice::from_ref(self), from, to, signed, &mut ret);
+        ret[0]
+    }
+
+    /// Write the multi-word value of `from` bits resized to `to` bits into
+    /// `out`, which is all 0
+    ///
+    /// Bits beyond `out` are dropped.
+    fn resize_into(values: &[Self], from: u32, to: u32, signed: bool, out: &mut [Self]) {
+        let payload_width = T::zero().count_zeros() as usize;
+        let (from, to) = (from as usize, to as usize);
+        let bit = |i: usize| {
+            values
+                .get(i / payload_width)
+                .map_or(SvLogic::Zero, |v| v.bit(i % payload_width))
+        };
+        let ext = if signed && from > 0 {
+            bit(from - 1)
+        } else {
+            SvLogic::Zero
+        };
+
+        for i in 0..to.min(out.len() * payload_width) {
+            let b = if i < from { bit(i) } else { ext };
+            out[i / payload_width].set_bit(i % payload_width, b);
+        }
+    }
+
+    /// Extend the multi-word value of `width` bits by copying bit `width - 1`
+    ///
+    /// `values[i]` has the bits from `i * payload_width`, and the result has
+    /// the same number of values.
+    #[cfg(feature = "alloc")]
+    pub fn sign_extend_vec(values: &[Self], width: u32) -> Vec<Self> {
+        let to = values.len() as u32 * T::zero().count_zeros();
+        Self::resize_vec(values, width, to, true)
+    }
+
+    /// Extend the multi-word value of `width` bits with 0
+    #[cfg(feature = "alloc")]
+    pub fn zero_extend_vec(values: &[Self], width: u32) -> Vec<Self> {
+        let to = values.len() as u32 * T::zero().count_zeros();
+        Self::resize_vec(values, width, to, false)
+    }
+
+    /// Convert the multi-word value of `from` bits to `to` bits
+    ///
+    /// The result has `ceil(to / payload_width)` values, and bits beyond
+    /// `values` are 0.
+    #[cfg(feature = "alloc")]
+    pub fn resize_vec(values: &[Self], from: u32, to: u32, signed: bool) -> Vec<Self> {
+        let payload_width = T::zero().count_zeros() as usize;
+        let mut ret = vec![Sv4State::zeros(); (to as usize).div_ceil(payload_width)];
+        Self::resize_into(values, from, to, signed, &mut ret);
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn extend() {
+        assert_eq!(sv("8'b0000_1010").sign_extend(4), sv("8'b1111_1010"));
+        assert_eq!(sv("8'b0000_x010").sign_extend(4), sv("8'bxxxx_x010"));
+        assert_eq!(sv("8'b0000_z010").sign_extend(4), sv("8'bzzzz_z010"));
+        assert_eq!(sv("8'b1111_1010").zero_extend(4), sv("8'b0000_1010"));
+        assert_eq!(sv("8'b1111_1010").resize(8, 3, true), sv("8'b0000_0010"));
+        assert_eq!(sv("8'b1111_1010").resize(2, 6, true), sv("8'b0011_1110"));
+    }
+
+    #[test]
+    fn extend_vec() {
+        let values = [sv("8'b1010_0101"), sv("8'b0000_00x1")];
+
+        assert_eq!(
+            Sv4State::sign_extend_vec(&values, 10),
+            vec![sv("8'b1010_0101"), sv("8'bxxxx_xxx1")]
+        );
+        assert_eq!(
+            Sv4State::zero_extend_vec(&values, 9),
+            vec![sv("8'b1010_0101"), sv("8'b0000_0001")]
+        );
+        assert_eq!(
+            Sv4State::resize_vec(&values, 10, 20, true),
+            vec![sv("8'b1010_0101"), sv("8'bxxxx_xxx1"), sv("8'b0000_xxxx")]
+        );
+        assert_eq!(
+            Sv4State::resize_vec(&values, 10, 4, true),
+            vec![sv("8'b0000_0101")]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Named bit fields of a wide DPI argument
+
+use crate::{read_dpi_bits, svLogicVecVal, write_dpi_bits, Sv4State};
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+
+/// Bit field placed at `lsb` with `width` bits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub lsb: usize,
+    pub width: u32,
+}
+
+/// Layout of named bit fields in a DPI argument
+///
+/// Each field is decoded into one [`Sv4State`], so the field width should not
+/// exceed the payload width; upper bits of wider fields are dropped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldMap {
+    fields: Vec<Field>,
+}
+
+impl FieldMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field
+    pub fn field(mut self, name: &str, lsb: usize, width: u32) -> Self {
+        self.fields.push(Field {
+            name: String::from(name),
+            lsb,
+            width,
+        });
+        self
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Index of the field named `name`
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.name == name)
+    }
+
+    /// Decode all fields from DPI canonical representation
+    ///
+    /// Bits beyond `data` are decoded as x.
+    pub fn decode<T: Copy + Debug + PrimInt + FromPrimitive>(
+        &self,
+        data: &[svLogicVecVal],
+    ) -> FieldValues<'_, T> {
+        let values = self
+            .fields
+            .iter()
+            .map(|f| read_dpi_bits(data, f.lsb, f.width))
+            .collect();
+        FieldValues { map: self, values }
+    }
+
+    /// Encode field values given in field order into DPI canonical representation
+    ///
+    /// Bits not covered by any field are left unchanged.
+    pub fn encode<T: Copy + Debug + PrimInt>(
+        &self,
+        values: &[Sv4State<T>],
+        data: &mut [svLogicVecVal],
+    ) {
+        for (f, value) in self.fields.iter().zip(values) {
+            write_dpi_bits(data, f.lsb, f.width, value);
+        }
+    }
+}
+
+/// Field values decoded by [`FieldMap::decode`]
+#[derive(Clone, Debug)]
+pub struct FieldValues<'a, T: Copy + Debug> {
+    map: &'a FieldMap,
+    values: Vec<Sv4State<T>>,
+}
+
+impl<'a, T: Copy + Debug + PrimInt> FieldValues<'a, T> {
+    /// Value of the field named `name`
+    pub fn get(&self, name: &str) -> Option<Sv4State<T>> {
+        self.map.index_of(name).map(|i| self.values[i])
+    }
+
+    /// Replace the value of the field named `name`
+    ///
+    /// Returns `false` if there is no such field.
+    pub fn set(&mut self, name: &str, value: Sv4State<T>) -> bool {
+        match self.map.index_of(name) {
+            Some(i) => {
+                self.values[i] = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Values in field order
+    pub fn values(&self) -> &[Sv4State<T>] {
+        &self.values
+    }
+
+    /// Pairs of field name and value in field order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Sv4State<T>)> + '_ {
+        self.map
+            .fields
+            .iter()
+            .zip(&self.values)
+            .map(|(f, v)| (f.name.as_str(), *v))
+    }
+
+    /// Encode into DPI canonical representation
+    pub fn encode(&self, data: &mut [svLogicVecVal]) {
+        self.map.encode(&self.values, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_encode() {
+        let buf = [
+            svLogicVecVal {
+                aval: 0x01234567,
+                bval: 0x00000000,
+            },
+            svLogicVecVal {
+                aval: 0x89abcdef,
+                bval: 0xffff0000,
+            },
+        ];
+        let map = FieldMap::new()
+            .field("low", 0, 4)
+            .field("cross", 28, 8)
+            .field("high", 48, 16);
+        let values = map.decode::<u16>(&buf);
+
+        assert_eq!(values.get("low"), Some(Sv4State::known(0x7)));
+        assert_eq!(values.get("cross"), Some(Sv4State::known(0xf0)));
+        assert_eq!(values.get("high"), Some(Sv4State::new(0, 0x7654, 0x89ab)));
+        assert_eq!(values.get("none"), None);
+
+        let mut out = [
+            svLogicVecVal { aval: 0, bval: 0 },
+            svLogicVecVal { aval: 0, bval: 0 },
+        ];
+        values.encode(&mut out);
+        assert_eq!(out[0].aval, 0x00000007);
+        assert_eq!(out[0].bval, 0x00000000);
+        assert_eq!(out[1].aval, 0x89ab000f);
+        assert_eq!(out[1].bval, 0xffff0000);
+    }
+}
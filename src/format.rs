@@ -0,0 +1,381 @@
+//! Configurable formatting profiles
+//!
+//! `Display` of [`Sv4State`] and reports of checkers follow the profile of the
+//! current thread, which can be changed by [`set_thread_profile`] or
+//! temporarily by [`with_profile`]. [`Sv4State::display_with`] formats with
+//! an explicit profile.
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::cell::RefCell;
+use std::fmt::{Debug, Display, Write};
+
+/// Radix of formatted digits
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Radix {
+    Binary,
+    Octal,
+    Hex,
+}
+
+/// Casing of x/z digits
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnknownCase {
+    /// `x`/`z` for digits whose bits are all unknown, and `X`/`Z` for digits
+    /// whose bits are partially unknown as `$display`
+    Display,
+    /// Always `x`/`z`
+    Lower,
+    /// Always `X`/`Z`
+    Upper,
+}
+
+/// Characters of x/z digits
+///
+/// They are used by `Display`, `Binary` and `LowerHex` of [`Sv4State`] and
+/// the other values of this crate, but not by file formats with a fixed
+/// grammar such as VCD, memory images and golden strings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnknownChars {
+    /// Digit whose bits are all x
+    pub x: char,
+    /// Digit whose bits are partially x
+    pub x_partial: char,
+    /// Digit whose bits are all z
+    pub z: char,
+    /// Digit whose bits are partially z
+    pub z_partial: char,
+}
+
+impl Default for UnknownChars {
+    fn default() -> Self {
+        UnknownChars {
+            x: 'x',
+            x_partial: 'X',
+            z: 'z',
+            z_partial: 'Z',
+        }
+    }
+}
+
+impl UnknownChars {
+    /// Character substituted for the canonical digit `c` of `x`, `X`, `z` or
+    /// `Z`
+    pub fn get(&self, c: char) -> char {
+        match c {
+            'x' => self.x,
+            'X' => self.x_partial,
+            'z' => self.z,
+            'Z' => self.z_partial,
+            _ => c,
+        }
+    }
+}
+
+/// Formatting profile
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatProfile {
+    pub radix: Radix,
+    /// Insert `_` every `grouping` digits from the least significant digit
+    /// (`0` disables grouping)
+    pub grouping: usize,
+    /// Prefix with the width and the base like `8'h`
+    pub show_width: bool,
+    /// Choice between the characters of [`FormatProfile::unknown_chars`]
+    /// for all and partially unknown digits
+    pub unknown_case: UnknownCase,
+    pub unknown_chars: UnknownChars,
+    /// Highlight x/z digits by ANSI escape sequences
+    pub color: bool,
+}
+
+impl Default for FormatProfile {
+    fn default() -> Self {
+        FormatProfile {
+            radix: Radix::Hex,
+            grouping: 0,
+            show_width: false,
+            unknown_case: UnknownCase::Display,
+            unknown_chars: UnknownChars::default(),
+            color: false,
+        }
+    }
+}
+
+thread_local! {
+    static PROFILE: RefCell<FormatProfile> = RefCell::new(FormatProfile::default());
+}
+
+/// Formatting profile of the current thread
+pub fn thread_profile() -> FormatProfile {
+    PROFILE.with(|p| p.borrow().clone())
+}
+
+/// Set the formatting profile of the current thread
+pub fn set_thread_profile(profile: FormatProfile) {
+    PROFILE.with(|p| *p.borrow_mut() = profile);
+}
+
+/// Characters of x/z digits of the profile of the current thread
+///
+/// Formatters fetch them once per call rather than per digit.
+pub(crate) fn unknown_chars() -> UnknownChars {
+    PROFILE.with(|p| p.borrow().unknown_chars)
+}
+
+/// Restores the replaced profile even if the closure panics
+struct ProfileGuard(FormatProfile);
+
+impl Drop for ProfileGuard {
+    fn drop(&mut self) {
+        let prev = std::mem::take(&mut self.0);
+        PROFILE.with(|p| *p.borrow_mut() = prev);
+    }
+}
+
+/// Run `f` with the formatting profile of the current thread replaced
+pub fn with_profile<R, F: FnOnce() -> R>(profile: FormatProfile, f: F) -> R {
+    let _guard = ProfileGuard(PROFILE.with(|p| p.replace(profile)));
+    f()
+}
+
+const COLOR_X: &str = "\x1b[31m";
+const COLOR_Z: &str = "\x1b[33m";
+const COLOR_RESET: &str = "\x1b[0m";
+
+/// [`Sv4State`] formatted with an explicit profile
+pub struct Formatted<'a, T: Copy + Debug> {
+    value: &'a Sv4State<T>,
+    profile: &'a FormatProfile,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Format with `profile` instead of the profile of the current thread
+    pub fn display_with<'a>(&'a self, profile: &'a FormatProfile) -> Formatted<'a, T> {
+        Formatted {
+            value: self,
+            profile,
+        }
+    }
+}
+
+impl<'a, T: Copy + Debug + PrimInt> Display for Formatted<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_profile(f, self.value, self.profile)
+    }
+}
+
+/// Format with the profile of the current thread
+impl<T: Copy + Debug + PrimInt> Display for Sv4State<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        PROFILE.with(|p| write_profile(f, self, &p.borrow()))
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Decimal string of the lower `width` bits like `$display("%0d")`
+    ///
+    /// Unknown values are `x`/`z` if all bits are x/z, and `X`/`Z` if some bits
+    /// are x/z, classified as a single digit of `LowerHex`. If `signed`, the
+    /// value is interpreted as two's complement.
+    pub fn to_dec_string(&self, width: u32, signed: bool) -> String {
+        let width = width.clamp(1, T::zero().count_zeros());
+        let value = self.normalize().zero_extend(width);
+        let mask = Sv4State::<T>::width_mask(width);
+        if let Some(c) = crate::unknown_digit(value.z, value.x, mask) {
+            String::from(c)
+        } else if signed {
+            value.as_signed(width).to_i128().map_or_else(
+                || {
+                    // wider than 128 bits
+                    if value.v >> (width as usize - 1) == T::zero() {
+                        wide_dec_string(value.v)
+                    } else {
+                        format!("-{}", wide_dec_string((!value.v + T::one()) & mask))
+                    }
+                },
+                |v| v.to_string(),
+            )
+        } else {
+            value
+                .v
+                .to_u128()
+                .map_or_else(|| wide_dec_string(value.v), |v| v.to_string())
+        }
+    }
+}
+
+/// Decimal string of a non-negative value wider than 128 bits
+fn wide_dec_string<T: Copy + Debug + PrimInt>(mut v: T) -> String {
+    if v < T::zero() {
+        return String::from("?");
+    }
+    let ten = (T::one() << 3) | (T::one() << 1);
+    let mut digits = Vec::new();
+    loop {
+        let d = (v % ten).to_u32().unwrap_or(0);
+        digits.push(std::char::from_digit(d, 10).unwrap_or('?'));
+        v = v / ten;
+        if v == T::zero() {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+fn write_profile<T: Copy + Debug + PrimInt>(
+    f: &mut std::fmt::Formatter<'_>,
+    value: &Sv4State<T>,
+    profile: &FormatProfile,
+) -> std::fmt::Result {
+    let value = value.normalize();
+    let payload_width = T::zero().count_zeros() as usize;
+    let (bits, base) = match profile.radix {
+        Radix::Binary => (1, 'b'),
+        Radix::Octal => (3, 'o'),
+        Radix::Hex => (4, 'h'),
+    };
+
+    if profile.show_width {
+        write!(f, "{}'{}", payload_width, base)?;
+    }
+
+    let digits = payload_width.div_ceil(bits);
+    for i in (0..digits).rev() {
+        let shift = i * bits;
+        let n = bits.min(payload_width - shift);
+        let mask = Sv4State::<T>::width_mask(n as u32);
+        let v = (value.v >> shift) & mask;
+        let z = (value.z >> shift) & mask;
+        let x = (value.x >> shift) & mask;
+
+        match crate::unknown_digit(z, x, mask) {
+            Some(c) => {
+                let color = if c.eq_ignore_ascii_case(&'z') {
+                    COLOR_Z
+                } else {
+                    COLOR_X
+                };
+                let c = match profile.unknown_case {
+                    UnknownCase::Display => c,
+                    UnknownCase::Lower => c.to_ascii_lowercase(),
+                    UnknownCase::Upper => c.to_ascii_uppercase(),
+                };
+                let c = profile.unknown_chars.get(c);
+                if profile.color {
+                    write!(f, "{}{}{}", color, c, COLOR_RESET)?;
+                } else {
+                    f.write_char(c)?;
+                }
+            }
+            None => {
+                let d = v.to_u32().unwrap_or(0);
+                f.write_char(std::char::from_digit(d, 16).unwrap_or('?'))?;
+            }
+        }
+
+        if profile.grouping != 0 && i != 0 && i % profile.grouping == 0 {
+            f.write_char('_')?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let sv: Sv4State<u16> = "16'b0000_1010_x0xx_01z1".parse().unwrap();
+
+        assert_eq!(format!("{}", sv), "0aXZ");
+        let profile = FormatProfile {
+            radix: Radix::Binary,
+            grouping: 4,
+            show_width: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format!("{}", sv.display_with(&profile)),
+            "16'b0000_1010_x0xx_01z1"
+        );
+        let profile = FormatProfile {
+            radix: Radix::Octal,
+            unknown_case: UnknownCase::Lower,
+            ..Default::default()
+        };
+        assert_eq!(format!("{}", sv.display_with(&profile)), "005xxz");
+        let profile = FormatProfile {
+            unknown_case: UnknownCase::Upper,
+            color: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format!("{}", sv.display_with(&profile)),
+            "0a\x1b[31mX\x1b[0m\x1b[33mZ\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn unknown_chars() {
+        let sv: Sv4State<u8> = "8'b1x0x_zzzz".parse().unwrap();
+        let profile = FormatProfile {
+            unknown_chars: UnknownChars {
+                x: '?',
+                x_partial: '?',
+                z: '#',
+                z_partial: '#',
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(format!("{}", sv.display_with(&profile)), "?#");
+        with_profile(profile, || {
+            assert_eq!(format!("{}", sv), "?#");
+            assert_eq!(format!("{:b}", sv), "1?0?####");
+            assert_eq!(format!("{:x}", sv), "?#");
+            assert_eq!(format!("{:b}", sv.view(2..6)), "0?##");
+            assert_eq!(format!("{:b}", crate::Sv4Vec::from_value(sv, 6)), "0?####");
+        });
+        assert_eq!(format!("{:b}", sv), "1x0xzzzz");
+    }
+
+    #[test]
+    fn dec_string() {
+        let sv = Sv4State::<u8>::known(0xfa);
+
+        assert_eq!(sv.to_dec_string(8, false), "250");
+        assert_eq!(sv.to_dec_string(8, true), "-6");
+        assert_eq!(sv.to_dec_string(4, true), "-6");
+        assert_eq!(sv.to_dec_string(3, true), "2");
+        assert_eq!(Sv4State::<u8>::all_x().to_dec_string(8, false), "x");
+        assert_eq!(Sv4State::<u8>::all_z_width(4).to_dec_string(4, true), "z");
+        let sv: Sv4State<u8> = "8'b0000_z01x".parse().unwrap();
+        assert_eq!(sv.to_dec_string(8, false), "Z");
+        assert_eq!(sv.to_dec_string(4, false), format!("{:x}", sv)[1..]);
+        assert_eq!(sv.to_dec_string(3, false), "X");
+        let sv: Sv4State<u8> = "8'b0000_z010".parse().unwrap();
+        assert_eq!(sv.to_dec_string(8, false), "Z");
+        assert_eq!(sv.to_dec_string(3, false), "2");
+    }
+
+    #[test]
+    fn thread_profile() {
+        let sv = Sv4State::<u8>::known(0xa5);
+        let profile = FormatProfile {
+            radix: Radix::Binary,
+            grouping: 4,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            with_profile(profile.clone(), || format!("{}", sv)),
+            "1010_0101"
+        );
+        assert_eq!(format!("{}", sv), "a5");
+        let ret = std::panic::catch_unwind(|| with_profile(profile, || panic!("boom")));
+        assert!(ret.is_err());
+        assert_eq!(format!("{}", sv), "a5");
+    }
+}
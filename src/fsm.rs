@@ -0,0 +1,164 @@
+//! Table-driven FSM checker with 4-state inputs
+
+use crate::pattern::Sv4Pattern;
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Transition of [`FsmChecker`]
+#[derive(Clone, Debug)]
+pub struct Transition<S, T: Copy + Debug> {
+    pub from: S,
+    pub guard: Sv4Pattern<T>,
+    pub to: S,
+}
+
+/// Error reported by [`FsmChecker`]
+#[derive(Clone, Debug)]
+pub enum FsmError<S, T: Copy + Debug> {
+    /// Bits guarded by transitions from `state` are x or z
+    UnknownInput { state: S, input: Sv4State<T> },
+    /// No transition from `state` matches in strict mode
+    NoTransition { state: S, input: Sv4State<T> },
+}
+
+impl<S: Debug, T: Copy + Debug + PrimInt> std::fmt::Display for FsmError<S, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FsmError::UnknownInput { state, input } => write!(
+                f,
+                "guarded input contains x/z: state {:?}, input {}",
+                state, input
+            ),
+            FsmError::NoTransition { state, input } => {
+                write!(f, "no transition: state {:?}, input {}", state, input)
+            }
+        }
+    }
+}
+
+impl<S: Debug, T: Copy + Debug + PrimInt> std::error::Error for FsmError<S, T> {}
+
+/// FSM checker whose transitions are guarded by [`Sv4Pattern`]s
+///
+/// Transitions are evaluated in the order they were added, and the first
+/// matching one is taken. If x/z appears on any bit guarded by the
+/// transitions from the current state, [`FsmError::UnknownInput`] is reported
+/// and the state is kept.
+#[derive(Clone, Debug)]
+pub struct FsmChecker<S, T: Copy + Debug> {
+    state: S,
+    transitions: Vec<Transition<S, T>>,
+    strict: bool,
+}
+
+impl<S: Clone + PartialEq + Debug, T: Copy + Debug + PrimInt> FsmChecker<S, T> {
+    pub fn new(initial: S) -> Self {
+        FsmChecker {
+            state: initial,
+            transitions: Vec::new(),
+            strict: false,
+        }
+    }
+
+    /// Add a transition
+    pub fn transition(mut self, from: S, guard: Sv4Pattern<T>, to: S) -> Self {
+        self.transitions.push(Transition { from, guard, to });
+        self
+    }
+
+    /// Report [`FsmError::NoTransition`] instead of keeping the state when
+    /// no transition matches
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Current state
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Evaluate one input sample
+    pub fn step(&mut self, input: &Sv4State<T>) -> Result<&S, FsmError<S, T>> {
+        let mut guarded = T::zero();
+        let mut next = None;
+        for t in self.transitions.iter().filter(|t| t.from == self.state) {
+            guarded = guarded | t.guard.care;
+            if next.is_none() && t.guard.matches(input) {
+                next = Some(t.to.clone());
+            }
+        }
+
+        if (input.z | input.x) & guarded != T::zero() {
+            return Err(FsmError::UnknownInput {
+                state: self.state.clone(),
+                input: *input,
+            });
+        }
+
+        match next {
+            Some(next) => self.state = next,
+            None if self.strict => {
+                return Err(FsmError::NoTransition {
+                    state: self.state.clone(),
+                    input: *input,
+                })
+            }
+            None => (),
+        }
+        Ok(&self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum State {
+        Idle,
+        Busy,
+    }
+
+    // bit 0: req, bit 1: done
+    fn checker() -> FsmChecker<State, u8> {
+        FsmChecker::new(State::Idle)
+            .transition(State::Idle, Sv4Pattern::new(0b01, 0b01), State::Busy)
+            .transition(State::Busy, Sv4Pattern::new(0b10, 0b10), State::Idle)
+    }
+
+    #[test]
+    fn step() {
+        let mut fsm = checker();
+
+        assert_eq!(fsm.step(&Sv4State::known(0b00)).unwrap(), &State::Idle);
+        assert_eq!(fsm.step(&Sv4State::known(0b01)).unwrap(), &State::Busy);
+        // only done is guarded in Busy, so x on req is allowed
+        assert_eq!(
+            fsm.step(&Sv4State::new(0b00, 0, 0b01)).unwrap(),
+            &State::Busy
+        );
+        assert_eq!(fsm.step(&Sv4State::known(0b10)).unwrap(), &State::Idle);
+    }
+
+    #[test]
+    fn unknown_input() {
+        let mut fsm = checker();
+
+        assert!(matches!(
+            fsm.step(&Sv4State::new(0b00, 0, 0b01)),
+            Err(FsmError::UnknownInput {
+                state: State::Idle,
+                ..
+            })
+        ));
+        assert_eq!(fsm.state(), &State::Idle);
+
+        let mut fsm = checker().strict(true);
+        assert!(matches!(
+            fsm.step(&Sv4State::known(0b00)),
+            Err(FsmError::NoTransition { .. })
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+//! Value changes for the `fst-writer` crate (`fst` feature)
+
+use crate::Sv4State;
+use fst_writer::{FstBodyWriter, FstSignalId, FstWriteError};
+use num_traits::PrimInt;
+use std::fmt::Debug;
+use std::io::{Seek, Write};
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// `fst-writer` value of the lower `width` bits
+    ///
+    /// The value is `width` characters of `0`, `1`, `x` and `z` from the most
+    /// significant bit, as [`FstBodyWriter::signal_change`] accepts.
+    pub fn to_fst_value(&self, width: u32) -> Vec<u8> {
+        let width = width.min(T::zero().count_zeros());
+        (0..width as usize)
+            .rev()
+            .map(|i| {
+                let b = T::one() << i;
+                if self.x & b != T::zero() {
+                    b'x'
+                } else if self.z & b != T::zero() {
+                    b'z'
+                } else if self.v & b != T::zero() {
+                    b'1'
+                } else {
+                    b'0'
+                }
+            })
+            .collect()
+    }
+}
+
+/// Write a value change of `signal` declared as a `width`-bit vector
+pub fn fst_change<T: Copy + Debug + PrimInt, W: Write + Seek>(
+    writer: &mut FstBodyWriter<W>,
+    signal: FstSignalId,
+    value: &Sv4State<T>,
+    width: u32,
+) -> Result<(), FstWriteError> {
+    writer.signal_change(signal, &value.to_fst_value(width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fst_writer::{
+        open_fst, FstFileType, FstInfo, FstScopeType, FstSignalType, FstVarDirection, FstVarType,
+    };
+
+    #[test]
+    fn fst() {
+        let sv: Sv4State<u8> = "8'b0000_1xz0".parse().unwrap();
+        assert_eq!(sv.to_fst_value(4), b"1xz0".to_vec());
+        assert_eq!(sv.to_fst_value(16), b"00001xz0".to_vec());
+
+        let path = std::env::temp_dir().join(format!("sv4state_fst_{}.fst", std::process::id()));
+        let info = FstInfo {
+            start_time: 0,
+            timescale_exponent: -9,
+            version: String::from("sv4state"),
+            date: String::from("today"),
+            file_type: FstFileType::Verilog,
+        };
+        let mut header = open_fst(&path, &info).unwrap();
+        header.scope("top", "", FstScopeType::Module).unwrap();
+        let id = header
+            .var(
+                "data",
+                FstSignalType::bit_vec(4),
+                FstVarType::Wire,
+                FstVarDirection::Implicit,
+                None,
+            )
+            .unwrap();
+        header.up_scope().unwrap();
+        let mut body = header.finish().unwrap();
+        body.time_change(0).unwrap();
+        fst_change(&mut body, id, &sv, 4).unwrap();
+        body.time_change(10).unwrap();
+        fst_change(&mut body, id, &Sv4State::<u8>::all_x(), 4).unwrap();
+        body.finish().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
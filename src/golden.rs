@@ -0,0 +1,94 @@
+//! Stable formatting for golden-file comparison
+//!
+//! Unlike `Display`, `Binary` and `LowerHex`, the output of a given
+//! [`GoldenVersion`] never changes, and is not affected by formatting
+//! profiles.
+
+use crate::literal::ParseError;
+use crate::Sv4State;
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+
+/// Version of the golden output grammar
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GoldenVersion {
+    /// `<width>'b<digits>`
+    ///
+    /// `<width>` is the payload width in decimal, and `<digits>` are exactly
+    /// `<width>` characters of `0`, `1`, `x` and `z` from the most significant
+    /// bit without separators. Overlapping v/z/x bits are normalized.
+    V1,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Format by the golden output grammar `version`
+    pub fn to_golden_string(&self, version: GoldenVersion) -> String {
+        match version {
+            GoldenVersion::V1 => {
+                let value = self.normalize();
+                let payload_width = T::zero().count_zeros() as usize;
+                let mut ret = format!("{}'b", payload_width);
+                for i in (0..payload_width).rev() {
+                    let bit = T::one() << i;
+                    let c = if value.x & bit != T::zero() {
+                        'x'
+                    } else if value.z & bit != T::zero() {
+                        'z'
+                    } else if value.v & bit != T::zero() {
+                        '1'
+                    } else {
+                        '0'
+                    };
+                    ret.push(c);
+                }
+                ret
+            }
+        }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Parse a string formatted by the golden output grammar `version`
+    pub fn from_golden_string(s: &str, version: GoldenVersion) -> Result<Self, ParseError> {
+        match version {
+            GoldenVersion::V1 => {
+                let payload_width = T::zero().count_zeros() as usize;
+                let prefix = format!("{}'b", payload_width);
+                let digits = s.strip_prefix(&prefix).ok_or(ParseError::InvalidSize)?;
+                if digits.len() != payload_width {
+                    return Err(ParseError::Overflow);
+                }
+                if let Some(c) = digits.chars().find(|c| !"01xz".contains(*c)) {
+                    return Err(ParseError::InvalidDigit(c));
+                }
+                s.parse()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_v1() {
+        let sv = Sv4State::<u16>::new(0x8421, 0x0f00, 0x00f0);
+        let s = sv.to_golden_string(GoldenVersion::V1);
+
+        assert_eq!(s, "16'b1000zzzzxxxx0001");
+        assert_eq!(Sv4State::from_golden_string(&s, GoldenVersion::V1), Ok(sv));
+        assert_eq!(
+            Sv4State::<u8>::from_golden_string("8'b1", GoldenVersion::V1),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_golden_string("8'h00", GoldenVersion::V1),
+            Err(ParseError::InvalidSize)
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_golden_string("8'b0000000X", GoldenVersion::V1),
+            Err(ParseError::InvalidDigit('X'))
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! X-injection variants for X-robustness testing
+//!
+//! Checkers and reference models fed by the variants should report x rather
+//! than a known value wherever an x bit affects the result, which detects
+//! X-optimism like `if` on an x condition.
+
+use crate::Sv4State;
+use core::fmt::Debug;
+use num_traits::PrimInt;
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Variants with bit `i` x for each `i` in the lower `width` bits
+    ///
+    /// `width` is clamped to the payload width.
+    pub fn x_injections(&self, width: u32) -> XInjections<T> {
+        XInjections {
+            value: self.normalize(),
+            bit: 0,
+            width: width.min(T::zero().count_zeros()),
+        }
+    }
+
+    /// `count` variants with random subsets of the lower `width` bits x
+    ///
+    /// Each bit is x with probability 1/2, and at least one bit is x if
+    /// `width` is not 0. The variants are the same for the same `seed`.
+    pub fn random_x_injections(&self, width: u32, seed: u64, count: usize) -> RandomXInjections<T> {
+        RandomXInjections {
+            value: self.normalize(),
+            mask: Self::width_mask(width.min(T::zero().count_zeros())),
+            state: seed,
+            count,
+        }
+    }
+
+    /// Value with the bits of `mask` x
+    pub fn inject_x(&self, mask: T) -> Self {
+        let value = self.normalize();
+        Sv4State::new(value.v & !mask, value.z & !mask, value.x | mask)
+    }
+}
+
+/// Iterator of [`Sv4State::x_injections`] yielding the x bit and the variant
+#[derive(Clone, Debug)]
+pub struct XInjections<T: Copy + Debug> {
+    value: Sv4State<T>,
+    bit: u32,
+    width: u32,
+}
+
+impl<T: Copy + Debug + PrimInt> Iterator for XInjections<T> {
+    type Item = (u32, Sv4State<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bit >= self.width {
+            return None;
+        }
+        let bit = self.bit;
+        self.bit += 1;
+        Some((bit, self.value.inject_x(T::one() << bit as usize)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.width - self.bit) as usize;
+        (n, Some(n))
+    }
+}
+
+/// Iterator of [`Sv4State::random_x_injections`] yielding the x mask and the
+/// variant
+#[derive(Clone, Debug)]
+pub struct RandomXInjections<T: Copy + Debug> {
+    value: Sv4State<T>,
+    mask: T,
+    /// splitmix64 state
+    state: u64,
+    count: usize,
+}
+
+impl<T: Copy + Debug + PrimInt> RandomXInjections<T> {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_mask(&mut self) -> T {
+        let mut mask = T::zero();
+        let mut random = 0;
+        for i in 0..T::zero().count_zeros() as usize {
+            if i % 64 == 0 {
+                random = self.next_u64();
+            }
+            if (random >> (i % 64)) & 1 == 1 {
+                mask = mask | T::one() << i;
+            }
+        }
+        mask & self.mask
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Iterator for RandomXInjections<T> {
+    type Item = (T, Sv4State<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count == 0 {
+            return None;
+        }
+        self.count -= 1;
+        let mut mask = self.next_mask();
+        while mask == T::zero() && self.mask != T::zero() {
+            mask = self.next_mask();
+        }
+        Some((mask, self.value.inject_x(mask)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count, Some(self.count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inject() {
+        let value = Sv4State::<u8>::known(0b1010);
+        let variants: Vec<_> = value.x_injections(4).collect();
+        assert_eq!(variants.len(), 4);
+        assert_eq!(variants[1], (1, "8'b0000_10x0".parse().unwrap()));
+        assert_eq!(value.x_injections(12).count(), 8);
+        assert_eq!(
+            Sv4State::<u8>::all_z().inject_x(0x0f),
+            "8'bzzzz_xxxx".parse().unwrap()
+        );
+
+        let random: Vec<_> = value.random_x_injections(4, 1, 16).collect();
+        assert_eq!(random.len(), 16);
+        assert!(random
+            .iter()
+            .all(|(m, v)| *m != 0 && *m < 0x10 && *v == value.inject_x(*m)));
+        assert!(random.iter().any(|(m, _)| m.count_ones() > 1));
+        assert!(random
+            .iter()
+            .zip(value.random_x_injections(4, 1, 16))
+            .all(|(a, b)| *a == b));
+        assert_ne!(
+            random,
+            value.random_x_injections(4, 2, 16).collect::<Vec<_>>()
+        );
+        assert_eq!(value.random_x_injections(0, 1, 2).next(), Some((0, value)));
+    }
+}
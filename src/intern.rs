@@ -0,0 +1,96 @@
+//! Interning of repeated values
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
+
+/// Handle of an interned value
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Sv4Handle(u32);
+
+/// Interner of wide values given as slices of [`Sv4State`]
+///
+/// Equal values (by case equality) share one handle and one storage, so long
+/// captures of mostly idle buses keep only a few distinct values.
+#[derive(Clone, Debug, Default)]
+pub struct Sv4Interner<T: Copy + Debug> {
+    values: Vec<Rc<[Sv4State<T>]>>,
+    handles: HashMap<Rc<[Sv4State<T>]>, Sv4Handle>,
+    hits: usize,
+}
+
+impl<T: Copy + Debug + PrimInt + Hash> Sv4Interner<T> {
+    pub fn new() -> Self {
+        Sv4Interner {
+            values: Vec::new(),
+            handles: HashMap::new(),
+            hits: 0,
+        }
+    }
+
+    /// Handle of `value`, which is stored if it is new
+    pub fn intern(&mut self, value: &[Sv4State<T>]) -> Sv4Handle {
+        if let Some(h) = self.handles.get(value) {
+            self.hits += 1;
+            return *h;
+        }
+        let value: Rc<[Sv4State<T>]> = value.iter().map(|v| v.normalize()).collect();
+        let handle = Sv4Handle(self.values.len() as u32);
+        self.values.push(Rc::clone(&value));
+        self.handles.insert(value, handle);
+        handle
+    }
+
+    /// Value of `handle`
+    pub fn get(&self, handle: Sv4Handle) -> Option<&[Sv4State<T>]> {
+        self.values.get(handle.0 as usize).map(|v| &v[..])
+    }
+
+    /// Shared storage of the value of `handle`
+    pub fn get_shared(&self, handle: Sv4Handle) -> Option<Rc<[Sv4State<T>]>> {
+        self.values.get(handle.0 as usize).cloned()
+    }
+
+    /// Number of distinct values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Number of [`Sv4Interner::intern`] calls which found an existing value
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let mut interner = Sv4Interner::<u32>::new();
+        let idle = [Sv4State::all_x(), Sv4State::all_x()];
+
+        let a = interner.intern(&idle);
+        let b = interner.intern(&[Sv4State::known(1), Sv4State::zeros()]);
+        let c = interner.intern(&[Sv4State::new(1, 0, !0), Sv4State::all_x()]);
+
+        assert_eq!(a, c);
+        assert_ne!(a, b);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.hits(), 1);
+        assert_eq!(interner.get(a), Some(&idle[..]));
+        assert!(Rc::ptr_eq(
+            &interner.get_shared(a).unwrap(),
+            &interner.get_shared(c).unwrap()
+        ));
+        assert_eq!(interner.get(Sv4Handle(2)), None);
+    }
+}
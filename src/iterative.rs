@@ -0,0 +1,184 @@
+//! Iterative reference models of multi-cycle arithmetic units
+//!
+//! Each model returns the partial values after every iteration, so that the
+//! intermediate state of RTL can be checked cycle by cycle. Once an unknown
+//! bit affects a decision, the decided bit and the remainder become x, and so
+//! do all the following iterations.
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Partial values after an iteration
+#[derive(Copy, Clone, Debug)]
+pub struct IterStep<T: Copy + Debug> {
+    /// quotient or root bits decided so far, the latest at bit 0
+    pub partial: Sv4State<T>,
+    /// partial remainder
+    pub remainder: Sv4State<T>,
+}
+
+impl<T: Copy + Debug + PrimInt> PartialEq for IterStep<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial == other.partial && self.remainder == other.remainder
+    }
+}
+
+/// Restoring division of the lower `width` bits, one quotient bit per
+/// iteration from the most significant
+///
+/// The remainder is `width` bits. As a hardware divider, a zero divisor gives
+/// an all-ones quotient and the dividend as the remainder.
+pub fn restoring_div<T: Copy + Debug + PrimInt>(
+    dividend: Sv4State<T>,
+    divisor: Sv4State<T>,
+    width: u32,
+) -> Vec<IterStep<T>> {
+    let width = width.clamp(1, T::zero().count_zeros());
+    let mask = Sv4State::<T>::width_mask(width);
+    let all_x = Sv4State::new(T::zero(), T::zero(), mask);
+    let (dividend, divisor) = (dividend.normalize(), divisor.normalize());
+    let mut unknown = (divisor.z | divisor.x) & mask != T::zero();
+
+    let mut ret = Vec::new();
+    let mut q = Sv4State::known(T::zero());
+    let mut r = T::zero();
+    for i in (0..width as usize).rev() {
+        unknown |= ((dividend.z | dividend.x) >> i) & T::one() != T::zero();
+        if unknown {
+            q = Sv4State::new(q.v << 1, T::zero(), (q.x << 1 | T::one()) & mask);
+            ret.push(IterStep {
+                partial: q,
+                remainder: all_x,
+            });
+            continue;
+        }
+
+        let carry = r >> (width as usize - 1) != T::zero();
+        let shifted = ((r << 1) | ((dividend.v >> i) & T::one())) & mask;
+        let bit = carry || shifted >= divisor.v & mask;
+        r = if bit {
+            (shifted - (divisor.v & mask)) & mask
+        } else {
+            shifted
+        };
+        let bit = if bit { T::one() } else { T::zero() };
+        q = Sv4State::new(((q.v << 1) | bit) & mask, T::zero(), (q.x << 1) & mask);
+        ret.push(IterStep {
+            partial: q,
+            remainder: Sv4State::known(r),
+        });
+    }
+    ret
+}
+
+/// Non-restoring square root of the lower `width` bits, one root bit per
+/// iteration from the most significant
+///
+/// An odd `width` is extended by a zero bit. The last step is the final
+/// remainder correction, so there are `width / 2 + 1` steps (rounded up). The
+/// remainder is a two's complement value of `width` bits, which is negative
+/// before the correction when the last trial subtraction failed.
+pub fn non_restoring_sqrt<T: Copy + Debug + PrimInt>(
+    radicand: Sv4State<T>,
+    width: u32,
+) -> Vec<IterStep<T>> {
+    let width = width.clamp(1, T::zero().count_zeros());
+    let mask = Sv4State::<T>::width_mask(width);
+    let all_x = Sv4State::new(T::zero(), T::zero(), mask);
+    let radicand = radicand.normalize();
+    let unknown_bits = radicand.z | radicand.x;
+    let to_t = |x: i128| T::from(x as u128 & mask.to_u128().unwrap_or(0)).unwrap_or(mask);
+
+    let mut ret = Vec::new();
+    let mut unknown = false;
+    let mut q = Sv4State::known(T::zero());
+    let mut r = 0i128;
+    for i in (0..width.div_ceil(2) as usize).rev() {
+        unknown |= (unknown_bits >> (2 * i)) & T::from(3).unwrap_or(mask) != T::zero();
+        if unknown {
+            q = Sv4State::new(q.v << 1, T::zero(), (q.x << 1 | T::one()) & mask);
+            ret.push(IterStep {
+                partial: q,
+                remainder: all_x,
+            });
+            continue;
+        }
+
+        let pair = ((radicand.v >> (2 * i)).to_u128().unwrap_or(0) & 3) as i128;
+        let root = q.v.to_u128().unwrap_or(0) as i128;
+        r = if r >= 0 {
+            (r << 2 | pair) - (root << 2 | 1)
+        } else {
+            (r << 2 | pair) + (root << 2 | 3)
+        };
+        let bit = if r >= 0 { T::one() } else { T::zero() };
+        q = Sv4State::known((q.v << 1) | bit);
+        ret.push(IterStep {
+            partial: q,
+            remainder: Sv4State::known(to_t(r)),
+        });
+    }
+
+    let remainder = if unknown {
+        all_x
+    } else {
+        if r < 0 {
+            r += (q.v.to_u128().unwrap_or(0) as i128) << 1 | 1;
+        }
+        Sv4State::known(to_t(r))
+    };
+    ret.push(IterStep {
+        partial: q,
+        remainder,
+    });
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn div() {
+        let steps = restoring_div(sv("8'd13"), sv("8'd3"), 4);
+        let partial: Vec<_> = steps.iter().map(|s| s.partial).collect();
+        let remainder: Vec<_> = steps.iter().map(|s| s.remainder).collect();
+        assert_eq!(partial, [sv("8'd0"), sv("8'd1"), sv("8'd2"), sv("8'd4")]);
+        assert_eq!(remainder, [sv("8'd1"), sv("8'd0"), sv("8'd0"), sv("8'd1")]);
+
+        let steps = restoring_div(sv("8'd255"), sv("8'd16"), 8);
+        assert_eq!(steps[7].partial, sv("8'd15"));
+        assert_eq!(steps[7].remainder, sv("8'd15"));
+
+        let steps = restoring_div(sv("8'b1101_x000"), sv("8'd3"), 8);
+        assert_eq!(steps[3].partial, sv("8'd4"));
+        assert_eq!(steps[4].partial, sv("8'b0000_100x"));
+        assert_eq!(steps[7].partial, sv("8'b0100_xxxx"));
+        assert_eq!(steps[7].remainder, Sv4State::all_x());
+    }
+
+    #[test]
+    fn sqrt() {
+        let steps = non_restoring_sqrt(sv("8'd200"), 8);
+        let partial: Vec<_> = steps.iter().map(|s| s.partial).collect();
+        let remainder: Vec<_> = steps.iter().map(|s| s.remainder).collect();
+        assert_eq!(
+            partial,
+            [sv("8'd1"), sv("8'd3"), sv("8'd7"), sv("8'd14"), sv("8'd14")]
+        );
+        assert_eq!(
+            remainder,
+            [sv("8'd2"), sv("8'd3"), sv("8'd1"), sv("8'd231"), sv("8'd4")]
+        );
+
+        let steps = non_restoring_sqrt(sv("8'b0001_zz00"), 8);
+        assert_eq!(steps[1].partial, sv("8'd1"));
+        assert_eq!(steps[2].partial, sv("8'b0000_001x"));
+        assert_eq!(steps[4].remainder, Sv4State::all_x());
+    }
+}
@@ -0,0 +1,240 @@
+//! Byte-lane steering of data buses
+//!
+//! Lane `i` of a bus is bits `[8 * i + 7 : 8 * i]`, and strobes have one bit
+//! per lane.
+
+use crate::mem::Sv4StateMem;
+use crate::{Sv4State, Sv4Vec, SvLogic};
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+impl<T: Copy + Debug + PrimInt> Sv4Vec<T> {
+    /// Number of byte lanes (rounded up)
+    pub fn lanes(&self) -> usize {
+        self.width().div_ceil(8)
+    }
+
+    /// Byte lane `i`
+    pub fn lane(&self, i: usize) -> Sv4Vec<T> {
+        self.select(8 * i + 7, 8 * i)
+    }
+
+    /// Rotate byte lanes to the more significant side by `n` lanes
+    pub fn rotate_lanes(&self, n: usize) -> Sv4Vec<T> {
+        let lanes = self.lanes();
+        let mut ret = Sv4Vec::zeros(lanes * 8);
+        for i in 0..lanes {
+            ret.insert(
+                8 * ((i + n) % lanes) + 7,
+                8 * ((i + n) % lanes),
+                &self.lane(i),
+            );
+        }
+        Sv4Vec::new(ret.words(), self.width())
+    }
+}
+
+/// Steer the lower `size` bytes of `data` to lanes from `offset` of a bus of
+/// `bus_bytes` lanes, as the write data of a narrow transfer
+///
+/// The offset is taken modulo `bus_bytes`, and bytes beyond the bus are
+/// dropped. Unwritten lanes are x, and the strobe has 1 for written lanes.
+pub fn steer_write<T: Copy + Debug + PrimInt>(
+    data: &Sv4Vec<T>,
+    offset: usize,
+    size: usize,
+    bus_bytes: usize,
+) -> (Sv4Vec<T>, Sv4Vec<T>) {
+    let mut bus = Sv4Vec::all_x(bus_bytes * 8);
+    let mut strobe = Sv4Vec::zeros(bus_bytes);
+    let offset = offset % bus_bytes.max(1);
+    for i in 0..size.min(bus_bytes - offset) {
+        let lane = offset + i;
+        bus.insert(8 * lane + 7, 8 * lane, &data.lane(i));
+        strobe.set_bit(lane, SvLogic::One);
+    }
+    (bus, strobe)
+}
+
+/// Extract `size` bytes from lanes from `offset` of `bus` to the lower bytes,
+/// as the read data of a narrow transfer
+///
+/// The offset is taken modulo the number of lanes, and bytes beyond the bus
+/// are x. A `size` of 0 gives an empty value.
+pub fn steer_read<T: Copy + Debug + PrimInt>(
+    bus: &Sv4Vec<T>,
+    offset: usize,
+    size: usize,
+) -> Sv4Vec<T> {
+    if size == 0 {
+        return Sv4Vec::zeros(0);
+    }
+    let offset = offset % bus.lanes().max(1);
+    bus.select(8 * (offset + size) - 1, 8 * offset)
+}
+
+/// Merge `new` into `old` by `strobe`
+///
+/// Lanes whose strobe is 1 are taken from `new`, and lanes whose strobe is 0
+/// are kept from `old`. If the strobe is x/z, bits of the lane are kept where
+/// `old` and `new` are the same known value, and are x otherwise.
+pub fn apply_strobe<T: Copy + Debug + PrimInt>(
+    old: &Sv4Vec<T>,
+    new: &Sv4Vec<T>,
+    strobe: &Sv4Vec<T>,
+) -> Sv4Vec<T> {
+    let mut ret = old.clone();
+    for i in 0..old.width() {
+        let bit = match strobe.bit(i / 8) {
+            SvLogic::Zero => old.bit(i),
+            SvLogic::One => new.bit(i),
+            _ => {
+                let (a, b) = (old.bit(i), new.bit(i));
+                if a == b && a.is_known() {
+                    a
+                } else {
+                    SvLogic::X
+                }
+            }
+        };
+        ret.set_bit(i, bit);
+    }
+    ret
+}
+
+/// Byte order of lanes in a bus
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Endian {
+    /// The lowest address is lane 0
+    Little,
+    /// The lowest address is the most significant lane
+    Big,
+}
+
+/// Applies bus beats to a byte-addressed memory image
+///
+/// A beat of `address` writes the bus-aligned bytes containing `address`, and
+/// lanes are mapped to addresses by the endianness.
+#[derive(Copy, Clone, Debug)]
+pub struct Reassembler {
+    bus_bytes: usize,
+    endian: Endian,
+}
+
+impl Reassembler {
+    /// Create a reassembler of a little-endian bus of `bus_bytes` lanes
+    pub fn new(bus_bytes: usize) -> Self {
+        Reassembler {
+            bus_bytes: bus_bytes.max(1),
+            endian: Endian::Little,
+        }
+    }
+
+    pub fn endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    /// Apply a beat of `data` with `strobe` to `mem`
+    ///
+    /// Lanes whose strobe is x/z are merged as [`apply_strobe`], with
+    /// never-written bytes as all-x.
+    pub fn apply<T: Copy + Debug + PrimInt>(
+        &self,
+        mem: &mut Sv4StateMem<u8>,
+        address: u64,
+        data: &Sv4Vec<T>,
+        strobe: &Sv4Vec<T>,
+    ) {
+        let base = address - address % self.bus_bytes as u64;
+        for lane in 0..self.bus_bytes {
+            let offset = match self.endian {
+                Endian::Little => lane,
+                Endian::Big => self.bus_bytes - 1 - lane,
+            };
+            let address = base + offset as u64;
+            let new = data.lane(lane).repack::<u8>().words()[0];
+            match strobe.bit(lane) {
+                SvLogic::Zero => (),
+                SvLogic::One => mem.write(address, new),
+                _ => {
+                    let old = mem.read(address);
+                    let agree = !(old.z | old.x | new.z | new.x | (old.v ^ new.v));
+                    mem.write(address, Sv4State::new(old.v & agree, 0, !agree));
+                }
+            }
+        }
+    }
+
+    /// Memory image after all `beats` of `(address, data, strobe)`
+    pub fn assemble<'a, T: Copy + Debug + PrimInt + 'a>(
+        &self,
+        beats: impl IntoIterator<Item = (u64, &'a Sv4Vec<T>, &'a Sv4Vec<T>)>,
+    ) -> Sv4StateMem<u8> {
+        let mut mem = Sv4StateMem::new();
+        for (address, data, strobe) in beats {
+            self.apply(&mut mem, address, data, strobe);
+        }
+        mem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vec32(s: &str) -> Sv4Vec<u32> {
+        Sv4Vec::from_value(s.parse().unwrap(), 32)
+    }
+
+    #[test]
+    fn steer() {
+        let data = vec32("32'h0000_beef");
+        let (bus, strobe) = steer_write(&data, 5, 2, 4);
+
+        assert_eq!(format!("{:b}", strobe), "0110");
+        assert_eq!(bus.words(), ["32'hxxbeefxx".parse().unwrap()]);
+        assert_eq!(steer_read(&bus, 1, 2), vec32("32'h0000_beef").select(15, 0));
+        assert_eq!(steer_write(&data, 3, 2, 4).0.lane(3), data.lane(0));
+        assert_eq!(format!("{:b}", steer_read(&bus, 3, 2)), "xxxxxxxxxxxxxxxx");
+        assert_eq!(steer_read(&bus, 0, 0), Sv4Vec::zeros(0));
+
+        assert_eq!(
+            vec32("32'h1122_3344").rotate_lanes(1).words(),
+            [Sv4State::known(0x2233_4411)]
+        );
+    }
+
+    #[test]
+    fn strobe() {
+        let old = vec32("32'h1122_3344");
+        let new = vec32("32'h1122_bbcc");
+        let strobe = Sv4Vec::from_value("4'b1x0x".parse().unwrap(), 4);
+
+        assert_eq!(
+            apply_strobe(&old, &new, &strobe).words(),
+            [Sv4State::new(0x1122_3344, 0, 0x88)]
+        );
+    }
+
+    #[test]
+    fn reassemble() {
+        let data = vec32("32'h4433_2211");
+        let full = Sv4Vec::from_value(Sv4State::known(0xf), 4);
+        let upper = Sv4Vec::from_value("4'b1x00".parse().unwrap(), 4);
+        let beats = [(0x100, &data, &full), (0x106, &data, &upper)];
+
+        let mem = Reassembler::new(4).assemble(beats.iter().copied());
+        assert_eq!(mem.read(0x101), Sv4State::known(0x22));
+        assert_eq!(mem.read(0x106), Sv4State::all_x());
+        assert_eq!(mem.read(0x107), Sv4State::known(0x44));
+        assert_eq!(mem.len(), 6);
+
+        let mem = Reassembler::new(4)
+            .endian(Endian::Big)
+            .assemble(beats.iter().copied());
+        assert_eq!(mem.read(0x100), Sv4State::known(0x44));
+        assert_eq!(mem.read(0x104), Sv4State::known(0x44));
+        assert!(!mem.is_written(0x106));
+    }
+}
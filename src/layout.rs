@@ -0,0 +1,116 @@
+//! Word and byte order of DPI canonical buffers
+
+use crate::lanes::Endian;
+use crate::{svLogicVecVal, Sv4State};
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+
+/// Layout of a DPI canonical buffer
+///
+/// The default is the layout of IEEE 1800-2017 Annex I: little-endian word
+/// order (element 0 has bits `[31:0]`) and little-endian bytes in each
+/// `aval`/`bval`. Buffers from other tools may have the most significant word
+/// first, or bytes of each word swapped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DpiLayout {
+    pub word_order: Endian,
+    pub byte_order: Endian,
+}
+
+impl Default for DpiLayout {
+    fn default() -> Self {
+        DpiLayout {
+            word_order: Endian::Little,
+            byte_order: Endian::Little,
+        }
+    }
+}
+
+impl DpiLayout {
+    pub fn new(word_order: Endian, byte_order: Endian) -> Self {
+        DpiLayout {
+            word_order,
+            byte_order,
+        }
+    }
+
+    /// Convert between this layout and the canonical layout
+    ///
+    /// The conversion is its own inverse.
+    fn reorder(&self, data: &[svLogicVecVal]) -> Vec<svLogicVecVal> {
+        let word = |w: &svLogicVecVal| match self.byte_order {
+            Endian::Little => svLogicVecVal {
+                aval: w.aval,
+                bval: w.bval,
+            },
+            Endian::Big => svLogicVecVal {
+                aval: w.aval.swap_bytes(),
+                bval: w.bval.swap_bytes(),
+            },
+        };
+        match self.word_order {
+            Endian::Little => data.iter().map(word).collect(),
+            Endian::Big => data.iter().rev().map(word).collect(),
+        }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Convert from a DPI buffer of `layout`
+    ///
+    /// The values are the same as [`Sv4State::from_dpi`] of the buffer in the
+    /// canonical layout.
+    pub fn from_dpi_layout(data: &[svLogicVecVal], layout: DpiLayout) -> Vec<Self> {
+        Self::from_dpi(&layout.reorder(data))
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Write `values` to a DPI buffer of `layout`
+    ///
+    /// Bits are placed as [`Sv4State::to_dpi_into`] in the canonical layout,
+    /// and then the words of `data` are reordered.
+    pub fn to_dpi_layout_into(values: &[Self], data: &mut [svLogicVecVal], layout: DpiLayout) {
+        let mut canonical = layout.reorder(data);
+        Self::to_dpi_into(values, &mut canonical);
+        for (d, c) in data.iter_mut().zip(layout.reorder(&canonical)) {
+            *d = c;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let buf = [
+            svLogicVecVal {
+                aval: 0x89ab_cdef,
+                bval: 0x0000_0000,
+            },
+            svLogicVecVal {
+                aval: 0x6745_2301,
+                bval: 0xff00_0000,
+            },
+        ];
+        let layout = DpiLayout::new(Endian::Big, Endian::Big);
+        let sv = Sv4State::<u32>::from_dpi_layout(&buf, layout);
+
+        assert_eq!(sv[0], Sv4State::new(0x0123_4500, 0x98, 0x67));
+        assert_eq!(sv[1], Sv4State::known(0xefcd_ab89));
+        assert_eq!(
+            Sv4State::<u32>::from_dpi_layout(&buf, DpiLayout::default()),
+            Sv4State::from_dpi(&buf)
+        );
+
+        let mut out = [
+            svLogicVecVal { aval: 0, bval: 0 },
+            svLogicVecVal { aval: 0, bval: 0 },
+        ];
+        Sv4State::to_dpi_layout_into(&sv, &mut out, layout);
+        assert_eq!((out[0].aval, out[0].bval), (buf[0].aval, buf[0].bval));
+        assert_eq!((out[1].aval, out[1].bval), (buf[1].aval, buf[1].bval));
+    }
+}
@@ -1,7 +1,163 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::{Debug, LowerHex, Write};
+use core::hash::{Hash, Hasher};
 use num_traits::{FromPrimitive, PrimInt, WrappingShr};
-use std::fmt::{Debug, LowerHex};
+
+#[cfg(feature = "std")]
+pub mod arbiter;
+mod bits;
+#[cfg(feature = "bitvec")]
+pub mod bitvec;
+#[cfg(feature = "std")]
+pub mod booth;
+#[cfg(feature = "std")]
+pub mod cache_line;
+#[cfg(feature = "std")]
+pub mod capture;
+#[cfg(feature = "std")]
+pub mod carry_save;
+pub mod consts;
+#[cfg(feature = "std")]
+pub mod correlate;
+#[cfg(feature = "std")]
+pub mod cstruct;
+#[cfg(feature = "std")]
+pub mod diag;
+#[cfg(feature = "std")]
+pub mod diff;
+pub mod dpi_view;
+#[cfg(feature = "std")]
+pub mod encoding;
+pub mod error;
+mod extend;
+#[cfg(feature = "std")]
+pub mod field;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(not(feature = "std"))]
+mod format {
+    /// Canonical digits, as there are no thread profiles without `std`
+    #[derive(Copy, Clone)]
+    pub(crate) struct UnknownChars;
+
+    impl UnknownChars {
+        pub(crate) fn get(&self, c: char) -> char {
+            c
+        }
+    }
+
+    pub(crate) fn unknown_chars() -> UnknownChars {
+        UnknownChars
+    }
+}
+#[cfg(feature = "std")]
+pub mod fsm;
+#[cfg(feature = "fst")]
+pub mod fst;
+#[cfg(feature = "std")]
+pub mod golden;
+pub mod inject;
+#[cfg(feature = "std")]
+pub mod intern;
+#[cfg(feature = "std")]
+pub mod iterative;
+#[cfg(feature = "std")]
+pub mod lanes;
+#[cfg(feature = "std")]
+pub mod layout;
+#[cfg(feature = "alloc")]
+pub mod literal;
+#[cfg(feature = "std")]
+pub mod logfile;
+pub mod logic;
+mod macros;
+#[cfg(feature = "std")]
+pub mod mem;
+#[cfg(feature = "std")]
+pub mod memfile;
+#[cfg(feature = "std")]
+pub mod net;
+#[cfg(feature = "ffi")]
+pub mod open_array;
+mod ops;
+#[cfg(feature = "std")]
+pub mod packet;
+#[cfg(feature = "rayon")]
+pub mod par;
+#[cfg(feature = "std")]
+pub mod pattern;
+#[cfg(feature = "alloc")]
+pub mod planes;
+#[cfg(feature = "rand")]
+pub mod random;
+#[cfg(feature = "std")]
+pub mod replacement;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "serde")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod scoreboard;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod signed;
+#[cfg(feature = "simd")]
+pub mod simd;
+#[cfg(feature = "alloc")]
+pub mod std_logic;
+pub mod strength;
+#[cfg(feature = "std")]
+pub mod temporal;
+pub mod time;
+#[cfg(feature = "std")]
+pub mod toggle;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod vcd;
+#[cfg(feature = "vcd-ng")]
+pub mod vcd_ng;
+#[cfg(feature = "alloc")]
+pub mod vec;
+#[cfg(feature = "std")]
+pub mod verilator;
+pub mod view;
+#[cfg(feature = "std")]
+pub mod vpi;
+#[cfg(feature = "ruint")]
+pub mod wide;
+#[cfg(feature = "typenum")]
+pub mod width;
+#[cfg(feature = "std")]
+pub mod window;
+pub mod words;
+#[cfg(feature = "std")]
+pub mod xcount;
+#[cfg(feature = "std")]
+pub mod xstats;
+
+pub use dpi_view::Sv4DpiView;
+pub use error::Error;
+pub use logic::{Bit4, SvLogic};
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub use macros::sv_diff as __sv_diff;
+#[doc(hidden)]
+pub use macros::{
+    from_literal as __from_literal, parse_literal as __parse_literal, Literal as __Literal,
+};
+#[cfg(feature = "alloc")]
+pub use planes::Sv4StatePlanes;
+#[cfg(feature = "alloc")]
+pub use vec::Sv4Vec;
+pub use view::Sv4View;
 
 /// Type for SystemVerilog 4-state value
 #[derive(Copy, Clone, Debug)]
@@ -21,112 +177,558 @@ pub struct svLogicVecVal {
     pub bval: u32,
 }
 
-impl<T: Copy + Debug + PrimInt + WrappingShr> std::fmt::Binary for Sv4State<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let payload_width = T::zero().count_zeros();
-        let mut buf = if f.alternate() {
-            String::from("0b")
+/// Type for packed bit array defined by IEEE 1800-2017 Annex I
+#[allow(non_camel_case_types)]
+pub type svBitVecVal = u32;
+
+impl<T: Copy + Debug + PrimInt> Default for Sv4State<T> {
+    /// All bits are x, as an uninitialized SystemVerilog 4-state variable
+    fn default() -> Self {
+        Self::all_x()
+    }
+}
+
+/// Case equality (`===`) of normalized values
+impl<T: Copy + Debug + PrimInt> PartialEq for Sv4State<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let a = self.normalize();
+        let b = other.normalize();
+        a.v == b.v && a.z == b.z && a.x == b.x
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Eq for Sv4State<T> {}
+
+impl<T: Copy + Debug + PrimInt + Hash> Hash for Sv4State<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let a = self.normalize();
+        a.v.hash(state);
+        a.z.hash(state);
+        a.x.hash(state);
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Create a value from v/z/x planes
+    ///
+    /// Overlapping bits are normalized: x takes precedence over z, and
+    /// v is cleared where z or x is set.
+    pub fn new(v: T, z: T, x: T) -> Self {
+        Sv4State { v, z, x }.normalize()
+    }
+
+    /// Create a fully known value
+    pub fn known(v: T) -> Self {
+        Sv4State {
+            v,
+            z: T::zero(),
+            x: T::zero(),
+        }
+    }
+
+    /// Resolve overlapping v/z/x bits
+    ///
+    /// x takes precedence over z, and v is cleared where z or x is set.
+    pub fn normalize(self) -> Self {
+        let z = self.z & !self.x;
+        let v = self.v & !(self.z | self.x);
+        Sv4State { v, z, x: self.x }
+    }
+
+    /// Whether v/z/x planes are disjoint
+    pub fn is_normalized(&self) -> bool {
+        self.z & self.x == T::zero() && self.v & (self.z | self.x) == T::zero()
+    }
+
+    /// Bit mask of the lower `width` bits
+    ///
+    /// `width` larger than the payload width is saturated.
+    pub fn width_mask(width: u32) -> T {
+        if width >= T::zero().count_zeros() {
+            !T::zero()
         } else {
-            String::from("")
-        };
+            !(!T::zero() << width as usize)
+        }
+    }
+
+    /// Whether all bits are 0 or 1
+    pub fn is_known(&self) -> bool {
+        self.z == T::zero() && self.x == T::zero()
+    }
+
+    /// All bits are x
+    pub fn all_x() -> Self {
+        Self::all_x_width(T::zero().count_zeros())
+    }
+
+    /// All bits are z
+    pub fn all_z() -> Self {
+        Self::all_z_width(T::zero().count_zeros())
+    }
+
+    /// All bits are 0
+    pub fn zeros() -> Self {
+        Sv4State {
+            v: T::zero(),
+            z: T::zero(),
+            x: T::zero(),
+        }
+    }
+
+    /// All bits are 1
+    pub fn ones() -> Self {
+        Self::ones_width(T::zero().count_zeros())
+    }
+
+    /// The lower `width` bits are x, and the rest are 0
+    pub fn all_x_width(width: u32) -> Self {
+        Sv4State {
+            v: T::zero(),
+            z: T::zero(),
+            x: Self::width_mask(width),
+        }
+    }
+
+    /// The lower `width` bits are z, and the rest are 0
+    pub fn all_z_width(width: u32) -> Self {
+        Sv4State {
+            v: T::zero(),
+            z: Self::width_mask(width),
+            x: T::zero(),
+        }
+    }
+
+    /// The lower `width` bits are 1, and the rest are 0
+    pub fn ones_width(width: u32) -> Self {
+        Sv4State {
+            v: Self::width_mask(width),
+            z: T::zero(),
+            x: T::zero(),
+        }
+    }
+}
+
+/// Canonical digit `x`, `X`, `z` or `Z` of the bits of `mask`, or `None` if
+/// they are known
+///
+/// All formatters classify digits by this, so z takes precedence over x and a
+/// digit with both z and x bits is `Z`.
+pub(crate) fn unknown_digit<T: PrimInt>(z: T, x: T, mask: T) -> Option<char> {
+    let (z, x) = (z & mask, x & mask);
+    if z == mask {
+        Some('z')
+    } else if z != T::zero() {
+        Some('Z')
+    } else if x == mask {
+        Some('x')
+    } else if x != T::zero() {
+        Some('X')
+    } else {
+        None
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + WrappingShr> core::fmt::Binary for Sv4State<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let value = self.normalize();
+        let chars = format::unknown_chars();
+        let payload_width = T::zero().count_zeros();
+        if f.alternate() {
+            f.write_str("0b")?;
+        }
 
         for i in 0..payload_width {
-            let v = (self.v.wrapping_shr(payload_width - i - 1)) & T::one();
-            let z = (self.z.wrapping_shr(payload_width - i - 1)) & T::one();
-            let x = (self.x.wrapping_shr(payload_width - i - 1)) & T::one();
+            let v = (value.v.wrapping_shr(payload_width - i - 1)) & T::one();
+            let z = (value.z.wrapping_shr(payload_width - i - 1)) & T::one();
+            let x = (value.x.wrapping_shr(payload_width - i - 1)) & T::one();
 
-            if z == T::one() {
-                buf.push('z')
+            let c = if z == T::one() {
+                chars.get('z')
             } else if x == T::one() {
-                buf.push('x')
+                chars.get('x')
             } else if v == T::one() {
-                buf.push('1')
+                '1'
             } else {
-                buf.push('0')
-            }
+                '0'
+            };
+            f.write_char(c)?;
         }
-        write!(f, "{}", buf)
+        Ok(())
     }
 }
 
-impl<T: Copy + Debug + PrimInt + WrappingShr + FromPrimitive + LowerHex> LowerHex for Sv4State<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<T: Copy + Debug + PrimInt + WrappingShr> LowerHex for Sv4State<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let value = self.normalize();
+        let chars = format::unknown_chars();
         let payload_width = T::zero().count_zeros();
-        let mut buf = if f.alternate() {
-            String::from("0x")
-        } else {
-            String::from("")
-        };
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
 
-        let all_hi = T::from_u32(15).unwrap();
+        let all_hi = Self::width_mask(4);
 
         for i in 0..payload_width / 4 {
-            let v = (self.v.wrapping_shr(payload_width - (i + 1) * 4)) & all_hi;
-            let z = (self.z.wrapping_shr(payload_width - (i + 1) * 4)) & all_hi;
-            let x = (self.x.wrapping_shr(payload_width - (i + 1) * 4)) & all_hi;
-
-            if z == all_hi {
-                buf.push('z')
-            } else if z != T::zero() {
-                buf.push('Z')
-            } else if x == all_hi {
-                buf.push('x')
-            } else if x != T::zero() {
-                buf.push('X')
-            } else {
-                buf.push_str(&format!("{:x}", v))
-            }
+            let v = (value.v.wrapping_shr(payload_width - (i + 1) * 4)) & all_hi;
+            let z = (value.z.wrapping_shr(payload_width - (i + 1) * 4)) & all_hi;
+            let x = (value.x.wrapping_shr(payload_width - (i + 1) * 4)) & all_hi;
+
+            let c = match unknown_digit(z, x, all_hi) {
+                Some(c) => chars.get(c),
+                None => v
+                    .to_u32()
+                    .and_then(|v| core::char::from_digit(v, 16))
+                    .unwrap_or('?'),
+            };
+            f.write_char(c)?;
         }
-        write!(f, "{}", buf)
+        Ok(())
     }
 }
 
 impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    #[cfg(feature = "alloc")]
     pub fn from_dpi(data: &[svLogicVecVal]) -> Vec<Self> {
+        Self::iter_from_dpi(data).collect()
+    }
+
+    /// Convert from DPI canonical representation of a `bit_width`-bit signal
+    ///
+    /// The result has `bit_width / payload_width` values (rounded up). Bits
+    /// from `bit_width`, which are undefined in the last canonical word, are
+    /// 0, and so are bits beyond `data`.
+    #[cfg(feature = "alloc")]
+    pub fn from_dpi_width(data: &[svLogicVecVal], bit_width: usize) -> Vec<Self> {
         let payload_width = T::zero().count_zeros() as usize;
-        let bit_width = 32 * data.len();
-        let len = if bit_width % payload_width == 0 {
-            bit_width / payload_width
-        } else {
-            bit_width / payload_width + 1
-        };
+        (0..bit_width.div_ceil(payload_width))
+            .map(|i| {
+                let value = Self::decode_dpi_word(data, i);
+                let mask = Self::width_mask((bit_width - i * payload_width) as u32);
+                Sv4State::new(value.v & mask, value.z & mask, value.x & mask)
+            })
+            .collect()
+    }
 
-        let mut ret = Vec::new();
-        for i in 0..len {
-            let mut v = T::zero();
-            let mut z = T::zero();
-            let mut x = T::zero();
-
-            for j in 0..(payload_width / 8) {
-                // byte index
-                let index = i * payload_width / 8 + j;
-
-                if index / 4 >= data.len() {
-                    break;
-                }
-
-                let aval = data[index / 4].aval >> ((index % 4) * 8) & 0xff;
-                let bval = data[index / 4].bval >> ((index % 4) * 8) & 0xff;
-                let aval = T::from_u32(aval).unwrap();
-                let bval = T::from_u32(bval).unwrap();
-                let aval = aval << (j * 8);
-                let bval = bval << (j * 8);
-
-                v = v | (aval & !bval);
-                z = z | (bval & !aval);
-                x = x | (bval & aval);
+    /// Convert from DPI canonical representation of a `width`-bit value
+    ///
+    /// Unlike [`Sv4State::from_dpi`], this fails if `data` is not the
+    /// `width / 32` words (rounded up) of the value, if the words don't split
+    /// into whole payload values, or if the payload width is not a multiple of
+    /// 8.
+    #[cfg(feature = "alloc")]
+    pub fn try_from_dpi(data: &[svLogicVecVal], width: usize) -> Result<Vec<Self>, Error> {
+        let payload_width = T::zero().count_zeros();
+        if !payload_width.is_multiple_of(8) {
+            return Err(Error::UnsupportedType { payload_width });
+        }
+        if data.len() != width.div_ceil(32) {
+            return Err(Error::WidthMismatch {
+                expected: width.div_ceil(32),
+                found: data.len(),
+            });
+        }
+        if !(32 * data.len()).is_multiple_of(payload_width as usize) {
+            return Err(Error::BadLength {
+                len: data.len(),
+                payload_width,
+            });
+        }
+        Ok(Self::from_dpi(data))
+    }
+
+    /// Convert from DPI canonical representation into a caller-provided slice
+    ///
+    /// The values are the same as [`Sv4State::from_dpi`], and as many as fit
+    /// in `out` are written. The number of written values is returned.
+    pub fn from_dpi_into(data: &[svLogicVecVal], out: &mut [Self]) -> usize {
+        let mut len = 0;
+        for (out, value) in out.iter_mut().zip(Self::iter_from_dpi(data)) {
+            *out = value;
+            len += 1;
+        }
+        len
+    }
+
+    /// Lazily convert from DPI canonical representation
+    ///
+    /// The iterator yields the same values as [`Sv4State::from_dpi`], decoding
+    /// each value when it is requested.
+    pub fn iter_from_dpi(
+        data: &[svLogicVecVal],
+    ) -> impl ExactSizeIterator<Item = Self> + DoubleEndedIterator + '_ {
+        let payload_width = T::zero().count_zeros() as usize;
+        let len = (32 * data.len()).div_ceil(payload_width);
+        (0..len).map(move |i| Self::decode_dpi_word(data, i))
+    }
+
+    /// `i`-th value of [`Sv4State::from_dpi`]
+    fn decode_dpi_word(data: &[svLogicVecVal], i: usize) -> Self {
+        let payload_width = T::zero().count_zeros() as usize;
+        let mut v = T::zero();
+        let mut z = T::zero();
+        let mut x = T::zero();
+
+        for j in 0..(payload_width / 8) {
+            // byte index
+            let index = i * payload_width / 8 + j;
+
+            if index / 4 >= data.len() {
+                break;
             }
 
-            ret.push(Sv4State { v, z, x });
+            let aval = data[index / 4].aval >> ((index % 4) * 8) & 0xff;
+            let bval = data[index / 4].bval >> ((index % 4) * 8) & 0xff;
+            let aval = from_u32_bits::<T>(aval) << (j * 8);
+            let bval = from_u32_bits::<T>(bval) << (j * 8);
+
+            v = v | (aval & !bval);
+            z = z | (bval & !aval);
+            x = x | (bval & aval);
+        }
+
+        Sv4State { v, z, x }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Write `values` to a caller-provided DPI canonical buffer
+    ///
+    /// This is the inverse of [`Sv4State::from_dpi`]: `values[i]` is placed
+    /// from bit `i * payload_width`. Bits beyond `data` are ignored, and bits
+    /// not covered by `values` are kept.
+    pub fn to_dpi_into(values: &[Self], data: &mut [svLogicVecVal]) {
+        let payload_width = T::zero().count_zeros();
+        for (i, value) in values.iter().enumerate() {
+            write_dpi_bits(data, i * payload_width as usize, payload_width, value);
+        }
+    }
+
+    /// Write `values` to a buffer of `aval`/`bval` pairs like `svLogicVecVal[]`
+    ///
+    /// `words[2 * i]` is `aval` and `words[2 * i + 1]` is `bval` of the `i`-th
+    /// 32-bit chunk. A trailing odd word is kept.
+    pub fn to_dpi_words_into(values: &[Self], words: &mut [u32]) {
+        // svLogicVecVal is repr(C) of two u32, so it has the same layout and
+        // alignment as a pair of words.
+        let data = unsafe {
+            core::slice::from_raw_parts_mut(
+                words.as_mut_ptr() as *mut svLogicVecVal,
+                words.len() / 2,
+            )
+        };
+        Self::to_dpi_into(values, data);
+    }
+
+    /// Write `values` to `len` elements of `svLogicVecVal` from `ptr`
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and writes of `len` elements, as the
+    /// buffer of an `output logic` DPI argument.
+    pub unsafe fn to_dpi_raw(values: &[Self], ptr: *mut svLogicVecVal, len: usize) {
+        if ptr.is_null() {
+            return;
+        }
+        let data = core::slice::from_raw_parts_mut(ptr, len);
+        Self::to_dpi_into(values, data);
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Decode only the bits of `bit_range` of DPI canonical representation
+    ///
+    /// The range is split into payload-width values from its least significant
+    /// bit, and the last value has the remaining bits with the upper bits 0.
+    /// Bits beyond `data` are x.
+    #[cfg(feature = "alloc")]
+    pub fn decode_range(data: &[svLogicVecVal], bit_range: core::ops::Range<usize>) -> Vec<Self> {
+        let payload_width = T::zero().count_zeros() as usize;
+        let mut ret = Vec::new();
+        let mut lsb = bit_range.start;
+        while lsb < bit_range.end {
+            let width = payload_width.min(bit_range.end - lsb);
+            ret.push(read_dpi_bits(data, lsb, width as u32));
+            lsb += width;
         }
         ret
     }
 }
 
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Convert from DPI `bit` vector representation
+    ///
+    /// The values are split like [`Sv4State::from_dpi`], and have no x/z bits.
+    #[cfg(feature = "alloc")]
+    pub fn from_dpi_bit(data: &[svBitVecVal]) -> Vec<Self> {
+        let data: Vec<svLogicVecVal> = data
+            .iter()
+            .map(|w| svLogicVecVal { aval: *w, bval: 0 })
+            .collect();
+        Self::from_dpi(&data)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Convert to DPI `bit` vector representation
+    ///
+    /// x/z bits are 0, as assigning a `logic` value to a `bit` value.
+    #[cfg(feature = "alloc")]
+    pub fn to_dpi_bit(values: &[Self]) -> Vec<svBitVecVal> {
+        let payload_width = T::zero().count_zeros() as usize;
+        let len = (values.len() * payload_width).div_ceil(32);
+        let mut data: Vec<svLogicVecVal> = (0..len)
+            .map(|_| svLogicVecVal { aval: 0, bval: 0 })
+            .collect();
+        Self::to_dpi_into(values, &mut data);
+        data.iter().map(|w| w.aval & !w.bval).collect()
+    }
+}
+
+/// Case equality (`===`) of the lower `width` bits of DPI canonical representations
+///
+/// The buffers are compared word by word without decoding. Bits beyond a
+/// buffer are x.
+pub fn dpi_eq(data_a: &[svLogicVecVal], data_b: &[svLogicVecVal], width: usize) -> bool {
+    (0..width.div_ceil(32)).all(|i| {
+        let n = (width - i * 32).min(32);
+        let mask = if n == 32 { !0 } else { (1u32 << n) - 1 };
+        let word = |data: &[svLogicVecVal]| {
+            data.get(i)
+                .map_or((mask, mask), |w| (w.aval & mask, w.bval & mask))
+        };
+        word(data_a) == word(data_b)
+    })
+}
+
+/// Whether the lower `width` bits of DPI canonical representation are all 0 or 1
+///
+/// Bits beyond `data` are x.
+pub fn dpi_is_known(data: &[svLogicVecVal], width: usize) -> bool {
+    (0..width.div_ceil(32)).all(|i| {
+        let n = (width - i * 32).min(32);
+        let mask = if n == 32 { !0 } else { (1u32 << n) - 1 };
+        data.get(i).is_some_and(|w| w.bval & mask == 0)
+    })
+}
+
+/// Bits of `x` as `T`
+///
+/// Types which can't represent `x` like signed types get the bits within the
+/// payload width, so decoding never panics.
+fn from_u32_bits<T: Copy + PrimInt + FromPrimitive>(x: u32) -> T {
+    T::from_u32(x).unwrap_or_else(|| {
+        (0..T::zero().count_zeros().min(32))
+            .filter(|i| (x >> i) & 1 == 1)
+            .fold(T::zero(), |acc, i| acc | T::one() << i as usize)
+    })
+}
+
+/// Lower 32 bits of `x`
+///
+/// Negative values of signed types get their two's complement bits, which
+/// `to_u32` would reject.
+fn to_u32_bits<T: Copy + PrimInt>(x: T) -> u32 {
+    x.to_u32().unwrap_or_else(|| {
+        (0..T::zero().count_zeros().min(32))
+            .filter(|i| (x >> *i as usize) & T::one() != T::zero())
+            .fold(0, |acc, i| acc | 1 << i)
+    })
+}
+
+/// Read `width` bits from `lsb` of DPI canonical representation
+///
+/// Bits beyond `data` are x, and `width` is saturated to the payload width.
+pub(crate) fn read_dpi_bits<T: Copy + Debug + PrimInt + FromPrimitive>(
+    data: &[svLogicVecVal],
+    lsb: usize,
+    width: u32,
+) -> Sv4State<T> {
+    let width = width.min(T::zero().count_zeros()) as usize;
+    let mut ret = Sv4State::zeros();
+    let mut pos = 0;
+    while pos < width {
+        let bit = lsb + pos;
+        let offset = bit % 32;
+        let n = (32 - offset).min(width - pos);
+        let mask = if n == 32 { !0 } else { (1u32 << n) - 1 };
+        let (aval, bval) = match data.get(bit / 32) {
+            Some(w) => ((w.aval >> offset) & mask, (w.bval >> offset) & mask),
+            None => (mask, mask),
+        };
+        let aval = from_u32_bits::<T>(aval) << pos;
+        let bval = from_u32_bits::<T>(bval) << pos;
+
+        ret.v = ret.v | (aval & !bval);
+        ret.z = ret.z | (bval & !aval);
+        ret.x = ret.x | (bval & aval);
+        pos += n;
+    }
+    ret
+}
+
+/// Write the lower `width` bits of `value` to `lsb` of DPI canonical representation
+///
+/// Bits beyond `data` are ignored, and `width` is saturated to the payload width.
+pub(crate) fn write_dpi_bits<T: Copy + Debug + PrimInt>(
+    data: &mut [svLogicVecVal],
+    lsb: usize,
+    width: u32,
+    value: &Sv4State<T>,
+) {
+    let width = width.min(T::zero().count_zeros()) as usize;
+    let value = value.normalize();
+    let aval = value.v | value.x;
+    let bval = value.z | value.x;
+    let mut pos = 0;
+    while pos < width {
+        let bit = lsb + pos;
+        let offset = bit % 32;
+        let n = (32 - offset).min(width - pos);
+        let mask = if n == 32 { !0 } else { (1u32 << n) - 1 };
+        let w = match data.get_mut(bit / 32) {
+            Some(w) => w,
+            None => break,
+        };
+        let chunk = Sv4State::<T>::width_mask(n as u32);
+        let a = to_u32_bits((aval >> pos) & chunk) << offset;
+        let b = to_u32_bits((bval >> pos) & chunk) << offset;
+        w.aval = (w.aval & !(mask << offset)) | a;
+        w.bval = (w.bval & !(mask << offset)) | b;
+        pos += n;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn from_dpi_signed() {
+        let data = [svLogicVecVal {
+            aval: 0x1234_56ff,
+            bval: 0x0000_f00f,
+        }];
+        let sv = Sv4State::<i8>::from_dpi(&data);
+        assert_eq!(sv.len(), 4);
+        assert_eq!(sv[0], Sv4State::new(-16, 0, 0x0f));
+        assert_eq!(sv[1], Sv4State::new(0x06, 0xa0u8 as i8, 0x50));
+        assert_eq!(
+            Sv4State::<i8>::decode_range(&data, 4..12),
+            [Sv4State::known(0x6f)]
+        );
+        assert_eq!(Sv4State::<i8>::try_from_dpi(&data, 32), Ok(sv));
+
+        let values = [Sv4State::<i8>::known(-1), Sv4State::new(-128, 0x30, 0x0c)];
+        let mut data = [svLogicVecVal { aval: 0, bval: 0 }];
+        Sv4State::to_dpi_into(&values, &mut data);
+        assert_eq!((data[0].aval, data[0].bval), (0x8cff, 0x3c00));
+        assert_eq!(Sv4State::<i8>::from_dpi(&data)[..2], values);
+        let values = [Sv4State::<i16>::known(-2), Sv4State::new(-0x100, 0, 0x7f)];
+        let mut data = [svLogicVecVal { aval: 0, bval: 0 }];
+        Sv4State::to_dpi_into(&values, &mut data);
+        assert_eq!(Sv4State::<i16>::from_dpi(&data), values);
+    }
+
     #[test]
     fn from_dpi_u8() {
         let buf = [
@@ -167,6 +769,215 @@ mod tests {
         assert_eq!(sv_u8[7].x, 0x89);
     }
 
+    #[test]
+    fn from_dpi_into() {
+        let buf = [svLogicVecVal {
+            aval: 0x0123_4567,
+            bval: 0x0000_ff00,
+        }];
+        let mut out = [Sv4State::<u8>::all_z(); 6];
+
+        assert_eq!(Sv4State::from_dpi_into(&buf, &mut out), 4);
+        assert_eq!(out[..4], Sv4State::from_dpi(&buf)[..]);
+        assert_eq!(out[4], Sv4State::all_z());
+        let mut out = [Sv4State::<u16>::zeros(); 1];
+        assert_eq!(Sv4State::from_dpi_into(&buf, &mut out), 1);
+        assert_eq!(out[0], Sv4State::new(0x0067, 0xba00, 0x4500));
+    }
+
+    #[test]
+    fn iter_from_dpi() {
+        let buf = [
+            svLogicVecVal {
+                aval: 0x0123_4567,
+                bval: 0x0000_ff00,
+            },
+            svLogicVecVal {
+                aval: 0x89ab_cdef,
+                bval: 0xffff_ffff,
+            },
+        ];
+        let mut iter = Sv4State::<u16>::iter_from_dpi(&buf);
+
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(Sv4State::new(0x0067, 0xba00, 0x4500)));
+        assert_eq!(iter.next_back(), Some(Sv4State::new(0, 0x7654, 0x89ab)));
+        assert_eq!(
+            Sv4State::<u8>::iter_from_dpi(&buf).collect::<Vec<_>>(),
+            Sv4State::from_dpi(&buf)
+        );
+    }
+
+    #[test]
+    fn from_dpi_width() {
+        let buf = [
+            svLogicVecVal {
+                aval: 0x0123_4567,
+                bval: 0x0000_ff00,
+            },
+            svLogicVecVal {
+                aval: 0x89ab_cdef,
+                bval: 0xffff_fff0,
+            },
+        ];
+        let sv = Sv4State::<u32>::from_dpi_width(&buf, 36);
+
+        assert_eq!(sv.len(), 2);
+        assert_eq!(sv[1], Sv4State::known(0xf));
+        assert_eq!(format!("{:x}", sv[1]), "0000000f");
+        assert_eq!(
+            Sv4State::<u16>::from_dpi_width(&buf, 12)[0],
+            Sv4State::new(0x067, 0xa00, 0x500)
+        );
+        assert_eq!(Sv4State::<u8>::from_dpi_width(&buf, 80).len(), 10);
+    }
+
+    #[test]
+    fn try_from_dpi() {
+        let buf = [
+            svLogicVecVal {
+                aval: 0x0123_4567,
+                bval: 0x0000_ff00,
+            },
+            svLogicVecVal {
+                aval: 0x89ab_cdef,
+                bval: 0xffff_ffff,
+            },
+        ];
+
+        assert_eq!(
+            Sv4State::<u16>::try_from_dpi(&buf, 64),
+            Ok(Sv4State::from_dpi(&buf))
+        );
+        assert_eq!(
+            Sv4State::<u16>::try_from_dpi(&buf, 16),
+            Err(Error::WidthMismatch {
+                expected: 1,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Sv4State::<u64>::try_from_dpi(&buf[..1], 32),
+            Err(Error::BadLength {
+                len: 1,
+                payload_width: 64
+            })
+        );
+        assert_eq!(
+            Sv4State::<i8>::try_from_dpi(&buf, 64),
+            Ok(Sv4State::from_dpi(&buf))
+        );
+    }
+
+    #[test]
+    fn to_dpi_into() {
+        let buf = [
+            svLogicVecVal {
+                aval: 0x01234567,
+                bval: 0x00000000,
+            },
+            svLogicVecVal {
+                aval: 0x89abcdef,
+                bval: 0xffffffff,
+            },
+        ];
+        let sv_u16 = Sv4State::<u16>::from_dpi(&buf);
+
+        let mut out = [
+            svLogicVecVal { aval: 0, bval: 0 },
+            svLogicVecVal { aval: 0, bval: 0 },
+        ];
+        Sv4State::to_dpi_into(&sv_u16, &mut out);
+        for (a, b) in out.iter().zip(buf.iter()) {
+            assert_eq!((a.aval, a.bval), (b.aval, b.bval));
+        }
+
+        let mut words = [0u32, 0, 0, 0, 0xdead];
+        Sv4State::to_dpi_words_into(&sv_u16[1..3], &mut words);
+        assert_eq!(words, [0xcdef0123, 0xffff0000, 0, 0, 0xdead]);
+
+        let mut out = [svLogicVecVal {
+            aval: 0xffffffff,
+            bval: 0,
+        }];
+        unsafe { Sv4State::to_dpi_raw(&[Sv4State::<u8>::all_z()], out.as_mut_ptr(), 1) };
+        assert_eq!((out[0].aval, out[0].bval), (0xffffff00, 0x000000ff));
+    }
+
+    #[test]
+    fn decode_range() {
+        let buf = [
+            svLogicVecVal {
+                aval: 0x01234567,
+                bval: 0x00000000,
+            },
+            svLogicVecVal {
+                aval: 0x89abcdef,
+                bval: 0xffffffff,
+            },
+        ];
+
+        let sv = Sv4State::<u16>::decode_range(&buf, 28..52);
+        assert_eq!(sv.len(), 2);
+        assert_eq!(sv[0], Sv4State::new(0, 0x2100, 0xdef0));
+        assert_eq!(sv[1], Sv4State::new(0, 0x43, 0xbc));
+        assert!(Sv4State::<u16>::decode_range(&buf, 4..4).is_empty());
+        assert_eq!(
+            Sv4State::<u8>::decode_range(&buf, 60..68),
+            vec![Sv4State::new(0, 0x07, 0xf8)]
+        );
+    }
+
+    #[test]
+    fn dpi_eq_is_known() {
+        let a = [
+            svLogicVecVal {
+                aval: 0x01234567,
+                bval: 0x00000000,
+            },
+            svLogicVecVal {
+                aval: 0x89abcdef,
+                bval: 0xffff0000,
+            },
+        ];
+        let b = [
+            svLogicVecVal {
+                aval: 0x01234567,
+                bval: 0x00000000,
+            },
+            svLogicVecVal {
+                aval: 0x00abcdef,
+                bval: 0x0f000000,
+            },
+        ];
+
+        assert!(dpi_eq(&a, &b, 48));
+        assert!(!dpi_eq(&a, &b, 57));
+        assert!(!dpi_eq(&a, &a[..1], 33));
+        assert!(dpi_is_known(&a, 48));
+        assert!(!dpi_is_known(&a, 49));
+        assert!(!dpi_is_known(&a[..1], 33));
+        assert!(dpi_is_known(&[], 0));
+    }
+
+    #[test]
+    fn dpi_bit() {
+        let sv = Sv4State::<u16>::from_dpi_bit(&[0x01234567, 0x89abcdef]);
+        assert_eq!(
+            sv,
+            vec![
+                Sv4State::known(0x4567),
+                Sv4State::known(0x0123),
+                Sv4State::known(0xcdef),
+                Sv4State::known(0x89ab),
+            ]
+        );
+        assert_eq!(Sv4State::to_dpi_bit(&sv), vec![0x01234567, 0x89abcdef]);
+
+        let sv: Sv4State<u8> = "8'b1x0z_1111".parse().unwrap();
+        assert_eq!(Sv4State::to_dpi_bit(&[sv]), vec![0x8f]);
+    }
+
     #[test]
     fn from_dpi_u16() {
         let buf = [
@@ -255,6 +1066,150 @@ mod tests {
         assert_eq!(sv_u128[0].x, 0x89abcdef00000000);
     }
 
+    #[test]
+    fn u128_payload() {
+        let sv: Sv4State<u128> = "128'h8000_0000_0000_0000_0000_0000_0000_xz01"
+            .parse()
+            .unwrap();
+        let hex = format!("8{}xz01", "0".repeat(27));
+        assert_eq!(format!("{:x}", sv), hex);
+        assert_eq!(format!("{:b}", sv).len(), 128);
+        assert_eq!(&format!("{:b}", sv)[112..], "xxxxzzzz00000001");
+        assert_eq!(format!("{}", sv), hex);
+        let octal = format::FormatProfile {
+            radix: format::Radix::Octal,
+            ..Default::default()
+        };
+        assert_eq!(
+            sv.display_with(&octal).to_string(),
+            format!("2{}XxzZ01", "0".repeat(36))
+        );
+        assert_eq!(sv.view(124..).get(), Sv4State::known(0x8));
+
+        let max = Sv4State::<u128>::known(u128::MAX);
+        assert_eq!(max.to_dec_string(128, false), u128::MAX.to_string());
+        assert_eq!(max.to_dec_string(128, true), "-1");
+        assert_eq!(max.as_signed(128).to_i128(), Some(-1));
+        assert_eq!(
+            Sv4State::<u128>::known(1 << 127).sign_extend(128),
+            Sv4State::known(1 << 127)
+        );
+        assert_eq!(Sv4State::<u128>::known(1 << 63).sign_extend(64).v, !0 << 63);
+        assert_eq!(Sv4State::<u128>::width_mask(128), u128::MAX);
+        assert_eq!(Sv4State::<u128>::width_mask(127), u128::MAX >> 1);
+
+        let one = Sv4State::<u128>::known(1);
+        assert_eq!(max + one, Sv4State::known(0));
+        assert_eq!(max.add_with_flags(one, 128).1, SvLogic::One);
+        assert_eq!(!max, Sv4State::known(0));
+        assert_eq!(sv & Sv4State::known(0xf0), Sv4State::known(0));
+        assert_eq!((sv ^ one).x, 0xff00);
+        assert_eq!(one.rotate_right(one, 128), Sv4State::known(1 << 127));
+        assert_eq!(
+            one.rotate_left(Sv4State::known(127), 128),
+            Sv4State::known(1 << 127)
+        );
+        assert_eq!(one.bit_reverse(128), Sv4State::known(1 << 127));
+        assert_eq!(one.byte_swap(128), Sv4State::known(1 << 120));
+        assert_eq!(max.popcount(128), Sv4State::known(128));
+        assert_eq!(sv.as_signed(128).shr(124).value().v, !0 << 3);
+
+        let mut buf: Vec<svLogicVecVal> =
+            (0..4).map(|_| svLogicVecVal { aval: 0, bval: 0 }).collect();
+        Sv4State::to_dpi_into(&[sv], &mut buf);
+        assert_eq!((buf[0].aval, buf[0].bval), (0x0000_f001, 0x0000_ff00));
+        assert_eq!(buf[3].aval, 0x8000_0000);
+        assert_eq!(Sv4State::<u128>::from_dpi(&buf), [sv]);
+        assert_eq!(Sv4State::<u128>::decode_range(&buf, 0..128), [sv]);
+        assert_eq!(Sv4State::<u128>::from_dpi_width(&buf, 100)[0].v, 0x01);
+        assert_eq!(Sv4State::<u128>::to_dpi_bit(&[max]), [!0; 4]);
+        assert_eq!(
+            Sv4State::<u8>::repack::<u128>(&Sv4State::<u128>::repack::<u8>(&[sv])),
+            [sv]
+        );
+
+        let vcd = sv.to_vcd_value(128);
+        assert_eq!(vcd.len(), 129);
+        assert_eq!(Sv4State::<u128>::from_vcd_value(&vcd, 128), Ok(sv));
+        let vpi = Sv4State::to_vpi(&[sv], 128);
+        assert_eq!(vpi[3].aval, i32::MIN);
+        assert_eq!(Sv4State::<u128>::from_vpi(&vpi, 128), [sv]);
+        assert_eq!(
+            Sv4State::<u128>::from_std_logic_vector(&sv.to_std_logic_vector(128)),
+            Ok(sv)
+        );
+    }
+
+    #[test]
+    fn default_all_x() {
+        let sv_u16 = Sv4State::<u16>::default();
+
+        assert_eq!(sv_u16.v, 0x0000);
+        assert_eq!(sv_u16.z, 0x0000);
+        assert_eq!(sv_u16.x, 0xffff);
+        assert_eq!(format!("{:b}", sv_u16), "xxxxxxxxxxxxxxxx");
+    }
+
+    #[test]
+    fn constant_constructors() {
+        assert_eq!(format!("{:b}", Sv4State::<u8>::all_x()), "xxxxxxxx");
+        assert_eq!(format!("{:b}", Sv4State::<u8>::all_z()), "zzzzzzzz");
+        assert_eq!(format!("{:b}", Sv4State::<u8>::zeros()), "00000000");
+        assert_eq!(format!("{:b}", Sv4State::<u8>::ones()), "11111111");
+        assert_eq!(format!("{:b}", Sv4State::<u8>::all_x_width(3)), "00000xxx");
+        assert_eq!(format!("{:b}", Sv4State::<u8>::all_z_width(5)), "000zzzzz");
+        assert_eq!(format!("{:b}", Sv4State::<u8>::ones_width(4)), "00001111");
+        assert_eq!(format!("{:b}", Sv4State::<u8>::ones_width(9)), "11111111");
+    }
+
+    #[test]
+    fn new_normalize() {
+        let sv_u8 = Sv4State::<u8>::new(0xff, 0x0f, 0x3c);
+
+        assert_eq!(sv_u8.v, 0xc0);
+        assert_eq!(sv_u8.z, 0x03);
+        assert_eq!(sv_u8.x, 0x3c);
+        assert!(sv_u8.is_normalized());
+        assert_eq!(format!("{:b}", sv_u8), "11xxxxzz");
+
+        let raw = Sv4State::<u8> {
+            v: 0x01,
+            z: 0x01,
+            x: 0x00,
+        };
+        assert!(!raw.is_normalized());
+        assert_eq!(format!("{:b}", raw.normalize()), "0000000z");
+        assert_eq!(format!("{:b}", raw), "0000000z");
+        let raw = Sv4State::<u8> {
+            v: 0xff,
+            z: 0x0f,
+            x: 0x03,
+        };
+        assert_eq!(format!("{:b}", raw), "1111zzxx");
+        assert_eq!(format!("{:x}", raw), "fZ");
+    }
+
+    #[test]
+    fn eq_hash() {
+        use std::collections::HashSet;
+
+        let a = Sv4State::<u8> {
+            v: 0xff,
+            z: 0x01,
+            x: 0x00,
+        };
+        let b = Sv4State::<u8>::new(0xfe, 0x01, 0x00);
+        assert_eq!(a, b);
+        assert_ne!(b, Sv4State::new(0xfe, 0x00, 0x01));
+        assert_ne!(Sv4State::<u8>::all_x(), Sv4State::all_z());
+
+        let mut set = HashSet::new();
+        set.insert(a);
+        set.insert(b);
+        set.insert(Sv4State::all_x());
+        assert_eq!(set.len(), 2);
+    }
+
     #[test]
     fn format_binary() {
         let buf = [
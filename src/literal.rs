@@ -0,0 +1,243 @@
+//! Parser of SystemVerilog integer literals
+
+use crate::Sv4State;
+use alloc::{vec, vec::Vec};
+use core::fmt::Debug;
+use core::str::FromStr;
+use num_traits::{FromPrimitive, PrimInt};
+
+/// Error of parsing SystemVerilog integer literals
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The literal is empty or has no digits
+    Empty,
+    /// The size is not a positive decimal number
+    InvalidSize,
+    /// The size exceeds the payload width
+    SizeTooLarge(u32),
+    /// The base is not one of `b`, `o`, `d` and `h`
+    InvalidBase(char),
+    /// The digit is not valid for the base
+    InvalidDigit(char),
+    /// The value doesn't fit in the size
+    Overflow,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty literal"),
+            ParseError::InvalidSize => write!(f, "invalid size"),
+            ParseError::SizeTooLarge(x) => write!(f, "size {} exceeds payload width", x),
+            ParseError::InvalidBase(x) => write!(f, "invalid base: '{}'", x),
+            ParseError::InvalidDigit(x) => write!(f, "invalid digit: '{}'", x),
+            ParseError::Overflow => write!(f, "value doesn't fit in the size"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Bit {
+    Zero,
+    One,
+    X,
+    Z,
+}
+
+/// Parse a literal like `8'b10xz_1010`, `'hff`, `16'sd42` or `42`
+///
+/// Unsized literals have the payload width. If the literal has fewer digits
+/// than its size, it is extended with x/z when the most significant digit is
+/// x/z, and with 0 otherwise. Bits above the size are 0.
+impl<T: Copy + Debug + PrimInt + FromPrimitive> FromStr for Sv4State<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let payload_width = T::zero().count_zeros();
+        let s = s.trim();
+
+        let (size, rest) = match s.find('\'') {
+            Some(i) => {
+                let size = if i == 0 {
+                    payload_width
+                } else {
+                    let size = s[..i]
+                        .trim()
+                        .replace('_', "")
+                        .parse::<u32>()
+                        .map_err(|_| ParseError::InvalidSize)?;
+                    if size == 0 {
+                        return Err(ParseError::InvalidSize);
+                    }
+                    if size > payload_width {
+                        return Err(ParseError::SizeTooLarge(size));
+                    }
+                    size
+                };
+                (size, &s[i + 1..])
+            }
+            None => (payload_width, s),
+        };
+
+        let mut chars = rest.trim_start().chars().peekable();
+        let base = if s.contains('\'') {
+            if let Some('s') | Some('S') = chars.peek() {
+                chars.next();
+            }
+            match chars.next() {
+                Some('b') | Some('B') => 2,
+                Some('o') | Some('O') => 8,
+                Some('d') | Some('D') => 10,
+                Some('h') | Some('H') => 16,
+                Some(c) => return Err(ParseError::InvalidBase(c)),
+                None => return Err(ParseError::Empty),
+            }
+        } else {
+            10
+        };
+
+        let digits: Vec<char> = chars.filter(|c| *c != '_').collect();
+        if digits.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        // bits from LSB
+        let bits = if base == 10 {
+            parse_decimal(&digits, size)?
+        } else {
+            parse_radix(&digits, base)?
+        };
+
+        let msb = bits.last().copied().unwrap_or(Bit::Zero);
+        let ext = match msb {
+            Bit::X | Bit::Z => msb,
+            _ => Bit::Zero,
+        };
+
+        for bit in bits.iter().skip(size as usize) {
+            if *bit != Bit::Zero {
+                return Err(ParseError::Overflow);
+            }
+        }
+
+        let mut ret = Sv4State::zeros();
+        for i in 0..size as usize {
+            let bit = bits.get(i).copied().unwrap_or(ext);
+            let b = T::one() << i;
+            match bit {
+                Bit::Zero => (),
+                Bit::One => ret.v = ret.v | b,
+                Bit::X => ret.x = ret.x | b,
+                Bit::Z => ret.z = ret.z | b,
+            }
+        }
+        Ok(ret)
+    }
+}
+
+fn parse_radix(digits: &[char], base: u32) -> Result<Vec<Bit>, ParseError> {
+    let bits_per_digit = match base {
+        2 => 1,
+        8 => 3,
+        _ => 4,
+    };
+    let mut ret = Vec::new();
+    for c in digits.iter().rev() {
+        let bit = match c {
+            'x' | 'X' => Some(Bit::X),
+            'z' | 'Z' | '?' => Some(Bit::Z),
+            _ => None,
+        };
+        match bit {
+            Some(bit) => ret.extend(core::iter::repeat_n(bit, bits_per_digit)),
+            None => {
+                let d = c.to_digit(base).ok_or(ParseError::InvalidDigit(*c))?;
+                for i in 0..bits_per_digit {
+                    ret.push(if (d >> i) & 1 == 1 {
+                        Bit::One
+                    } else {
+                        Bit::Zero
+                    });
+                }
+            }
+        }
+    }
+    Ok(ret)
+}
+
+fn parse_decimal(digits: &[char], size: u32) -> Result<Vec<Bit>, ParseError> {
+    if digits.len() == 1 {
+        match digits[0] {
+            'x' | 'X' => return Ok(vec![Bit::X; size as usize]),
+            'z' | 'Z' | '?' => return Ok(vec![Bit::Z; size as usize]),
+            _ => (),
+        }
+    }
+    let mut value: u128 = 0;
+    for c in digits {
+        let d = c.to_digit(10).ok_or(ParseError::InvalidDigit(*c))?;
+        value = value
+            .checked_mul(10)
+            .and_then(|x| x.checked_add(d as u128))
+            .ok_or(ParseError::Overflow)?;
+    }
+    Ok((0..128)
+        .map(|i| {
+            if (value >> i) & 1 == 1 {
+                Bit::One
+            } else {
+                Bit::Zero
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let sv: Sv4State<u8> = "8'b10xz_1010".parse().unwrap();
+        assert_eq!(format!("{:b}", sv), "10xz1010");
+
+        let sv: Sv4State<u16> = "12'hx5".parse().unwrap();
+        assert_eq!(format!("{:b}", sv), "0000xxxxxxxx0101");
+
+        let sv: Sv4State<u16> = "'o17".parse().unwrap();
+        assert_eq!(sv, Sv4State::known(0o17));
+
+        let sv: Sv4State<u32> = "16'sd1234".parse().unwrap();
+        assert_eq!(sv, Sv4State::known(1234));
+
+        let sv: Sv4State<u8> = "4'dz".parse().unwrap();
+        assert_eq!(format!("{:b}", sv), "0000zzzz");
+
+        let sv: Sv4State<u8> = "42".parse().unwrap();
+        assert_eq!(sv, Sv4State::known(42));
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!("".parse::<Sv4State<u8>>(), Err(ParseError::Empty));
+        assert_eq!("8'h".parse::<Sv4State<u8>>(), Err(ParseError::Empty));
+        assert_eq!("0'h0".parse::<Sv4State<u8>>(), Err(ParseError::InvalidSize));
+        assert_eq!(
+            "9'h0".parse::<Sv4State<u8>>(),
+            Err(ParseError::SizeTooLarge(9))
+        );
+        assert_eq!(
+            "8'q0".parse::<Sv4State<u8>>(),
+            Err(ParseError::InvalidBase('q'))
+        );
+        assert_eq!(
+            "8'b102".parse::<Sv4State<u8>>(),
+            Err(ParseError::InvalidDigit('2'))
+        );
+        assert_eq!("4'h1f".parse::<Sv4State<u8>>(), Err(ParseError::Overflow));
+        assert_eq!("256".parse::<Sv4State<u8>>(), Err(ParseError::Overflow));
+    }
+}
@@ -0,0 +1,347 @@
+//! Line-oriented monitor logs with typed columns
+
+use crate::literal::ParseError;
+use crate::Sv4State;
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+use std::io::BufRead;
+use std::marker::PhantomData;
+
+/// Type of a log column
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Hexadecimal 4-state value like `1f`, `x` or `0z_ff`
+    Hex,
+    /// Binary 4-state value like `10xz`
+    Binary,
+    /// Signed decimal integer
+    Decimal,
+    /// Any string
+    String,
+}
+
+/// Separator of log columns
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Separator {
+    /// One or more whitespaces
+    Whitespace,
+    /// A comma, with surrounding whitespaces removed
+    Comma,
+}
+
+/// Value of a log column
+#[derive(Clone, Debug)]
+pub enum LogField<T: Copy + Debug> {
+    Value(Sv4State<T>),
+    Decimal(i64),
+    String(String),
+}
+
+impl<T: Copy + Debug + PrimInt> PartialEq for LogField<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (LogField::Value(a), LogField::Value(b)) => a == b,
+            (LogField::Decimal(a), LogField::Decimal(b)) => a == b,
+            (LogField::String(a), LogField::String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Parsed log line
+#[derive(Clone, Debug)]
+pub struct LogRecord<T: Copy + Debug> {
+    /// 1-based line number
+    pub line: usize,
+    /// fields in the order of the declared columns
+    pub fields: Vec<LogField<T>>,
+}
+
+impl<T: Copy + Debug + PrimInt> PartialEq for LogRecord<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.line == other.line && self.fields == other.fields
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> LogRecord<T> {
+    /// 4-state value of column `i`
+    pub fn value(&self, i: usize) -> Option<Sv4State<T>> {
+        match self.fields.get(i) {
+            Some(LogField::Value(x)) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Decimal integer of column `i`
+    pub fn decimal(&self, i: usize) -> Option<i64> {
+        match self.fields.get(i) {
+            Some(LogField::Decimal(x)) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// String of column `i`
+    pub fn string(&self, i: usize) -> Option<&str> {
+        match self.fields.get(i) {
+            Some(LogField::String(x)) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+/// Error of parsing logs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogError {
+    /// 1-based line number
+    pub line: usize,
+    pub kind: LogErrorKind,
+}
+
+/// Kind of [`LogError`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogErrorKind {
+    /// The number of columns differs from the declaration
+    ColumnCount { expected: usize, found: usize },
+    /// The column is not a valid 4-state value
+    InvalidValue {
+        column: String,
+        text: String,
+        error: ParseError,
+    },
+    /// The column is not a valid decimal integer
+    InvalidDecimal { column: String, text: String },
+    /// Reading the line failed
+    Io(std::io::ErrorKind),
+}
+
+impl std::fmt::Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            LogErrorKind::ColumnCount { expected, found } => write!(
+                f,
+                "line {}: expected {} columns, found {}",
+                self.line, expected, found
+            ),
+            LogErrorKind::InvalidValue {
+                column,
+                text,
+                error,
+            } => write!(
+                f,
+                "line {}: invalid value of {}: {}: {}",
+                self.line, column, text, error
+            ),
+            LogErrorKind::InvalidDecimal { column, text } => {
+                write!(
+                    f,
+                    "line {}: invalid decimal of {}: {}",
+                    self.line, column, text
+                )
+            }
+            LogErrorKind::Io(x) => write!(f, "line {}: {}", self.line, x),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+/// Parser of logs with declared columns
+///
+/// Empty lines and lines starting with `#` are skipped.
+///
+/// ```
+/// use sv4state::logfile::{ColumnType, LogParser};
+///
+/// let parser = LogParser::<u32>::new()
+///     .column("time", ColumnType::Decimal)
+///     .column("data", ColumnType::Hex);
+/// let record = parser.parse_line(1, "100 dead_bxxx").unwrap().unwrap();
+/// assert_eq!(record.decimal(0), Some(100));
+/// assert_eq!(format!("{:x}", record.value(1).unwrap()), "deadbxxx");
+/// ```
+#[derive(Clone, Debug)]
+pub struct LogParser<T> {
+    columns: Vec<(String, ColumnType)>,
+    separator: Separator,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Default for LogParser<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> LogParser<T> {
+    pub fn new() -> Self {
+        LogParser {
+            columns: Vec::new(),
+            separator: Separator::Whitespace,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Declare the next column
+    pub fn column(mut self, name: &str, ty: ColumnType) -> Self {
+        self.columns.push((String::from(name), ty));
+        self
+    }
+
+    pub fn separator(mut self, separator: Separator) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Index of the column named `name`
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|(x, _)| x == name)
+    }
+
+    /// Parse a line, giving `None` for skipped lines
+    pub fn parse_line(&self, line: usize, text: &str) -> Result<Option<LogRecord<T>>, LogError> {
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            return Ok(None);
+        }
+        let err = |kind| LogError { line, kind };
+
+        let items: Vec<&str> = match self.separator {
+            Separator::Whitespace => text.split_whitespace().collect(),
+            Separator::Comma => text.split(',').map(|x| x.trim()).collect(),
+        };
+        if items.len() != self.columns.len() {
+            return Err(err(LogErrorKind::ColumnCount {
+                expected: self.columns.len(),
+                found: items.len(),
+            }));
+        }
+
+        let mut fields = Vec::new();
+        for ((name, ty), item) in self.columns.iter().zip(items) {
+            let field = match ty {
+                ColumnType::Hex | ColumnType::Binary => {
+                    let base = if *ty == ColumnType::Hex { 'h' } else { 'b' };
+                    let value = format!("'{}{}", base, item).parse().map_err(|error| {
+                        err(LogErrorKind::InvalidValue {
+                            column: name.clone(),
+                            text: String::from(item),
+                            error,
+                        })
+                    })?;
+                    LogField::Value(value)
+                }
+                ColumnType::Decimal => {
+                    let value = item.parse().map_err(|_| {
+                        err(LogErrorKind::InvalidDecimal {
+                            column: name.clone(),
+                            text: String::from(item),
+                        })
+                    })?;
+                    LogField::Decimal(value)
+                }
+                ColumnType::String => LogField::String(String::from(item)),
+            };
+            fields.push(field);
+        }
+        Ok(Some(LogRecord { line, fields }))
+    }
+
+    /// Parse lines of `reader` as they are read
+    pub fn records<R: BufRead>(&self, reader: R) -> LogRecords<'_, R, T> {
+        LogRecords {
+            parser: self,
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+}
+
+/// Iterator of [`LogRecord`]s created by [`LogParser::records`]
+pub struct LogRecords<'a, R, T> {
+    parser: &'a LogParser<T>,
+    lines: std::io::Lines<R>,
+    line: usize,
+}
+
+impl<'a, R: BufRead, T: Copy + Debug + PrimInt + FromPrimitive> Iterator for LogRecords<'a, R, T> {
+    type Item = Result<LogRecord<T>, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            let text = match self.lines.next()? {
+                Ok(x) => x,
+                Err(e) => {
+                    return Some(Err(LogError {
+                        line: self.line,
+                        kind: LogErrorKind::Io(e.kind()),
+                    }))
+                }
+            };
+            match self.parser.parse_line(self.line, &text) {
+                Ok(None) => (),
+                Ok(Some(x)) => return Some(Ok(x)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records() {
+        let text = "# time, name, data, flags\n\
+                    10, wr, 1f, 10\n\
+                    \n\
+                    20, rd, xx, 0z\n\
+                    30, rd, 1\n";
+        let parser = LogParser::<u8>::new()
+            .column("time", ColumnType::Decimal)
+            .column("name", ColumnType::String)
+            .column("data", ColumnType::Hex)
+            .column("flags", ColumnType::Binary)
+            .separator(Separator::Comma);
+        let records: Vec<_> = parser.records(text.as_bytes()).collect();
+
+        assert_eq!(records.len(), 3);
+        let record = records[1].as_ref().unwrap();
+        assert_eq!(record.line, 4);
+        assert_eq!(record.decimal(0), Some(20));
+        assert_eq!(record.string(1), Some("rd"));
+        assert_eq!(record.value(2), Some(Sv4State::all_x()));
+        assert_eq!(record.value(3), Some(Sv4State::new(0, 0x01, 0)));
+        assert_eq!(record.value(0), None);
+        assert_eq!(
+            records[2].as_ref().unwrap_err().kind,
+            LogErrorKind::ColumnCount {
+                expected: 4,
+                found: 3
+            }
+        );
+        assert_eq!(parser.find("data"), Some(2));
+    }
+
+    #[test]
+    fn invalid() {
+        let parser = LogParser::<u8>::new()
+            .column("time", ColumnType::Decimal)
+            .column("data", ColumnType::Hex);
+
+        assert_eq!(parser.parse_line(1, "  # comment"), Ok(None));
+        assert_eq!(
+            parser.parse_line(2, "1 fff").unwrap_err().kind,
+            LogErrorKind::InvalidValue {
+                column: String::from("data"),
+                text: String::from("fff"),
+                error: ParseError::Overflow
+            }
+        );
+        assert_eq!(
+            parser.parse_line(3, "t 00").unwrap_err().to_string(),
+            "line 3: invalid decimal of time: t"
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! `sv4state!` literal and `assert_sv_eq!` assertion macros
+
+/// Literal parsed at compile time by [`sv4state!`]
+#[doc(hidden)]
+#[derive(Copy, Clone, Debug)]
+pub struct Literal {
+    pub size: u32,
+    pub v: u128,
+    pub z: u128,
+    pub x: u128,
+}
+
+/// Parse the size and the digits of a literal, panicking on errors
+///
+/// This follows `Sv4State::from_str` except that unsized literals have
+/// `payload_width` bits.
+#[doc(hidden)]
+pub const fn parse_literal(size: &str, digits: &str, payload_width: u32) -> Literal {
+    let size = parse_size(size.as_bytes(), payload_width);
+    let s = digits.as_bytes();
+    let mut i = 0;
+    if i < s.len() && s[i] == b'\'' {
+        i += 1;
+    }
+    if i < s.len() && (s[i] == b's' || s[i] == b'S') {
+        i += 1;
+    }
+    if i >= s.len() {
+        panic!("empty literal");
+    }
+    // bits per digit, or 0 for decimal
+    let bits = match s[i] {
+        b'b' | b'B' => 1,
+        b'o' | b'O' => 3,
+        b'd' | b'D' => 0,
+        b'h' | b'H' => 4,
+        _ => panic!("invalid base"),
+    };
+    let mut ret = Literal {
+        size,
+        v: 0,
+        z: 0,
+        x: 0,
+    };
+    let mask = if size >= 128 {
+        u128::MAX
+    } else {
+        (1 << size) - 1
+    };
+
+    if bits == 0 {
+        let (mut value, mut count, mut unknown): (u128, usize, u8) = (0, 0, 0);
+        let mut j = i + 1;
+        while j < s.len() {
+            let c = s[j];
+            j += 1;
+            if c == b'_' {
+                continue;
+            }
+            count += 1;
+            match c {
+                b'x' | b'X' => unknown = b'x',
+                b'z' | b'Z' | b'?' => unknown = b'z',
+                b'0'..=b'9' => {
+                    value = match value.checked_mul(10) {
+                        Some(v) => match v.checked_add((c - b'0') as u128) {
+                            Some(v) => v,
+                            None => panic!("value doesn't fit in the size"),
+                        },
+                        None => panic!("value doesn't fit in the size"),
+                    }
+                }
+                _ => panic!("invalid digit"),
+            }
+        }
+        if count == 0 {
+            panic!("empty literal");
+        }
+        if unknown != 0 && count != 1 {
+            panic!("invalid digit");
+        }
+        match unknown {
+            b'x' => ret.x = mask,
+            b'z' => ret.z = mask,
+            _ => {
+                if value & !mask != 0 {
+                    panic!("value doesn't fit in the size");
+                }
+                ret.v = value;
+            }
+        }
+        return ret;
+    }
+
+    // digits from the least significant one
+    let (mut pos, mut msb) = (0u32, 0u8);
+    let mut j = s.len();
+    while j > i + 1 {
+        j -= 1;
+        let c = s[j];
+        let (d, kind) = match c {
+            b'_' => continue,
+            b'x' | b'X' => (0, b'x'),
+            b'z' | b'Z' | b'?' => (0, b'z'),
+            b'0'..=b'9' => ((c - b'0') as u32, b'v'),
+            b'a'..=b'f' => ((c - b'a') as u32 + 10, b'v'),
+            b'A'..=b'F' => ((c - b'A') as u32 + 10, b'v'),
+            _ => panic!("invalid digit"),
+        };
+        if d >> bits != 0 {
+            panic!("invalid digit");
+        }
+        let mut k = 0;
+        while k < bits {
+            if kind != b'v' || (d >> k) & 1 == 1 {
+                set_bit(&mut ret, pos + k, kind);
+            }
+            k += 1;
+        }
+        pos = pos.saturating_add(bits);
+        msb = kind;
+    }
+    if msb == 0 {
+        panic!("empty literal");
+    }
+    if msb != b'v' {
+        while pos < size {
+            set_bit(&mut ret, pos, msb);
+            pos += 1;
+        }
+    }
+    ret
+}
+
+const fn set_bit(lit: &mut Literal, pos: u32, kind: u8) {
+    if pos >= lit.size {
+        panic!("value doesn't fit in the size");
+    }
+    let b = 1 << pos;
+    match kind {
+        b'x' => lit.x |= b,
+        b'z' => lit.z |= b,
+        _ => lit.v |= b,
+    }
+}
+
+const fn parse_size(s: &[u8], payload_width: u32) -> u32 {
+    if s.is_empty() {
+        return payload_width;
+    }
+    let mut ret: u32 = 0;
+    let mut i = 0;
+    while i < s.len() {
+        match s[i] {
+            b'_' => (),
+            b'0'..=b'9' => {
+                ret = match ret.checked_mul(10) {
+                    Some(v) => v.saturating_add((s[i] - b'0') as u32),
+                    None => u32::MAX,
+                }
+            }
+            _ => panic!("invalid size"),
+        }
+        i += 1;
+    }
+    if ret == 0 {
+        panic!("invalid size");
+    }
+    if ret > payload_width {
+        panic!("size exceeds payload width");
+    }
+    ret
+}
+
+/// Convert a literal to a value of any payload type
+///
+/// # Panics
+///
+/// Panics if the size of the literal exceeds the payload width.
+#[doc(hidden)]
+pub fn from_literal<T: Copy + core::fmt::Debug + num_traits::PrimInt>(
+    lit: Literal,
+) -> crate::Sv4State<T> {
+    let payload_width = T::zero().count_zeros();
+    assert!(
+        lit.size <= payload_width,
+        "size {} exceeds payload width",
+        lit.size
+    );
+    let mut ret = crate::Sv4State::zeros();
+    for i in 0..lit.size as usize {
+        let b = T::one() << i;
+        if (lit.v >> i) & 1 == 1 {
+            ret.v = ret.v | b;
+        }
+        if (lit.z >> i) & 1 == 1 {
+            ret.z = ret.z | b;
+        }
+        if (lit.x >> i) & 1 == 1 {
+            ret.x = ret.x | b;
+        }
+    }
+    ret
+}
+
+/// SystemVerilog literal checked at compile time
+///
+/// `sv4state!(u8, 8'b10xz_1010)` is a constant `Sv4State<u8>`, where the
+/// payload type is one of `u8`, `u16`, `u32`, `u64` and `u128`, and unsized
+/// literals like `sv4state!(u16, 'hx5)` have the payload width.
+/// `sv4state!(32'hdeadZZZZ)` has any payload type by inference, and panics if
+/// the size exceeds the payload width.
+///
+/// Invalid sizes, bases and digits, and values not fitting in the size are
+/// compile errors. Literals are the same as `str::parse` except that `?` and
+/// spaces are not allowed.
+///
+/// ```
+/// use sv4state::{sv4state, Sv4State};
+///
+/// const EXPECTED: [Sv4State<u8>; 2] = [sv4state!(u8, 8'b10xz_1010), sv4state!(u8, 4'hx)];
+/// assert_eq!(EXPECTED[0], "8'b10xz_1010".parse().unwrap());
+/// assert_eq!(format!("{:b}", EXPECTED[1]), "0000xxxx");
+///
+/// let sv: Sv4State<u32> = sv4state!(32'hdeadZZZZ);
+/// assert_eq!(format!("{:x}", sv), "deadzzzz");
+/// ```
+///
+/// ```compile_fail
+/// let _ = sv4state::sv4state!(u8, 4'h1f);
+/// ```
+#[macro_export]
+macro_rules! sv4state {
+    ($t:ident, $size:literal $digits:lifetime) => {{
+        const VALUE: $crate::Sv4State<$t> = {
+            let lit = $crate::__parse_literal(stringify!($size), stringify!($digits), <$t>::BITS);
+            $crate::Sv4State {
+                v: lit.v as $t,
+                z: lit.z as $t,
+                x: lit.x as $t,
+            }
+        };
+        VALUE
+    }};
+    ($t:ident, $digits:lifetime) => {{
+        const VALUE: $crate::Sv4State<$t> = {
+            let lit = $crate::__parse_literal("", stringify!($digits), <$t>::BITS);
+            $crate::Sv4State {
+                v: lit.v as $t,
+                z: lit.z as $t,
+                x: lit.x as $t,
+            }
+        };
+        VALUE
+    }};
+    ($size:literal $digits:lifetime) => {{
+        const LITERAL: $crate::__Literal =
+            $crate::__parse_literal(stringify!($size), stringify!($digits), 128);
+        $crate::__from_literal(LITERAL)
+    }};
+}
+
+/// Per-bit diff of `left` and `right`, or `None` if they match
+///
+/// If `x_dont_care`, x/z bits of `right` match any bit.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub fn sv_diff<T: Copy + core::fmt::Debug + num_traits::PrimInt>(
+    left: &crate::Sv4State<T>,
+    right: &crate::Sv4State<T>,
+    x_dont_care: bool,
+) -> Option<alloc::string::String> {
+    use alloc::string::String;
+
+    let width = T::zero().count_zeros() as usize;
+    let (mut l, mut r, mut markers) = (String::new(), String::new(), String::new());
+    for i in (0..width).rev() {
+        let (a, b) = (left.bit(i), right.bit(i));
+        l.push(a.to_char());
+        r.push(b.to_char());
+        markers.push(if a == b || (x_dont_care && !b.is_known()) {
+            ' '
+        } else {
+            '^'
+        });
+    }
+    if !markers.contains('^') {
+        return None;
+    }
+    Some(alloc::format!(
+        "assertion `left == right` failed{}\n  left: {}\n right: {}\n        {}",
+        if x_dont_care {
+            " (x/z of right are don't care)"
+        } else {
+            ""
+        },
+        l,
+        r,
+        markers.trim_end()
+    ))
+}
+
+/// Assert that two [`Sv4State`](crate::Sv4State) are case-equal, and print
+/// an aligned binary diff on failure
+///
+/// With `x_dont_care`, x/z bits of the right value match any bit like `==?`.
+///
+/// ```
+/// use sv4state::{assert_sv_eq, Sv4State};
+///
+/// let actual: Sv4State<u8> = "8'b1010_0x11".parse().unwrap();
+/// assert_sv_eq!(actual, "8'b1010_0x11".parse().unwrap());
+/// assert_sv_eq!(actual, "8'b10xx_zzz1".parse().unwrap(), x_dont_care);
+/// ```
+///
+/// A failure panics with
+///
+/// ```text
+/// assertion `left == right` failed
+///   left: 10100x11
+///  right: 10100011
+///              ^
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_sv_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_sv_eq!(@ $left, $right, false)
+    };
+    ($left:expr, $right:expr, x_dont_care $(,)?) => {
+        $crate::assert_sv_eq!(@ $left, $right, true)
+    };
+    (@ $left:expr, $right:expr, $x_dont_care:expr) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if let Some(diff) = $crate::__sv_diff(left, right, $x_dont_care) {
+                    panic!("{}", diff);
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Sv4State;
+
+    #[test]
+    fn sv4state() {
+        let cases: [(Sv4State<u16>, &str); 7] = [
+            (sv4state!(u16, 8'b10xz_1010), "8'b10xz_1010"),
+            (sv4state!(u16, 12'hx5), "12'hx5"),
+            (sv4state!(u16, 'o17), "'o17"),
+            (sv4state!(u16, 16'sd1234), "16'sd1234"),
+            (sv4state!(u16, 8'dz), "8'dz"),
+            (sv4state!(u16, 7'o1_7), "7'o17"),
+            (sv4state!(u16, 16'hZ_0f), "16'hz0f"),
+        ];
+        for (value, literal) in &cases {
+            assert_eq!(*value, literal.parse().unwrap(), "{}", literal);
+        }
+
+        let sv: Sv4State<u128> = sv4state!(100'hx);
+        assert_eq!(sv, Sv4State::all_x_width(100));
+        let sv: Sv4State<u8> = sv4state!(8'd255);
+        assert_eq!(sv, Sv4State::known(255));
+    }
+
+    #[test]
+    fn assert_sv_eq() {
+        let a: Sv4State<u8> = sv4state!(8'b1010_0x11);
+        let b: Sv4State<u8> = sv4state!(8'b10xx_zz01);
+        assert_sv_eq!(a, a);
+        assert_eq!(
+            super::sv_diff(&a, &b, true).unwrap(),
+            "assertion `left == right` failed (x/z of right are don't care)\n  \
+             left: 10100x11\n \
+             right: 10xxzz01\n        \
+             \x20     ^"
+        );
+        assert_eq!(
+            super::sv_diff(&a, &b, false).unwrap().lines().last(),
+            Some("          ^^^^^")
+        );
+
+        let result = std::panic::catch_unwind(|| assert_sv_eq!(a, b, x_dont_care));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Sparse 4-state memory model
+
+use crate::memfile::{parse_words, write_mem, MemFileError, MemFileErrorKind, MemFormat};
+use crate::Sv4State;
+use num_traits::{FromPrimitive, PrimInt};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Sparse memory of 4-state words
+///
+/// Only written addresses are stored, and reads of never-written addresses
+/// give all-x.
+#[derive(Clone, Debug, Default)]
+pub struct Sv4StateMem<T: Copy + Debug> {
+    words: HashMap<u64, Sv4State<T>>,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4StateMem<T> {
+    pub fn new() -> Self {
+        Sv4StateMem {
+            words: HashMap::new(),
+        }
+    }
+
+    /// Read the word at `address`
+    pub fn read(&self, address: u64) -> Sv4State<T> {
+        self.words
+            .get(&address)
+            .copied()
+            .unwrap_or_else(Sv4State::all_x)
+    }
+
+    /// Write `value` to `address`
+    pub fn write(&mut self, address: u64, value: Sv4State<T>) {
+        self.words.insert(address, value);
+    }
+
+    /// Write the bits of `value` selected by `mask` to `address`
+    ///
+    /// The other bits keep the current value, which is all-x if `address` has
+    /// never been written.
+    pub fn write_masked(&mut self, address: u64, value: Sv4State<T>, mask: T) {
+        let cur = self.read(address);
+        let merged = Sv4State::new(
+            (cur.v & !mask) | (value.v & mask),
+            (cur.z & !mask) | (value.z & mask),
+            (cur.x & !mask) | (value.x & mask),
+        );
+        self.write(address, merged);
+    }
+
+    /// Whether `address` has been written
+    pub fn is_written(&self, address: u64) -> bool {
+        self.words.contains_key(&address)
+    }
+
+    /// Number of written addresses
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Forget all written words
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    /// Written words in address order
+    pub fn iter(&self) -> impl Iterator<Item = (u64, Sv4State<T>)> {
+        let mut ret: Vec<_> = self.words.iter().map(|(a, v)| (*a, *v)).collect();
+        ret.sort_by_key(|(a, _)| *a);
+        ret.into_iter()
+    }
+
+    /// Backdoor load of `words` from `base`
+    ///
+    /// Addresses wrap around from `u64::MAX` to 0.
+    pub fn load(&mut self, base: u64, words: &[Sv4State<T>]) {
+        for (i, w) in words.iter().enumerate() {
+            self.write(base.wrapping_add(i as u64), *w);
+        }
+    }
+
+    /// Backdoor dump of `len` words from `base`
+    ///
+    /// Addresses wrap around from `u64::MAX` to 0.
+    pub fn dump(&self, base: u64, len: usize) -> Vec<Sv4State<T>> {
+        (0..len as u64)
+            .map(|i| self.read(base.wrapping_add(i)))
+            .collect()
+    }
+
+    /// Dump `len` words from `base` as a memory image file
+    pub fn dump_mem(&self, base: u64, len: usize, format: MemFormat, width: u32) -> String {
+        write_mem(&self.dump(base, len), format, width, false)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4StateMem<T> {
+    /// Backdoor load of a memory image file like `$readmemh`/`$readmemb`
+    ///
+    /// Addresses of the image are offset by `base`. Addresses skipped by the
+    /// image are not written. Words beyond `u64::MAX` are
+    /// [`MemFileErrorKind::AddressOverflow`], and then no word is written.
+    pub fn load_mem(
+        &mut self,
+        base: u64,
+        text: &str,
+        format: MemFormat,
+    ) -> Result<(), MemFileError> {
+        let words = parse_words::<T>(text, format)?
+            .into_iter()
+            .map(|(line, address, value)| {
+                let address = (address as u64).checked_add(base).ok_or(MemFileError {
+                    line,
+                    kind: MemFileErrorKind::AddressOverflow,
+                })?;
+                Ok((address, value))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (address, value) in words {
+            self.write(address, value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mem() {
+        let mut mem = Sv4StateMem::<u8>::new();
+        assert_eq!(mem.read(0x1000), Sv4State::all_x());
+
+        mem.write(0x1000, Sv4State::known(0x12));
+        mem.write_masked(0x2000, Sv4State::known(0x34), 0x0f);
+        assert_eq!(mem.read(0x1000), Sv4State::known(0x12));
+        assert_eq!(format!("{:x}", mem.read(0x2000)), "x4");
+        assert!(mem.is_written(0x2000));
+        assert!(!mem.is_written(0x1001));
+        assert_eq!(mem.len(), 2);
+
+        mem.load(0x10, &[Sv4State::known(1), Sv4State::all_z()]);
+        assert_eq!(
+            mem.dump(0x10, 3),
+            vec![Sv4State::known(1), Sv4State::all_z(), Sv4State::all_x()]
+        );
+        assert_eq!(mem.dump_mem(0x10, 3, MemFormat::Hex, 8), "01\nzz\nxx\n");
+        assert_eq!(
+            mem.iter().map(|(a, _)| a).collect::<Vec<_>>(),
+            vec![0x10, 0x11, 0x1000, 0x2000]
+        );
+
+        mem.clear();
+        mem.load_mem(0x100, "aa @4 bb", MemFormat::Hex).unwrap();
+        assert_eq!(mem.len(), 2);
+        assert_eq!(mem.read(0x100), Sv4State::known(0xaa));
+        assert_eq!(mem.read(0x104), Sv4State::known(0xbb));
+        assert!(!mem.is_written(0x101));
+
+        assert_eq!(
+            mem.load_mem(u64::MAX, "aa\nbb", MemFormat::Hex),
+            Err(MemFileError {
+                line: 2,
+                kind: MemFileErrorKind::AddressOverflow
+            })
+        );
+        assert_eq!(mem.len(), 2);
+        mem.load(u64::MAX, &[Sv4State::known(1), Sv4State::known(2)]);
+        assert_eq!(
+            mem.dump(u64::MAX, 2),
+            [Sv4State::known(1), Sv4State::known(2)]
+        );
+    }
+}
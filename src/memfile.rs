@@ -0,0 +1,314 @@
+//! `$readmemh`/`$readmemb` memory image files, and `$writememh`/`$writememb`
+
+use crate::literal::ParseError;
+use crate::Sv4State;
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+
+/// Radix of memory image words
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemFormat {
+    /// `$readmemb`
+    Binary,
+    /// `$readmemh`
+    Hex,
+}
+
+/// Error of parsing memory image files
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemFileError {
+    /// 1-based line number
+    pub line: usize,
+    pub kind: MemFileErrorKind,
+}
+
+/// Kind of [`MemFileError`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemFileErrorKind {
+    /// `@` is not followed by a hexadecimal address
+    InvalidAddress(String),
+    /// The word is not a valid number
+    InvalidWord(String, ParseError),
+    /// `/*` is not closed
+    UnterminatedComment,
+    /// The word is placed beyond the largest address, which is
+    /// [`MAX_MEM_ADDRESS`] for [`parse_mem`]
+    AddressOverflow,
+}
+
+impl std::fmt::Display for MemFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            MemFileErrorKind::InvalidAddress(x) => {
+                write!(f, "line {}: invalid address: {}", self.line, x)
+            }
+            MemFileErrorKind::InvalidWord(x, e) => {
+                write!(f, "line {}: invalid word: {}: {}", self.line, x, e)
+            }
+            MemFileErrorKind::UnterminatedComment => {
+                write!(f, "line {}: unterminated comment", self.line)
+            }
+            MemFileErrorKind::AddressOverflow => {
+                write!(f, "line {}: address overflow", self.line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MemFileError {}
+
+/// Largest address of images parsed by [`parse_mem`]
+///
+/// The result has an entry for each address up to the largest one, so larger
+/// addresses are rejected rather than allocated. Sparse images are loaded by
+/// [`Sv4StateMem::load_mem`](crate::mem::Sv4StateMem::load_mem).
+pub const MAX_MEM_ADDRESS: usize = (1 << 24) - 1;
+
+/// Parse a memory image like `$readmemh`/`$readmemb`
+///
+/// Each word must fit in the payload width. Words lacking digits are
+/// extended like SystemVerilog literals. Entries not given by the image are
+/// all-x. Words beyond [`MAX_MEM_ADDRESS`] are
+/// [`MemFileErrorKind::AddressOverflow`].
+pub fn parse_mem<T: Copy + Debug + PrimInt + FromPrimitive>(
+    text: &str,
+    format: MemFormat,
+) -> Result<Vec<Sv4State<T>>, MemFileError> {
+    let mut ret = Vec::new();
+    for (line, address, value) in parse_words(text, format)? {
+        if address > MAX_MEM_ADDRESS {
+            return Err(MemFileError {
+                line,
+                kind: MemFileErrorKind::AddressOverflow,
+            });
+        }
+        if ret.len() <= address {
+            ret.resize(address + 1, Sv4State::all_x());
+        }
+        ret[address] = value;
+    }
+    Ok(ret)
+}
+
+/// Parse a memory image into words with their line numbers and addresses in
+/// file order
+pub(crate) fn parse_words<T: Copy + Debug + PrimInt + FromPrimitive>(
+    text: &str,
+    format: MemFormat,
+) -> Result<Vec<(usize, usize, Sv4State<T>)>, MemFileError> {
+    let base = match format {
+        MemFormat::Binary => 'b',
+        MemFormat::Hex => 'h',
+    };
+
+    let mut ret = Vec::new();
+    // `None` after a word at the largest address
+    let mut address = Some(0usize);
+    for (line, token) in tokenize(text)? {
+        let err = |kind| MemFileError { line, kind };
+        if let Some(x) = token.strip_prefix('@') {
+            address = Some(
+                usize::from_str_radix(&x.replace('_', ""), 16)
+                    .map_err(|_| err(MemFileErrorKind::InvalidAddress(String::from(token))))?,
+            );
+        } else {
+            let value = format!("'{}{}", base, token)
+                .parse()
+                .map_err(|e| err(MemFileErrorKind::InvalidWord(String::from(token), e)))?;
+            let a = address.ok_or_else(|| err(MemFileErrorKind::AddressOverflow))?;
+            ret.push((line, a, value));
+            address = a.checked_add(1);
+        }
+    }
+    Ok(ret)
+}
+
+/// Parse a memory image like `$readmemh`
+pub fn readmemh<T: Copy + Debug + PrimInt + FromPrimitive>(
+    text: &str,
+) -> Result<Vec<Sv4State<T>>, MemFileError> {
+    parse_mem(text, MemFormat::Hex)
+}
+
+/// Parse a memory image like `$readmemb`
+pub fn readmemb<T: Copy + Debug + PrimInt + FromPrimitive>(
+    text: &str,
+) -> Result<Vec<Sv4State<T>>, MemFileError> {
+    parse_mem(text, MemFormat::Binary)
+}
+
+/// Dump a memory image like `$writememh`/`$writememb`
+///
+/// Each word is written as the lower `width` bits, one per line. Digits
+/// containing x are written as `x`, and other digits containing z as `z`. If
+/// `annotate` is true, each line is prefixed with the address like `@1f `,
+/// which [`parse_mem`] accepts.
+pub fn write_mem<T: Copy + Debug + PrimInt>(
+    mem: &[Sv4State<T>],
+    format: MemFormat,
+    width: u32,
+    annotate: bool,
+) -> String {
+    let bits = match format {
+        MemFormat::Binary => 1,
+        MemFormat::Hex => 4,
+    };
+    let width = width.clamp(1, T::zero().count_zeros()) as usize;
+    let digits = width.div_ceil(bits);
+
+    let mut ret = String::new();
+    for (address, value) in mem.iter().enumerate() {
+        let value = value.normalize();
+        if annotate {
+            ret.push_str(&format!("@{:x} ", address));
+        }
+        for i in (0..digits).rev() {
+            let shift = i * bits;
+            let mask = Sv4State::<T>::width_mask(bits.min(width - shift) as u32);
+            let c = if (value.x >> shift) & mask != T::zero() {
+                'x'
+            } else if (value.z >> shift) & mask != T::zero() {
+                'z'
+            } else {
+                let d = ((value.v >> shift) & mask).to_u32().unwrap_or(0);
+                std::char::from_digit(d, 16).unwrap_or('?')
+            };
+            ret.push(c);
+        }
+        ret.push('\n');
+    }
+    ret
+}
+
+/// Dump a memory image like `$writememh`
+pub fn writememh<T: Copy + Debug + PrimInt>(mem: &[Sv4State<T>], width: u32) -> String {
+    write_mem(mem, MemFormat::Hex, width, false)
+}
+
+/// Dump a memory image like `$writememb`
+pub fn writememb<T: Copy + Debug + PrimInt>(mem: &[Sv4State<T>], width: u32) -> String {
+    write_mem(mem, MemFormat::Binary, width, false)
+}
+
+/// Split into whitespace-separated tokens with line numbers, removing comments
+fn tokenize(text: &str) -> Result<Vec<(usize, &str)>, MemFileError> {
+    let mut ret = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(x) = rest.strip_prefix("//") {
+            rest = x.find('\n').map_or("", |i| &x[i..]);
+        } else if let Some(x) = rest.strip_prefix("/*") {
+            let end = x.find("*/").ok_or(MemFileError {
+                line,
+                kind: MemFileErrorKind::UnterminatedComment,
+            })?;
+            line += x[..end].matches('\n').count();
+            rest = &x[end + 2..];
+        } else if rest.starts_with('\n') {
+            line += 1;
+            rest = &rest[1..];
+        } else if rest.starts_with(char::is_whitespace) {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+        } else {
+            let end = token_end(rest);
+            ret.push((line, &rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    Ok(ret)
+}
+
+/// Length of the token at the start of `s`, which ends at whitespace or a comment
+fn token_end(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let comment = bytes[i] == b'/' && matches!(bytes.get(i + 1), Some(b'/') | Some(b'*'));
+        if bytes[i].is_ascii_whitespace() || comment {
+            break;
+        }
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readmem() {
+        let text = "
+            // comment
+            01 2x /* multi
+            line */ zz
+            @8 ff_ff // comment
+            @0000_0004 1
+        ";
+        let mem = readmemh::<u16>(text).unwrap();
+
+        assert_eq!(mem.len(), 9);
+        assert_eq!(mem[0], Sv4State::known(0x01));
+        assert_eq!(format!("{:x}", mem[1]), "002x");
+        assert_eq!(mem[2], Sv4State::all_z());
+        assert_eq!(mem[3], Sv4State::all_x());
+        assert_eq!(mem[4], Sv4State::known(0x1));
+        assert_eq!(mem[8], Sv4State::known(0xffff));
+
+        let mem = readmemb::<u8>("1x0z\n011").unwrap();
+        assert_eq!(format!("{:b}", mem[0]), "00001x0z");
+        assert_eq!(mem[1], Sv4State::known(0b011));
+    }
+
+    #[test]
+    fn writemem() {
+        let mem: Vec<Sv4State<u16>> = vec![
+            Sv4State::known(0x1a),
+            "16'h0_1x_z".parse().unwrap(),
+            Sv4State::new(0, 0x0002, 0x0001),
+        ];
+
+        assert_eq!(writememh(&mem, 12), "01a\n1xz\n00x\n");
+        assert_eq!(writememb(&mem[..1], 6), "011010\n");
+        let text = write_mem(&mem, MemFormat::Hex, 16, true);
+        assert_eq!(text, "@0 001a\n@1 01xz\n@2 000x\n");
+        assert_eq!(readmemh::<u16>(&text).unwrap()[..2], mem[..2]);
+    }
+
+    #[test]
+    fn readmem_error() {
+        assert_eq!(
+            readmemh::<u8>("00\n@xyz"),
+            Err(MemFileError {
+                line: 2,
+                kind: MemFileErrorKind::InvalidAddress(String::from("@xyz"))
+            })
+        );
+        assert_eq!(
+            readmemh::<u8>("/* \n */ 100"),
+            Err(MemFileError {
+                line: 2,
+                kind: MemFileErrorKind::InvalidWord(String::from("100"), ParseError::Overflow)
+            })
+        );
+        assert_eq!(
+            readmemb::<u8>("0 /* 1"),
+            Err(MemFileError {
+                line: 1,
+                kind: MemFileErrorKind::UnterminatedComment
+            })
+        );
+        assert_eq!(
+            parse_words::<u8>(
+                "@ffffffffffffffff 00
+01",
+                MemFormat::Hex
+            ),
+            Err(MemFileError {
+                line: 2,
+                kind: MemFileErrorKind::AddressOverflow
+            })
+        );
+    }
+}
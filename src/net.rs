@@ -0,0 +1,84 @@
+//! Resolution of multiple drivers by net types
+//!
+//! Drivers are combined per bit by the resolution tables of IEEE 1800-2017
+//! 6.6. All drivers are assumed to have the same strength; see
+//! [`crate::strength`] for strength-aware resolution.
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Bits driven to 0, 1 and x by any driver
+fn driven<T: Copy + Debug + PrimInt>(drivers: &[Sv4State<T>]) -> (T, T, T) {
+    drivers
+        .iter()
+        .fold((T::zero(), T::zero(), T::zero()), |(zero, one, x), d| {
+            let d = d.normalize();
+            let known = !(d.z | d.x);
+            (zero | (!d.v & known), one | d.v, x | d.x)
+        })
+}
+
+/// Resolve `drivers` of `wire`/`tri`
+///
+/// Disagreeing drivers give x, and bits driven by no driver are z.
+pub fn resolve_wire<T: Copy + Debug + PrimInt>(drivers: &[Sv4State<T>]) -> Sv4State<T> {
+    let (zero, one, x) = driven(drivers);
+    let none = !(zero | one | x);
+    let conflict = x | (zero & one);
+    Sv4State::new(one & !conflict, none, conflict)
+}
+
+/// Resolve `drivers` of `wand`/`triand`
+///
+/// Any 0 driver gives 0.
+pub fn resolve_wand<T: Copy + Debug + PrimInt>(drivers: &[Sv4State<T>]) -> Sv4State<T> {
+    let (zero, one, x) = driven(drivers);
+    let none = !(zero | one | x);
+    Sv4State::new(one & !zero & !x, none, x & !zero)
+}
+
+/// Resolve `drivers` of `wor`/`trior`
+///
+/// Any 1 driver gives 1.
+pub fn resolve_wor<T: Copy + Debug + PrimInt>(drivers: &[Sv4State<T>]) -> Sv4State<T> {
+    let (zero, one, x) = driven(drivers);
+    let none = !(zero | one | x);
+    Sv4State::new(one, none, x & !one)
+}
+
+/// Resolve `drivers` of `tri0`, whose undriven bits are 0
+pub fn resolve_tri0<T: Copy + Debug + PrimInt>(drivers: &[Sv4State<T>]) -> Sv4State<T> {
+    let ret = resolve_wire(drivers);
+    Sv4State::new(ret.v, T::zero(), ret.x)
+}
+
+/// Resolve `drivers` of `tri1`, whose undriven bits are 1
+pub fn resolve_tri1<T: Copy + Debug + PrimInt>(drivers: &[Sv4State<T>]) -> Sv4State<T> {
+    let ret = resolve_wire(drivers);
+    Sv4State::new(ret.v | ret.z, T::zero(), ret.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u16> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn resolve() {
+        // columns: every pair of 0, 1, x, z
+        let a = sv("16'b0000_1111_xxxx_zzzz");
+        let b = sv("16'b01xz_01xz_01xz_01xz");
+
+        assert_eq!(resolve_wire(&[a, b]), sv("16'b0xx0_x1x1_xxxx_01xz"));
+        assert_eq!(resolve_wand(&[a, b]), sv("16'b0000_01x1_0xxx_01xz"));
+        assert_eq!(resolve_wor(&[a, b]), sv("16'b01x0_1111_x1xx_01xz"));
+        assert_eq!(resolve_tri0(&[a, b]), sv("16'b0xx0_x1x1_xxxx_01x0"));
+        assert_eq!(resolve_tri1(&[a, b]), sv("16'b0xx0_x1x1_xxxx_01x1"));
+        assert_eq!(resolve_wire::<u16>(&[]), Sv4State::all_z());
+        assert_eq!(resolve_tri1::<u16>(&[]), Sv4State::ones());
+    }
+}
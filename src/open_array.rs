@@ -0,0 +1,201 @@
+//! DPI open array (`svOpenArrayHandle`) access (`ffi` feature)
+//!
+//! The `sv*` functions are provided by the simulator when the library is
+//! loaded as a DPI shared object.
+
+#![allow(non_camel_case_types)]
+
+use crate::{read_dpi_bits, svLogicVecVal, Sv4State};
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+use std::os::raw::{c_int, c_void};
+
+/// Handle of an open array defined by IEEE 1800-2017 Annex H
+pub type svOpenArrayHandle = *const c_void;
+
+extern "C" {
+    fn svLeft(h: svOpenArrayHandle, d: c_int) -> c_int;
+    fn svRight(h: svOpenArrayHandle, d: c_int) -> c_int;
+    fn svLow(h: svOpenArrayHandle, d: c_int) -> c_int;
+    fn svHigh(h: svOpenArrayHandle, d: c_int) -> c_int;
+    fn svSize(h: svOpenArrayHandle, d: c_int) -> c_int;
+    fn svDimensions(h: svOpenArrayHandle) -> c_int;
+    fn svGetArrElemPtr1(h: svOpenArrayHandle, indx1: c_int) -> *mut c_void;
+}
+
+/// Open array argument like `input logic [N:0] a[]`
+///
+/// Dimension 0 is the packed dimension, and dimension 1 is the first unpacked
+/// dimension.
+pub struct OpenArray {
+    handle: svOpenArrayHandle,
+}
+
+impl OpenArray {
+    /// Wrap `handle`
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be an open array handle given by the simulator, and valid
+    /// while the returned value is used.
+    pub unsafe fn new(handle: svOpenArrayHandle) -> Self {
+        OpenArray { handle }
+    }
+
+    /// Number of unpacked dimensions
+    pub fn dimensions(&self) -> u32 {
+        unsafe { svDimensions(self.handle).max(0) as u32 }
+    }
+
+    /// Left bound of dimension `d`
+    pub fn left(&self, d: u32) -> i32 {
+        unsafe { svLeft(self.handle, d as c_int) }
+    }
+
+    /// Right bound of dimension `d`
+    pub fn right(&self, d: u32) -> i32 {
+        unsafe { svRight(self.handle, d as c_int) }
+    }
+
+    /// Lower bound of dimension `d`
+    pub fn low(&self, d: u32) -> i32 {
+        unsafe { svLow(self.handle, d as c_int) }
+    }
+
+    /// Upper bound of dimension `d`
+    pub fn high(&self, d: u32) -> i32 {
+        unsafe { svHigh(self.handle, d as c_int) }
+    }
+
+    /// Number of elements of dimension `d`
+    pub fn size(&self, d: u32) -> usize {
+        unsafe { svSize(self.handle, d as c_int).max(0) as usize }
+    }
+
+    /// Width of each packed element
+    pub fn element_width(&self) -> u32 {
+        self.size(0).max(1) as u32
+    }
+
+    /// Element at `index` of the first unpacked dimension
+    ///
+    /// `None` is returned if `index` is out of bounds. Bits above the payload
+    /// width are dropped.
+    pub fn get<T: Copy + Debug + PrimInt + FromPrimitive>(
+        &self,
+        index: i32,
+    ) -> Option<Sv4State<T>> {
+        if self.dimensions() == 0 || index < self.low(1) || index > self.high(1) {
+            return None;
+        }
+        let ptr = unsafe { svGetArrElemPtr1(self.handle, index as c_int) } as *const svLogicVecVal;
+        if ptr.is_null() {
+            return None;
+        }
+        let width = self.element_width();
+        let data = unsafe { std::slice::from_raw_parts(ptr, width.div_ceil(32) as usize) };
+        Some(read_dpi_bits(data, 0, width))
+    }
+
+    /// Elements of the first unpacked dimension from the left bound to the
+    /// right bound
+    pub fn iter<T: Copy + Debug + PrimInt + FromPrimitive>(
+        &self,
+    ) -> impl Iterator<Item = Sv4State<T>> + '_ {
+        let (left, right) = if self.dimensions() == 0 {
+            (0, -1)
+        } else {
+            (self.left(1), self.right(1))
+        };
+        let indices: Vec<i32> = if left <= right {
+            (left..=right).collect()
+        } else {
+            (right..=left).rev().collect()
+        };
+        indices.into_iter().filter_map(move |i| self.get(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Open array of 12-bit elements as `logic [11:0] a[3:1]`
+    struct Mock {
+        elems: Vec<svLogicVecVal>,
+    }
+
+    fn mock(h: svOpenArrayHandle) -> &'static Mock {
+        unsafe { &*(h as *const Mock) }
+    }
+
+    #[no_mangle]
+    extern "C" fn svLeft(_: svOpenArrayHandle, d: c_int) -> c_int {
+        [11, 3][d as usize]
+    }
+
+    #[no_mangle]
+    extern "C" fn svRight(_: svOpenArrayHandle, d: c_int) -> c_int {
+        [0, 1][d as usize]
+    }
+
+    #[no_mangle]
+    extern "C" fn svLow(_: svOpenArrayHandle, d: c_int) -> c_int {
+        [0, 1][d as usize]
+    }
+
+    #[no_mangle]
+    extern "C" fn svHigh(_: svOpenArrayHandle, d: c_int) -> c_int {
+        [11, 3][d as usize]
+    }
+
+    #[no_mangle]
+    extern "C" fn svSize(_: svOpenArrayHandle, d: c_int) -> c_int {
+        [12, 3][d as usize]
+    }
+
+    #[no_mangle]
+    extern "C" fn svDimensions(_: svOpenArrayHandle) -> c_int {
+        1
+    }
+
+    #[no_mangle]
+    extern "C" fn svGetArrElemPtr1(h: svOpenArrayHandle, indx1: c_int) -> *mut c_void {
+        &mock(h).elems[(indx1 - 1) as usize] as *const svLogicVecVal as *mut c_void
+    }
+
+    #[test]
+    fn open_array() {
+        let m = Mock {
+            elems: vec![
+                svLogicVecVal {
+                    aval: 0x123,
+                    bval: 0,
+                },
+                svLogicVecVal {
+                    aval: 0xf00,
+                    bval: 0x0f0,
+                },
+                svLogicVecVal {
+                    aval: 0xfff,
+                    bval: 0xfff,
+                },
+            ],
+        };
+        let array = unsafe { OpenArray::new(&m as *const Mock as svOpenArrayHandle) };
+
+        assert_eq!(array.dimensions(), 1);
+        assert_eq!(array.element_width(), 12);
+        assert_eq!(array.get::<u16>(1), Some(Sv4State::known(0x123)));
+        assert_eq!(array.get::<u16>(4), None);
+        let values: Vec<Sv4State<u16>> = array.iter().collect();
+        assert_eq!(
+            values,
+            vec![
+                Sv4State::all_x_width(12),
+                Sv4State::new(0xf00, 0x0f0, 0),
+                Sv4State::known(0x123),
+            ]
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Operators with SystemVerilog 4-state semantics
+//!
+//! Bitwise operators work per bit, and z is treated as x as in SystemVerilog.
+//! Arithmetic operators give all-x if any bit of the operands is x/z, and
+//! wrap around otherwise. The right-hand side can be a plain integer, which is
+//! a fully known value of the same width. Evaluations are counted by
+//! [`crate::xcount`] if it is enabled. Operands need not be normalized, and
+//! results are checked to be normalized by debug assertions.
+
+#[cfg(feature = "std")]
+use crate::xcount;
+use crate::{Bit4, Sv4State};
+use core::fmt::Debug;
+use core::ops::{Add, BitAnd, BitOr, BitXor, Mul, Not, Sub};
+use num_traits::{PrimInt, WrappingAdd, WrappingMul, WrappingSub};
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Bit mask of x/z bits
+    fn unknown_mask(&self) -> T {
+        self.z | self.x
+    }
+
+    /// Count an evaluation of `op` giving `self`
+    #[cfg(feature = "std")]
+    fn count(self, op: &'static str) -> Self {
+        debug_assert!(self.is_normalized(), "{} gave {:?}", op, self);
+        xcount::record(op, self.x != T::zero());
+        self
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn count(self, op: &'static str) -> Self {
+        debug_assert!(self.is_normalized(), "{} gave {:?}", op, self);
+        self
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> BitAnd for Sv4State<T> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        let (a, b) = (self.normalize(), rhs.normalize());
+        let (ua, ub) = (a.unknown_mask(), b.unknown_mask());
+        let zero = (!a.v & !ua) | (!b.v & !ub);
+        Sv4State::new(a.v & b.v, T::zero(), (ua | ub) & !zero).count("&")
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> BitOr for Sv4State<T> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        let (a, b) = (self.normalize(), rhs.normalize());
+        let one = a.v | b.v;
+        Sv4State::new(one, T::zero(), (a.unknown_mask() | b.unknown_mask()) & !one).count("|")
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> BitXor for Sv4State<T> {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        let (a, b) = (self.normalize(), rhs.normalize());
+        Sv4State::new(a.v ^ b.v, T::zero(), a.unknown_mask() | b.unknown_mask()).count("^")
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Not for Sv4State<T> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        let a = self.normalize();
+        Sv4State::new(!a.v, T::zero(), a.unknown_mask()).count("~")
+    }
+}
+
+macro_rules! arith_op {
+    ($tr:ident, $method:ident, $op:expr, $wrapping_tr:ident, $wrapping:ident) => {
+        impl<T: Copy + Debug + PrimInt + $wrapping_tr> $tr for Sv4State<T> {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                let ret = if self.is_known() && rhs.is_known() {
+                    Sv4State::known(self.v.$wrapping(&rhs.v))
+                } else {
+                    Sv4State::all_x()
+                };
+                ret.count($op)
+            }
+        }
+    };
+}
+
+arith_op!(Add, add, "+", WrappingAdd, wrapping_add);
+arith_op!(Sub, sub, "-", WrappingSub, wrapping_sub);
+arith_op!(Mul, mul, "*", WrappingMul, wrapping_mul);
+
+macro_rules! int_rhs_op {
+    ($tr:ident, $method:ident, $($bound:ident),*) => {
+        impl<T: Copy + Debug + PrimInt $(+ $bound)*> $tr<T> for Sv4State<T> {
+            type Output = Self;
+
+            fn $method(self, rhs: T) -> Self {
+                self.$method(Sv4State::known(rhs))
+            }
+        }
+    };
+}
+
+int_rhs_op!(BitAnd, bitand,);
+int_rhs_op!(BitOr, bitor,);
+int_rhs_op!(BitXor, bitxor,);
+int_rhs_op!(Add, add, WrappingAdd);
+int_rhs_op!(Sub, sub, WrappingSub);
+int_rhs_op!(Mul, mul, WrappingMul);
+
+impl<T: Copy + Debug + PrimInt + WrappingAdd + WrappingSub> Sv4State<T> {
+    /// `width`-bit addition with the carry out and the signed overflow
+    ///
+    /// Bits above `width` of the operands are ignored. If any bit of the
+    /// operands is x/z, the result is all-x and both flags are x.
+    pub fn add_with_flags(self, rhs: Self, width: u32) -> (Self, Bit4, Bit4) {
+        self.with_flags(rhs, width, false)
+    }
+
+    /// `width`-bit subtraction with the borrow and the signed overflow
+    ///
+    /// The carry flag is set if `self < rhs` as unsigned values. Unknown
+    /// operands are handled like [`Sv4State::add_with_flags`].
+    pub fn sub_with_flags(self, rhs: Self, width: u32) -> (Self, Bit4, Bit4) {
+        self.with_flags(rhs, width, true)
+    }
+
+    fn with_flags(self, rhs: Self, width: u32, sub: bool) -> (Self, Bit4, Bit4) {
+        let op = if sub { "-" } else { "+" };
+        let width = width.clamp(1, T::zero().count_zeros());
+        let mask = Sv4State::<T>::width_mask(width);
+        if (self.unknown_mask() | rhs.unknown_mask()) & mask != T::zero() {
+            return (Sv4State::all_x().count(op), Bit4::X, Bit4::X);
+        }
+
+        let (a, b) = (self.v & mask, rhs.v & mask);
+        let ret = if sub {
+            a.wrapping_sub(&b)
+        } else {
+            a.wrapping_add(&b)
+        };
+        // carry out of the most significant bit, without comparisons on `T`
+        // which would be signed for signed payloads
+        let sign = |x: T| x & (T::one() << (width - 1) as usize) != T::zero();
+        let (a_msb, b_msb, ret_msb) = (sign(a), sign(b), sign(ret));
+        let (carry, overflow) = if sub {
+            (
+                (!a_msb && b_msb) || ((!a_msb || b_msb) && ret_msb),
+                a_msb != b_msb && ret_msb != a_msb,
+            )
+        } else {
+            (
+                (a_msb && b_msb) || ((a_msb || b_msb) && !ret_msb),
+                a_msb == b_msb && ret_msb != a_msb,
+            )
+        };
+        let ret = Sv4State::known(ret & mask).count(op);
+        (ret, carry.into(), overflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn bitwise() {
+        let a = sv("8'b0011_xzxz");
+        let b = sv("8'b0101_0011");
+
+        assert_eq!(a & b, sv("8'b0001_00xx"));
+        assert_eq!(a | b, sv("8'b0111_xx11"));
+        assert_eq!(a ^ b, sv("8'b0110_xxxx"));
+        assert_eq!(!a, sv("8'b1100_xxxx"));
+        assert_eq!(a & 0x0c, sv("8'b0000_xx00"));
+        assert_eq!(a | 0xf0, sv("8'b1111_xxxx"));
+
+        // operands out of normal form give normalized results
+        let raw = Sv4State {
+            v: 0xffu8,
+            z: 0x0f,
+            x: 0x03,
+        };
+        assert_eq!((raw & 0xff).x, 0x0f);
+        assert_eq!((!raw | raw).v, 0xf0);
+    }
+
+    #[test]
+    fn arith() {
+        let a = Sv4State::<u8>::known(0xf0);
+
+        assert_eq!(a + 0x20, Sv4State::known(0x10));
+        assert_eq!(a - Sv4State::known(0xf1), Sv4State::known(0xff));
+        assert_eq!(a * 2, Sv4State::known(0xe0));
+        assert_eq!(a + sv("8'b0000_000z"), Sv4State::all_x());
+    }
+
+    #[test]
+    fn flags() {
+        let a = Sv4State::<u8>::known(0x7);
+
+        assert_eq!(
+            a.add_with_flags(Sv4State::known(0x1), 4),
+            (Sv4State::known(0x8), Bit4::Zero, Bit4::One)
+        );
+        assert_eq!(
+            a.add_with_flags(Sv4State::known(0xfa), 4),
+            (Sv4State::known(0x1), Bit4::One, Bit4::Zero)
+        );
+        assert_eq!(
+            Sv4State::<u8>::known(0xff).add_with_flags(Sv4State::known(0x80), 8),
+            (Sv4State::known(0x7f), Bit4::One, Bit4::One)
+        );
+        assert_eq!(
+            a.sub_with_flags(Sv4State::known(0x8), 4),
+            (Sv4State::known(0xf), Bit4::One, Bit4::One)
+        );
+        assert_eq!(
+            a.sub_with_flags(sv("8'bz000_0001"), 4),
+            (Sv4State::known(0x6), Bit4::Zero, Bit4::Zero)
+        );
+        assert_eq!(
+            a.add_with_flags(sv("8'b0000_000x"), 4),
+            (Sv4State::all_x(), Bit4::X, Bit4::X)
+        );
+
+        // carry and borrow are unsigned for signed payloads
+        let (m1, p1) = (Sv4State::<i8>::known(-1), Sv4State::<i8>::known(1));
+        assert_eq!(
+            m1.add_with_flags(p1, 8),
+            (Sv4State::known(0), Bit4::One, Bit4::Zero)
+        );
+        assert_eq!(
+            m1.sub_with_flags(p1, 8),
+            (Sv4State::known(-2), Bit4::Zero, Bit4::Zero)
+        );
+        assert_eq!(
+            p1.sub_with_flags(m1, 8),
+            (Sv4State::known(2), Bit4::One, Bit4::Zero)
+        );
+        assert_eq!(
+            Sv4State::<i8>::known(127).add_with_flags(p1, 8),
+            (Sv4State::known(-128), Bit4::Zero, Bit4::One)
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Packet segmentation of per-cycle stream samples
+
+use crate::{Bit4, Sv4State, Sv4Vec};
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Qualifier signal of a stream
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Qualifier {
+    Valid,
+    Start,
+    End,
+}
+
+/// Problem found by [`PacketSegmenter`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SegmentIssue {
+    /// The qualifier is x/z at `cycle`
+    ///
+    /// An unknown valid drops the beat, and an unknown start/end is regarded
+    /// as 0.
+    UnknownQualifier { cycle: u64, qualifier: Qualifier },
+    /// A packet started at `cycle` while the previous one was not ended, and
+    /// the previous one was discarded
+    MissingEnd { cycle: u64 },
+    /// A valid beat outside packets at `cycle` was dropped
+    MissingStart { cycle: u64 },
+}
+
+/// Packet collected by [`PacketSegmenter`]
+#[derive(Clone, Debug)]
+pub struct Packet<T: Copy + Debug> {
+    /// cycle of the start beat
+    pub start: u64,
+    /// cycle of the end beat
+    pub end: u64,
+    /// number of beats
+    pub beats: usize,
+    /// beats concatenated with the first beat at the least significant bits
+    pub payload: Sv4Vec<T>,
+}
+
+impl<T: Copy + Debug + PrimInt> PartialEq for Packet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start
+            && self.end == other.end
+            && self.beats == other.beats
+            && self.payload == other.payload
+    }
+}
+
+/// Groups per-cycle samples into packets by valid/start/end qualifiers
+///
+/// A packet is the valid beats from a beat with start to a beat with end. A
+/// beat with both start and end is a single-beat packet.
+#[derive(Clone, Debug)]
+pub struct PacketSegmenter<T: Copy + Debug> {
+    width: usize,
+    cycle: u64,
+    current: Option<(u64, Vec<Sv4Vec<T>>)>,
+    issues: Vec<SegmentIssue>,
+}
+
+impl<T: Copy + Debug + PrimInt> PacketSegmenter<T> {
+    /// Create a segmenter of `width`-bit beats
+    ///
+    /// `width` is saturated to the payload width.
+    pub fn new(width: usize) -> Self {
+        PacketSegmenter {
+            width: width.min(T::zero().count_zeros() as usize),
+            cycle: 0,
+            current: None,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Sample of the next cycle, which gives a packet if it ended
+    pub fn push(
+        &mut self,
+        valid: Bit4,
+        start: Bit4,
+        end: Bit4,
+        data: Sv4State<T>,
+    ) -> Option<Packet<T>> {
+        let cycle = self.cycle;
+        self.cycle += 1;
+
+        let mut qualifier = |value: Bit4, qualifier| {
+            if !value.is_known() {
+                self.issues
+                    .push(SegmentIssue::UnknownQualifier { cycle, qualifier });
+            }
+            value == Bit4::One
+        };
+        if !qualifier(valid, Qualifier::Valid) {
+            return None;
+        }
+        let start = qualifier(start, Qualifier::Start);
+        let end = qualifier(end, Qualifier::End);
+
+        if start {
+            if self.current.is_some() {
+                self.issues.push(SegmentIssue::MissingEnd { cycle });
+            }
+            self.current = Some((cycle, Vec::new()));
+        }
+        let beats = match &mut self.current {
+            Some((_, beats)) => beats,
+            None => {
+                self.issues.push(SegmentIssue::MissingStart { cycle });
+                return None;
+            }
+        };
+        beats.push(Sv4Vec::from_value(data, self.width));
+
+        if end {
+            let (start, mut beats) = self.current.take()?;
+            beats.reverse();
+            Some(Packet {
+                start,
+                end: cycle,
+                beats: beats.len(),
+                payload: Sv4Vec::concat(&beats),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Whether a packet is started and not ended
+    pub fn in_packet(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Problems found so far
+    pub fn issues(&self) -> &[SegmentIssue] {
+        &self.issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment() {
+        use crate::SvLogic::{One, Zero, X};
+
+        let mut seg = PacketSegmenter::<u8>::new(8);
+        let beats = [
+            (One, One, Zero, 0x11),
+            (Zero, X, X, 0xff),
+            (One, Zero, One, 0x22),
+            (One, One, One, 0x33),
+            (One, Zero, Zero, 0x44),
+            (X, Zero, Zero, 0x55),
+            (One, One, X, 0x66),
+            (One, One, One, 0x77),
+        ];
+        let packets: Vec<_> = beats
+            .iter()
+            .filter_map(|(v, s, e, d)| seg.push(*v, *s, *e, Sv4State::known(*d)))
+            .collect();
+
+        assert_eq!(packets.len(), 3);
+        assert_eq!(
+            (packets[0].start, packets[0].end, packets[0].beats),
+            (0, 2, 2)
+        );
+        assert_eq!(format!("{:b}", packets[0].payload), "0010001000010001");
+        assert_eq!((packets[1].start, packets[1].beats), (3, 1));
+        assert_eq!(packets[2].start, 7);
+        assert!(!seg.in_packet());
+        assert_eq!(
+            seg.issues(),
+            [
+                SegmentIssue::MissingStart { cycle: 4 },
+                SegmentIssue::UnknownQualifier {
+                    cycle: 5,
+                    qualifier: Qualifier::Valid
+                },
+                SegmentIssue::UnknownQualifier {
+                    cycle: 6,
+                    qualifier: Qualifier::End
+                },
+                SegmentIssue::MissingEnd { cycle: 7 },
+            ]
+        );
+    }
+}
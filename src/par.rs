@@ -0,0 +1,51 @@
+//! Parallel conversion of large DPI canonical buffers
+
+use crate::{svLogicVecVal, Sv4State};
+use num_traits::{FromPrimitive, PrimInt};
+use rayon::prelude::*;
+use std::fmt::Debug;
+
+/// Number of values converted by a task
+const CHUNK_VALUES: usize = 4096;
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive + Send> Sv4State<T> {
+    /// Convert from DPI canonical representation in parallel
+    ///
+    /// `data` is split into chunks of whole values, which are converted on the
+    /// rayon thread pool. The values are the same as [`Sv4State::from_dpi`],
+    /// in the same order.
+    pub fn par_from_dpi(data: &[svLogicVecVal]) -> Vec<Self> {
+        let words_per_value = (T::zero().count_zeros() as usize / 32).max(1);
+        data.par_chunks(words_per_value * CHUNK_VALUES)
+            .flat_map_iter(Self::iter_from_dpi)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn par_from_dpi() {
+        let data: Vec<_> = (0..10_001u32)
+            .map(|i| svLogicVecVal {
+                aval: i.wrapping_mul(0x9e37_79b9),
+                bval: i.rotate_left(i % 32) & 0x0f0f_0f0f,
+            })
+            .collect();
+
+        assert_eq!(
+            Sv4State::<u8>::par_from_dpi(&data),
+            Sv4State::from_dpi(&data)
+        );
+        assert_eq!(
+            Sv4State::<u64>::par_from_dpi(&data),
+            Sv4State::from_dpi(&data)
+        );
+        assert_eq!(
+            Sv4State::<u128>::par_from_dpi(&data[..9]),
+            Sv4State::from_dpi(&data[..9])
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Bit patterns with don't-care bits
+
+use crate::literal::ParseError;
+use crate::Sv4State;
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// Pattern matched against [`Sv4State`]
+///
+/// Bits set in `care` must equal the corresponding bits of `pattern`
+/// (including x/z), and other bits are don't-care.
+#[derive(Copy, Clone, Debug)]
+pub struct Sv4Pattern<T: Copy + Debug> {
+    /// expected value of cared bits
+    pub pattern: Sv4State<T>,
+    /// bit flag of cared bits
+    pub care: T,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4Pattern<T> {
+    /// Pattern matching known `value` on `care` bits
+    pub fn new(value: T, care: T) -> Self {
+        Sv4Pattern {
+            pattern: Sv4State::known(value & care),
+            care,
+        }
+    }
+
+    /// Pattern of a golden vector, where x bits of `expected` are don't-care
+    ///
+    /// If `z_dont_care`, z bits are also don't-care, and otherwise they
+    /// match only z.
+    pub fn from_expected(expected: Sv4State<T>, z_dont_care: bool) -> Self {
+        let expected = expected.normalize();
+        let dont_care = if z_dont_care {
+            expected.x | expected.z
+        } else {
+            expected.x
+        };
+        Sv4Pattern {
+            pattern: expected,
+            care: !dont_care,
+        }
+    }
+
+    /// Pattern matching any value
+    pub fn any() -> Self {
+        Sv4Pattern {
+            pattern: Sv4State::zeros(),
+            care: T::zero(),
+        }
+    }
+
+    /// Whether `value` matches the pattern
+    pub fn matches(&self, value: &Sv4State<T>) -> bool {
+        let value = value.normalize();
+        let pattern = self.pattern.normalize();
+        let diff = (value.v ^ pattern.v) | (value.z ^ pattern.z) | (value.x ^ pattern.x);
+        diff & self.care == T::zero()
+    }
+
+    /// Whether any cared bit of `value` is x or z
+    pub fn has_unknown_in(&self, value: &Sv4State<T>) -> bool {
+        (value.z | value.x) & self.care != T::zero()
+    }
+
+    /// Cared planes of the pattern
+    fn cared(&self) -> Sv4State<T> {
+        let pattern = self.pattern.normalize();
+        Sv4State {
+            v: pattern.v & self.care,
+            z: pattern.z & self.care,
+            x: pattern.x & self.care,
+        }
+    }
+
+    /// Pattern matching the values matched by both patterns, or `None` if
+    /// no value matches both
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let (a, b) = (self.cared(), other.cared());
+        let diff = (a.v ^ b.v) | (a.z ^ b.z) | (a.x ^ b.x);
+        if diff & self.care & other.care != T::zero() {
+            return None;
+        }
+        Some(Sv4Pattern {
+            pattern: Sv4State::new(a.v | b.v, a.z | b.z, a.x | b.x),
+            care: self.care | other.care,
+        })
+    }
+
+    /// Whether some value matches both patterns
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intersect(other).is_some()
+    }
+
+    /// Whether all values matched by `other` match this pattern
+    pub fn covers(&self, other: &Self) -> bool {
+        self.care & !other.care == T::zero() && self.matches(&other.cared())
+    }
+}
+
+/// Parse a literal like `8'b1??0_xx10`, where `?` is don't-care
+///
+/// Other digits including x/z must match exactly. Like `?` in `casez`, a most
+/// significant `?` is extended to the size, and bits above the size must be 0.
+impl<T: Copy + Debug + PrimInt + FromPrimitive> FromStr for Sv4Pattern<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // `?` is z for the literal parser, so locate them by the z bits of
+        // the literal with only `?` digits
+        let (prefix, digits) = match s.find('\'') {
+            Some(i) => {
+                let rest = &s[i + 1..];
+                let mut j = i + 1 + rest.len() - rest.trim_start().len();
+                if let Some('s') | Some('S') = s[j..].chars().next() {
+                    j += 1;
+                }
+                j += s[j..].chars().next().map_or(0, char::len_utf8);
+                (&s[..j], &s[j..])
+            }
+            None => ("", s),
+        };
+        let mask: String = digits
+            .chars()
+            .map(|c| match c {
+                '?' => 'z',
+                '_' => '_',
+                _ => '0',
+            })
+            .collect();
+
+        let value: Sv4State<T> = s.parse()?;
+        let dont_care = format!("{}{}", prefix, mask).parse::<Sv4State<T>>()?.z;
+        Ok(Sv4Pattern {
+            pattern: Sv4State::new(value.v, value.z & !dont_care, value.x),
+            care: !dont_care,
+        })
+    }
+}
+
+/// Payload-width binary digits with `?` for don't-care bits
+impl<T: Copy + Debug + PrimInt> std::fmt::Display for Sv4Pattern<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pattern = self.cared();
+        for i in (0..T::zero().count_zeros() as usize).rev() {
+            let c = if (self.care >> i) & T::one() == T::zero() {
+                '?'
+            } else {
+                pattern.bit(i).to_char()
+            };
+            std::fmt::Write::write_char(f, c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Table of patterns like an instruction decoder, where the first matching
+/// entry wins
+#[derive(Clone, Debug, Default)]
+pub struct Sv4MatchTable<T: Copy + Debug, V> {
+    entries: Vec<(Sv4Pattern<T>, V)>,
+}
+
+impl<T: Copy + Debug + PrimInt, V> Sv4MatchTable<T, V> {
+    pub fn new() -> Self {
+        Sv4MatchTable {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add an entry after the existing ones
+    pub fn entry(mut self, pattern: Sv4Pattern<T>, value: V) -> Self {
+        self.entries.push((pattern, value));
+        self
+    }
+
+    pub fn entries(&self) -> &[(Sv4Pattern<T>, V)] {
+        &self.entries
+    }
+
+    /// Value of the first entry matching `value`
+    pub fn lookup(&self, value: &Sv4State<T>) -> Option<&V> {
+        self.entries
+            .iter()
+            .find(|(p, _)| p.matches(value))
+            .map(|(_, v)| v)
+    }
+
+    /// Indices of entries which never match first, as an earlier entry
+    /// covers them
+    pub fn shadowed(&self) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|i| {
+                self.entries[..*i]
+                    .iter()
+                    .any(|(p, _)| p.covers(&self.entries[*i].0))
+            })
+            .collect()
+    }
+}
+
+/// Whether `actual` equals `expected` on the bits not x in `expected`
+///
+/// Other bits including z must be case-equal. See
+/// [`Sv4Pattern::from_expected`] to also ignore z bits.
+pub fn matches_expected<T: Copy + Debug + PrimInt>(
+    actual: &Sv4State<T>,
+    expected: &Sv4State<T>,
+) -> bool {
+    Sv4Pattern::from_expected(*expected, false).matches(actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        let pat = Sv4Pattern::<u8>::new(0b1000_0001, 0b1100_0011);
+
+        assert!(pat.matches(&Sv4State::known(0b1011_1101)));
+        assert!(!pat.matches(&Sv4State::known(0b1111_1101)));
+        assert!(pat.matches(&Sv4State::new(0b1000_0001, 0, 0b0011_1100)));
+        assert!(!pat.matches(&Sv4State::new(0b1000_0000, 0, 0b0000_0001)));
+        assert!(pat.has_unknown_in(&Sv4State::new(0b1000_0000, 0, 0b0000_0001)));
+        assert!(Sv4Pattern::any().matches(&Sv4State::<u8>::all_x()));
+    }
+
+    #[test]
+    fn expected() {
+        let expected: Sv4State<u8> = "8'b10xx_z01x".parse().unwrap();
+        assert!(matches_expected(
+            &"8'b1001_z011".parse().unwrap(),
+            &expected
+        ));
+        assert!(matches_expected(
+            &"8'b10zx_z01x".parse().unwrap(),
+            &expected
+        ));
+        assert!(!matches_expected(
+            &"8'b1001_1011".parse().unwrap(),
+            &expected
+        ));
+        assert!(!matches_expected(
+            &"8'b0001_z011".parse().unwrap(),
+            &expected
+        ));
+
+        let pat = Sv4Pattern::from_expected(expected, true);
+        assert!(pat.matches(&"8'b1001_1011".parse().unwrap()));
+        assert_eq!(pat.care, 0b1100_0110);
+    }
+
+    #[test]
+    fn parse() {
+        let pat: Sv4Pattern<u16> = "8'b1??0_xx10".parse().unwrap();
+        assert_eq!(pat.to_string(), "000000001??0xx10");
+        assert!(pat.matches(&"16'b1010_xx10".parse().unwrap()));
+        assert!(!pat.matches(&"16'b1010_1110".parse().unwrap()));
+        assert_eq!(
+            "8'h?z".parse::<Sv4Pattern<u8>>().unwrap().to_string(),
+            "????zzzz"
+        );
+        assert_eq!(
+            "'b?1".parse::<Sv4Pattern<u8>>().unwrap().to_string(),
+            "???????1"
+        );
+        assert_eq!("8'd?".parse::<Sv4Pattern<u8>>().unwrap().care, 0);
+        assert!("8'b1?2".parse::<Sv4Pattern<u8>>().is_err());
+
+        let a: Sv4Pattern<u8> = "8'b1???_????".parse().unwrap();
+        let b: Sv4Pattern<u8> = "8'b??01_????".parse().unwrap();
+        assert_eq!(a.intersect(&b).unwrap().to_string(), "1?01????");
+        assert!(!a.overlaps(&"8'b0???_????".parse().unwrap()));
+        assert!(a.covers(&a.intersect(&b).unwrap()) && !a.covers(&b));
+
+        let table = Sv4MatchTable::new()
+            .entry("8'b0000_0000".parse().unwrap(), "nop")
+            .entry("8'b0???_????".parse().unwrap(), "alu")
+            .entry("8'b01??_????".parse().unwrap(), "shift")
+            .entry("8'b1???_????".parse().unwrap(), "mem");
+        assert_eq!(table.lookup(&Sv4State::known(0x00)), Some(&"nop"));
+        assert_eq!(table.lookup(&Sv4State::known(0x45)), Some(&"alu"));
+        assert_eq!(table.lookup(&Sv4State::new(0, 0, 0x80)), None);
+        assert_eq!(table.shadowed(), [2]);
+    }
+}
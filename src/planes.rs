@@ -0,0 +1,249 @@
+//! Structure-of-arrays container of 4-state values
+
+use crate::{Bit4, Sv4State, SvLogic};
+use alloc::{vec, vec::Vec};
+use core::fmt::Debug;
+use num_traits::PrimInt;
+
+/// Values stored as contiguous planes of v, z and x
+///
+/// Bulk operations are element-wise loops over the planes, which vectorize
+/// far better than loops over `Vec<Sv4State<T>>`. They have the same results
+/// as the operators of [`Sv4State`], but are not counted by
+/// [`xcount`](crate::xcount). Binary operations stop at the shorter operand.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sv4StatePlanes<T> {
+    v: Vec<T>,
+    z: Vec<T>,
+    x: Vec<T>,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4StatePlanes<T> {
+    pub fn new() -> Self {
+        Sv4StatePlanes {
+            v: Vec::new(),
+            z: Vec::new(),
+            x: Vec::new(),
+        }
+    }
+
+    /// Create from planes, which are truncated to the shortest
+    ///
+    /// The values are normalized.
+    pub fn from_planes(mut v: Vec<T>, mut z: Vec<T>, mut x: Vec<T>) -> Self {
+        let len = v.len().min(z.len()).min(x.len());
+        v.truncate(len);
+        z.truncate(len);
+        x.truncate(len);
+        for ((v, z), x) in v.iter_mut().zip(&mut z).zip(&x) {
+            *z = *z & !*x;
+            *v = *v & !(*z | *x);
+        }
+        Sv4StatePlanes { v, z, x }
+    }
+
+    pub fn from_states(values: &[Sv4State<T>]) -> Self {
+        let mut ret = Self::new();
+        for value in values {
+            ret.push(*value);
+        }
+        ret
+    }
+
+    pub fn to_states(&self) -> Vec<Sv4State<T>> {
+        self.iter().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.v.is_empty()
+    }
+
+    pub fn push(&mut self, value: Sv4State<T>) {
+        let value = value.normalize();
+        self.v.push(value.v);
+        self.z.push(value.z);
+        self.x.push(value.x);
+    }
+
+    pub fn get(&self, i: usize) -> Option<Sv4State<T>> {
+        Some(Sv4State {
+            v: *self.v.get(i)?,
+            z: self.z[i],
+            x: self.x[i],
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Sv4State<T>> + '_ {
+        self.v
+            .iter()
+            .zip(&self.z)
+            .zip(&self.x)
+            .map(|((v, z), x)| Sv4State {
+                v: *v,
+                z: *z,
+                x: *x,
+            })
+    }
+
+    /// Plane of value bits
+    pub fn v(&self) -> &[T] {
+        &self.v
+    }
+
+    /// Plane of hi-z flags
+    pub fn z(&self) -> &[T] {
+        &self.z
+    }
+
+    /// Plane of x flags
+    pub fn x(&self) -> &[T] {
+        &self.x
+    }
+
+    /// Element-wise operation over the planes of `self` and `rhs`
+    fn zip_with(&self, rhs: &Self, f: impl Fn(T, T, T, T, T, T) -> (T, T)) -> Self {
+        let len = self.len().min(rhs.len());
+        let mut ret = Sv4StatePlanes {
+            v: Vec::with_capacity(len),
+            z: vec![T::zero(); len],
+            x: Vec::with_capacity(len),
+        };
+        for i in 0..len {
+            let (v, x) = f(
+                self.v[i], self.z[i], self.x[i], rhs.v[i], rhs.z[i], rhs.x[i],
+            );
+            ret.v.push(v);
+            ret.x.push(x);
+        }
+        ret
+    }
+
+    /// Element-wise `&`
+    pub fn and(&self, rhs: &Self) -> Self {
+        self.zip_with(rhs, |av, az, ax, bv, bz, bx| {
+            let (ua, ub) = (az | ax, bz | bx);
+            let zero = (!av & !ua) | (!bv & !ub);
+            (av & bv, (ua | ub) & !zero)
+        })
+    }
+
+    /// Element-wise `|`
+    pub fn or(&self, rhs: &Self) -> Self {
+        self.zip_with(rhs, |av, az, ax, bv, bz, bx| {
+            let one = av | bv;
+            (one, (az | ax | bz | bx) & !one)
+        })
+    }
+
+    /// Element-wise `^`
+    pub fn xor(&self, rhs: &Self) -> Self {
+        self.zip_with(rhs, |av, az, ax, bv, bz, bx| {
+            let unknown = az | ax | bz | bx;
+            ((av ^ bv) & !unknown, unknown)
+        })
+    }
+
+    /// Element-wise `~`
+    pub fn not(&self) -> Self {
+        let x: Vec<T> = self.z.iter().zip(&self.x).map(|(z, x)| *z | *x).collect();
+        let v = self.v.iter().zip(&x).map(|(v, x)| !*v & !*x).collect();
+        Sv4StatePlanes {
+            v,
+            z: vec![T::zero(); self.len()],
+            x,
+        }
+    }
+
+    /// Element-wise case equality (`===`)
+    pub fn case_eq(&self, rhs: &Self) -> Vec<bool> {
+        let len = self.len().min(rhs.len());
+        (0..len)
+            .map(|i| self.v[i] == rhs.v[i] && self.z[i] == rhs.z[i] && self.x[i] == rhs.x[i])
+            .collect()
+    }
+
+    /// Element-wise logical equality (`==`)
+    ///
+    /// The result is 0 if some bits known in both differ, x if otherwise some
+    /// bits are x/z, and 1 if all bits are the same known values.
+    pub fn logic_eq(&self, rhs: &Self) -> Vec<Bit4> {
+        let len = self.len().min(rhs.len());
+        (0..len)
+            .map(|i| {
+                let unknown = self.z[i] | self.x[i] | rhs.z[i] | rhs.x[i];
+                if (self.v[i] ^ rhs.v[i]) & !unknown != T::zero() {
+                    SvLogic::Zero
+                } else if unknown != T::zero() {
+                    SvLogic::X
+                } else {
+                    SvLogic::One
+                }
+            })
+            .collect()
+    }
+
+    /// Number of values with x/z bits
+    pub fn count_unknown(&self) -> usize {
+        self.z
+            .iter()
+            .zip(&self.x)
+            .filter(|(z, x)| (**z | **x) != T::zero())
+            .count()
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> core::iter::FromIterator<Sv4State<T>> for Sv4StatePlanes<T> {
+    fn from_iter<I: IntoIterator<Item = Sv4State<T>>>(iter: I) -> Self {
+        let mut ret = Self::new();
+        for value in iter {
+            ret.push(value);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bulk_ops() {
+        let a: Vec<Sv4State<u8>> = ["8'b1100_xz10", "8'hf0", "8'bz"]
+            .iter()
+            .map(|x| x.parse().unwrap())
+            .collect();
+        let b: Vec<Sv4State<u8>> = ["8'b1010_1100", "8'bx", "8'bz"]
+            .iter()
+            .map(|x| x.parse().unwrap())
+            .collect();
+        let (pa, pb) = (
+            Sv4StatePlanes::from_states(&a),
+            Sv4StatePlanes::from_states(&b),
+        );
+
+        let zip = |f: fn(Sv4State<u8>, Sv4State<u8>) -> Sv4State<u8>| -> Vec<_> {
+            a.iter().zip(&b).map(|(a, b)| f(*a, *b)).collect()
+        };
+        assert_eq!(pa.and(&pb).to_states(), zip(|a, b| a & b));
+        assert_eq!(pa.or(&pb).to_states(), zip(|a, b| a | b));
+        assert_eq!(pa.xor(&pb).to_states(), zip(|a, b| a ^ b));
+        assert_eq!(
+            pa.not().to_states(),
+            a.iter().map(|a| !*a).collect::<Vec<_>>()
+        );
+
+        assert_eq!(pa.case_eq(&pb), [false, false, true]);
+        assert_eq!(pa.logic_eq(&pb), [SvLogic::Zero, SvLogic::X, SvLogic::X]);
+        assert_eq!(pa.count_unknown(), 2);
+        assert_eq!(pa.get(1), Some(Sv4State::known(0xf0)));
+        assert_eq!(pa.iter().collect::<Sv4StatePlanes<_>>(), pa);
+        assert_eq!(
+            Sv4StatePlanes::from_planes(vec![0xffu8, 1], vec![0x0f], vec![0x03]).get(0),
+            Some(Sv4State::new(0xf0, 0x0c, 0x03))
+        );
+    }
+}
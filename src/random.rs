@@ -0,0 +1,133 @@
+//! Random 4-state values for stimulus (`rand` feature)
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::Debug;
+
+/// Distribution of values whose bits are independently 0, 1, x or z
+///
+/// ```
+/// use rand::Rng;
+/// use sv4state::random::Sv4Distribution;
+/// use sv4state::Sv4State;
+///
+/// let dist = Sv4Distribution::with_unknown(0.1, 0.05).unwrap().width(12);
+/// let value: Sv4State<u16> = rand::thread_rng().sample(dist);
+/// assert_eq!(value.v >> 12, 0);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sv4Distribution {
+    /// cumulative probabilities of 0, 1 and x
+    thresholds: [f64; 3],
+    width: Option<u32>,
+}
+
+impl Sv4Distribution {
+    /// Bits with probabilities proportional to the weights
+    ///
+    /// Returns `None` if a weight is negative or not finite, or if all
+    /// weights are 0.
+    pub fn new(zero: f64, one: f64, x: f64, z: f64) -> Option<Self> {
+        let weights = [zero, one, x, z];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return None;
+        }
+        let sum: f64 = weights.iter().sum();
+        if sum <= 0.0 {
+            return None;
+        }
+        Some(Sv4Distribution {
+            thresholds: [zero / sum, (zero + one) / sum, (zero + one + x) / sum],
+            width: None,
+        })
+    }
+
+    /// Bits which are x with probability `x`, z with probability `z`, and
+    /// otherwise 0 or 1 evenly
+    pub fn with_unknown(x: f64, z: f64) -> Option<Self> {
+        let known = (1.0 - x - z) / 2.0;
+        Self::new(known, known, x, z)
+    }
+
+    /// Bits which are 0 or 1 evenly
+    pub fn known() -> Self {
+        Sv4Distribution {
+            thresholds: [0.5, 1.0, 1.0],
+            width: None,
+        }
+    }
+
+    /// Only the lower `width` bits are random, and the others are 0
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Iterator of values from an RNG seeded by `seed`
+    ///
+    /// The values are the same for the same `seed` and version of `rand`.
+    pub fn seeded<T: Copy + Debug + PrimInt>(self, seed: u64) -> impl Iterator<Item = Sv4State<T>> {
+        StdRng::seed_from_u64(seed).sample_iter(self)
+    }
+}
+
+impl Default for Sv4Distribution {
+    fn default() -> Self {
+        Self::known()
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Distribution<Sv4State<T>> for Sv4Distribution {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Sv4State<T> {
+        let payload_width = T::zero().count_zeros();
+        let width = self.width.map_or(payload_width, |w| w.min(payload_width));
+        let (mut v, mut z, mut x) = (T::zero(), T::zero(), T::zero());
+        for i in 0..width as usize {
+            let p: f64 = rng.gen();
+            let bit = T::one() << i;
+            if p < self.thresholds[0] {
+                continue;
+            }
+            if p < self.thresholds[1] {
+                v = v | bit;
+            } else if p < self.thresholds[2] {
+                x = x | bit;
+            } else {
+                z = z | bit;
+            }
+        }
+        Sv4State { v, z, x }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distribution() {
+        let dist = Sv4Distribution::with_unknown(0.25, 0.25).unwrap();
+        let values: Vec<Sv4State<u64>> = dist.seeded(1).take(64).collect();
+        assert_eq!(values, dist.seeded(1).take(64).collect::<Vec<_>>());
+        let count = |f: fn(&Sv4State<u64>) -> u64| values.iter().map(f).sum::<u64>();
+        let (ones, x, z) = (
+            count(|s| s.v.count_ones().into()),
+            count(|s| s.x.count_ones().into()),
+            count(|s| s.z.count_ones().into()),
+        );
+        // 4096 bits of which 1024 are expected for each state
+        for n in [ones, x, z] {
+            assert!((900..1150).contains(&n), "{}", n);
+        }
+
+        let narrow: Sv4State<u8> = StdRng::seed_from_u64(2).sample(dist.width(3));
+        assert_eq!((narrow.v | narrow.z | narrow.x) >> 3, 0);
+        let known: Sv4State<u32> = StdRng::seed_from_u64(3).sample(Sv4Distribution::known());
+        assert!(known.is_known());
+        assert_eq!(Sv4Distribution::new(0.0, 0.0, 0.0, 0.0), None);
+        assert_eq!(Sv4Distribution::with_unknown(0.75, 0.5), None);
+    }
+}
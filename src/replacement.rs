@@ -0,0 +1,222 @@
+//! Reference models of cache replacement policies
+//!
+//! Way selects are 4-state values. A select with x/z bits may touch any way
+//! matching its known bits, and the state bits are updated as the merge of
+//! all those candidates: bits updated to the same value by all candidates
+//! get that value, and the others become x. A victim depending on unknown
+//! state bits is unknown.
+
+use crate::{Sv4State, SvLogic};
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Ways matching the known bits of `way`
+fn candidates<T: Copy + Debug + PrimInt>(way: Sv4State<T>, ways: usize) -> Vec<usize> {
+    let payload_width = T::zero().count_zeros() as usize;
+    (0..ways)
+        .filter(|w| {
+            (0..payload_width).all(|i| {
+                let bit = (i < usize::BITS as usize && (w >> i) & 1 == 1).into();
+                let sel = way.bit(i);
+                !sel.is_known() || sel == bit
+            })
+        })
+        .collect()
+}
+
+/// Merge of the states updated by each candidate
+fn merge(bits: &mut [SvLogic], updates: Vec<Vec<SvLogic>>) {
+    if let Some((first, rest)) = updates.split_first() {
+        for (i, bit) in bits.iter_mut().enumerate() {
+            *bit = if rest.iter().all(|x| x[i] == first[i]) {
+                first[i]
+            } else {
+                SvLogic::X
+            };
+        }
+    }
+}
+
+/// True-LRU model by an age matrix
+///
+/// The state has a bit for each pair of ways `i < j`, which is 1 if way `i`
+/// is older than way `j`. The reset state orders way 0 as the oldest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrueLru {
+    ways: usize,
+    older: Vec<SvLogic>,
+}
+
+impl TrueLru {
+    pub fn new(ways: usize) -> Self {
+        TrueLru {
+            ways,
+            older: vec![SvLogic::One; ways * ways],
+        }
+    }
+
+    pub fn ways(&self) -> usize {
+        self.ways
+    }
+
+    /// Whether way `i` is older than way `j`
+    pub fn is_older(&self, i: usize, j: usize) -> SvLogic {
+        match i.cmp(&j) {
+            std::cmp::Ordering::Less => self.older[i * self.ways + j],
+            std::cmp::Ordering::Equal => SvLogic::Zero,
+            std::cmp::Ordering::Greater => match self.older[j * self.ways + i] {
+                SvLogic::Zero => SvLogic::One,
+                SvLogic::One => SvLogic::Zero,
+                _ => SvLogic::X,
+            },
+        }
+    }
+
+    /// Make `way` the most recently used
+    ///
+    /// A select matching no way doesn't change the state.
+    pub fn touch<T: Copy + Debug + PrimInt>(&mut self, way: Sv4State<T>) {
+        let updates = candidates(way, self.ways)
+            .into_iter()
+            .map(|w| {
+                let mut older = self.older.clone();
+                for j in 0..self.ways {
+                    if j < w {
+                        older[j * self.ways + w] = SvLogic::One;
+                    } else if j > w {
+                        older[w * self.ways + j] = SvLogic::Zero;
+                    }
+                }
+                older
+            })
+            .collect();
+        merge(&mut self.older, updates);
+    }
+
+    /// Least recently used way, or `None` if it is unknown
+    pub fn victim(&self) -> Option<usize> {
+        (0..self.ways)
+            .find(|&i| (0..self.ways).all(|j| i == j || self.is_older(i, j) == SvLogic::One))
+    }
+
+    /// Whether all state bits are 0 or 1
+    pub fn is_known(&self) -> bool {
+        (0..self.ways).all(|i| (i + 1..self.ways).all(|j| self.is_older(i, j).is_known()))
+    }
+}
+
+/// Tree pseudo-LRU model
+///
+/// The state has a bit for each node of a binary tree in heap order, where 0
+/// points the victim to the lower half. The number of ways is rounded up to a
+/// power of two, and the reset state points to way 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreePlru {
+    levels: u32,
+    nodes: Vec<SvLogic>,
+}
+
+impl TreePlru {
+    pub fn new(ways: usize) -> Self {
+        let levels = ways.max(1).next_power_of_two().trailing_zeros();
+        TreePlru {
+            levels,
+            nodes: vec![SvLogic::Zero; (1 << levels) - 1],
+        }
+    }
+
+    pub fn ways(&self) -> usize {
+        1 << self.levels
+    }
+
+    /// Node bits in heap order
+    pub fn nodes(&self) -> &[SvLogic] {
+        &self.nodes
+    }
+
+    /// Point the nodes on the path to `way` away from it
+    ///
+    /// A select matching no way doesn't change the state.
+    pub fn touch<T: Copy + Debug + PrimInt>(&mut self, way: Sv4State<T>) {
+        let updates = candidates(way, self.ways())
+            .into_iter()
+            .map(|w| {
+                let mut nodes = self.nodes.clone();
+                let mut node = 0;
+                for level in (0..self.levels).rev() {
+                    let dir = (w >> level) & 1;
+                    nodes[node] = (dir == 0).into();
+                    node = 2 * node + 1 + dir;
+                }
+                nodes
+            })
+            .collect();
+        merge(&mut self.nodes, updates);
+    }
+
+    /// Way pointed by the nodes, or `None` if it is unknown
+    pub fn victim(&self) -> Option<usize> {
+        let mut node = 0;
+        let mut way = 0;
+        for _ in 0..self.levels {
+            let dir = match self.nodes[node] {
+                SvLogic::Zero => 0,
+                SvLogic::One => 1,
+                _ => return None,
+            };
+            way = (way << 1) | dir;
+            node = 2 * node + 1 + dir;
+        }
+        Some(way)
+    }
+
+    /// Whether all node bits are 0 or 1
+    pub fn is_known(&self) -> bool {
+        self.nodes.iter().all(|x| x.is_known())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn true_lru() {
+        let mut lru = TrueLru::new(4);
+        assert_eq!(lru.victim(), Some(0));
+
+        lru.touch(Sv4State::<u8>::known(0));
+        lru.touch(Sv4State::<u8>::known(2));
+        assert_eq!(lru.victim(), Some(1));
+        assert_eq!(lru.is_older(3, 2), SvLogic::One);
+
+        lru.touch(Sv4State::<u8>::new(1, 0, 2));
+        assert!(!lru.is_known());
+        assert_eq!(lru.victim(), None);
+        assert_eq!(lru.is_older(1, 0), SvLogic::X);
+        assert_eq!(lru.is_older(0, 2), SvLogic::One);
+
+        lru.touch(Sv4State::<u8>::known(1));
+        lru.touch(Sv4State::<u8>::known(3));
+        assert_eq!(lru.victim(), Some(0));
+    }
+
+    #[test]
+    fn tree_plru() {
+        let mut plru = TreePlru::new(3);
+        assert_eq!(plru.ways(), 4);
+        assert_eq!(plru.victim(), Some(0));
+
+        plru.touch(Sv4State::<u8>::known(0));
+        assert_eq!(plru.victim(), Some(2));
+        plru.touch(Sv4State::<u8>::known(2));
+        assert_eq!(plru.victim(), Some(1));
+
+        plru.touch(Sv4State::<u8>::new(0, 0, 2));
+        assert_eq!(plru.nodes(), [SvLogic::X, SvLogic::One, SvLogic::One]);
+        assert_eq!(plru.victim(), None);
+        plru.touch(Sv4State::<u8>::known(3));
+        assert!(plru.is_known());
+        assert_eq!(plru.victim(), Some(1));
+    }
+}
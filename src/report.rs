@@ -0,0 +1,379 @@
+//! End-of-test report
+//!
+//! Results of checkers are registered into a [`TestReport`] through
+//! [`Reportable`], and summarized as text or JSON at the end of test.
+
+use crate::correlate::Correlation;
+use crate::scoreboard::Scoreboard;
+use crate::temporal::Violation;
+use crate::toggle::{Sv4ToggleCoverage, ToggleReport};
+use crate::xstats::{XReport, XStats};
+use num_traits::PrimInt;
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+/// Result of one check
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportItem {
+    /// Kind of the checker like `scoreboard`
+    pub category: String,
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Checkers which can be registered into [`TestReport`]
+pub trait Reportable {
+    /// Category of [`ReportItem`]
+    fn category(&self) -> &'static str;
+
+    /// Whether the check passed, and the detail
+    fn result(&self) -> (bool, String);
+}
+
+impl<T: Copy + Debug + PrimInt + Hash, D: PartialEq + Debug> Reportable for Scoreboard<T, D> {
+    fn category(&self) -> &'static str {
+        "scoreboard"
+    }
+
+    /// Passed if no error occurred and no expected entry is pending
+    fn result(&self) -> (bool, String) {
+        let detail = format!(
+            "{} matched, {} errors, {} pending",
+            self.matched(),
+            self.errors(),
+            self.pending()
+        );
+        (self.errors() == 0 && self.pending() == 0, detail)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Reportable for [Violation<T>] {
+    fn category(&self) -> &'static str {
+        "temporal"
+    }
+
+    /// Passed if there is no violation
+    fn result(&self) -> (bool, String) {
+        let detail = match self.first() {
+            None => String::from("0 violations"),
+            Some(v) => format!("{} violations, first: {}", self.len(), v),
+        };
+        (self.is_empty(), detail)
+    }
+}
+
+impl<T: Copy + Debug> Reportable for Correlation<T> {
+    fn category(&self) -> &'static str {
+        "correlation"
+    }
+
+    /// Passed if all flows are complete and no ID is unknown
+    fn result(&self) -> (bool, String) {
+        let incomplete = self.incomplete().count();
+        let detail = format!(
+            "{} flows, {} incomplete, {} unknown IDs",
+            self.flows.len(),
+            incomplete,
+            self.unknown.len()
+        );
+        (incomplete == 0 && self.unknown.is_empty(), detail)
+    }
+}
+
+impl Reportable for XReport {
+    fn category(&self) -> &'static str {
+        "x-report"
+    }
+
+    /// Passed if no signal is ever x/z
+    fn result(&self) -> (bool, String) {
+        let unknown: Vec<&str> = self
+            .iter()
+            .filter(|(_, x)| x.unknown() > 0)
+            .map(|(name, _)| name)
+            .collect();
+        let detail = format!(
+            "{} of {} signals unknown{}{}",
+            unknown.len(),
+            self.len(),
+            if unknown.is_empty() { "" } else { ": " },
+            unknown.join(", ")
+        );
+        (unknown.is_empty(), detail)
+    }
+}
+
+impl Reportable for XStats {
+    fn category(&self) -> &'static str {
+        "x-report"
+    }
+
+    /// Passed if the signal is never x/z
+    fn result(&self) -> (bool, String) {
+        let detail = format!(
+            "unknown {} of {} ({:.1}%), x {}, z {}",
+            self.unknown(),
+            self.total,
+            self.unknown_ratio() * 100.0,
+            self.x,
+            self.z
+        );
+        (self.unknown() == 0, detail)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Reportable for Sv4ToggleCoverage<T> {
+    fn category(&self) -> &'static str {
+        "toggle"
+    }
+
+    /// Passed if all bits rose and fell
+    fn result(&self) -> (bool, String) {
+        let uncovered: Vec<String> = self
+            .counts()
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_covered())
+            .map(|(i, c)| match (c.rise, c.fall) {
+                (0, 0) => format!("[{}] rise fall", i),
+                (0, _) => format!("[{}] rise", i),
+                _ => format!("[{}] fall", i),
+            })
+            .collect();
+        let detail = format!(
+            "{} of {} bits toggled ({:.1}%){}{}",
+            self.covered(),
+            self.width(),
+            self.ratio() * 100.0,
+            if uncovered.is_empty() {
+                ""
+            } else {
+                ", missing: "
+            },
+            uncovered.join(", ")
+        );
+        (uncovered.is_empty(), detail)
+    }
+}
+
+impl Reportable for ToggleReport {
+    fn category(&self) -> &'static str {
+        "toggle"
+    }
+
+    /// Passed if all bits of all signals rose and fell
+    fn result(&self) -> (bool, String) {
+        let (mut covered, mut bits) = (0, 0);
+        let mut uncovered = Vec::new();
+        for (name, counts) in self.iter() {
+            let n = counts.iter().filter(|c| c.is_covered()).count();
+            covered += n;
+            bits += counts.len();
+            if n < counts.len() {
+                uncovered.push(name);
+            }
+        }
+        let detail = format!(
+            "{} of {} bits toggled{}{}",
+            covered,
+            bits,
+            if uncovered.is_empty() {
+                ""
+            } else {
+                ", missing in: "
+            },
+            uncovered.join(", ")
+        );
+        (uncovered.is_empty(), detail)
+    }
+}
+
+/// Collector of check results
+#[derive(Clone, Debug, Default)]
+pub struct TestReport {
+    pub name: String,
+    items: Vec<ReportItem>,
+}
+
+impl TestReport {
+    pub fn new(name: &str) -> Self {
+        TestReport {
+            name: String::from(name),
+            items: Vec::new(),
+        }
+    }
+
+    /// Register a result of `category`
+    pub fn add(&mut self, category: &str, name: &str, passed: bool, detail: &str) {
+        self.items.push(ReportItem {
+            category: String::from(category),
+            name: String::from(name),
+            passed,
+            detail: String::from(detail),
+        });
+    }
+
+    /// Register the result of `checker`
+    pub fn record<R: Reportable + ?Sized>(&mut self, name: &str, checker: &R) {
+        let (passed, detail) = checker.result();
+        self.add(checker.category(), name, passed, &detail);
+    }
+
+    /// Append the results of `other`, e.g. of another simulation shard
+    pub fn merge(&mut self, other: &TestReport) {
+        self.items.extend_from_slice(&other.items);
+    }
+
+    pub fn items(&self) -> &[ReportItem] {
+        &self.items
+    }
+
+    /// Whether all registered checks passed
+    pub fn passed(&self) -> bool {
+        self.items.iter().all(|x| x.passed)
+    }
+
+    /// Number of failed checks
+    pub fn failures(&self) -> usize {
+        self.items.iter().filter(|x| !x.passed).count()
+    }
+
+    /// Text summary with one line per check
+    pub fn to_text(&self) -> String {
+        let mut ret = format!(
+            "{}: {} ({} checks, {} failed)\n",
+            self.name,
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.items.len(),
+            self.failures()
+        );
+        for x in &self.items {
+            let _ = writeln!(
+                ret,
+                "  [{}] {} {}: {}",
+                if x.passed { "PASS" } else { "FAIL" },
+                x.category,
+                x.name,
+                x.detail
+            );
+        }
+        ret
+    }
+
+    /// JSON summary
+    ///
+    /// The object has `name`, `passed`, `failures` and `items`, and each item
+    /// has `category`, `name`, `passed` and `detail`.
+    pub fn to_json(&self) -> String {
+        let items: Vec<String> = self
+            .items
+            .iter()
+            .map(|x| {
+                format!(
+                    r#"{{"category":{},"name":{},"passed":{},"detail":{}}}"#,
+                    json_string(&x.category),
+                    json_string(&x.name),
+                    x.passed,
+                    json_string(&x.detail)
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"name":{},"passed":{},"failures":{},"items":[{}]}}"#,
+            json_string(&self.name),
+            self.passed(),
+            self.failures(),
+            items.join(",")
+        )
+    }
+}
+
+/// JSON string literal of `s`
+fn json_string(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(ret, "\\u{:04x}", c as u32);
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoreboard::{ScoreboardOrder, UnknownTagPolicy};
+    use crate::Sv4State;
+
+    #[test]
+    fn report() {
+        let mut sb = Scoreboard::<u8, u32>::new(ScoreboardOrder::InOrder, UnknownTagPolicy::Reject);
+        sb.expect(Sv4State::known(1), 10).unwrap();
+        sb.check(Sv4State::known(1), 10).unwrap();
+        let stats = XStats {
+            total: 100,
+            known: 90,
+            x: 10,
+            z: 0,
+        };
+        let violations: Vec<Violation<u8>> = Vec::new();
+
+        let mut report = TestReport::new("smoke");
+        report.record("sb", &sb);
+        report.record("timing", &violations[..]);
+        assert!(report.passed());
+        report.record("data", &stats);
+        report.add("watchdog", "timeout", true, "quote \" tab\t");
+
+        assert!(!report.passed());
+        assert_eq!(report.failures(), 1);
+        assert_eq!(
+            report.to_text(),
+            "smoke: FAIL (4 checks, 1 failed)\n\
+             \x20 [PASS] scoreboard sb: 1 matched, 0 errors, 0 pending\n\
+             \x20 [PASS] temporal timing: 0 violations\n\
+             \x20 [FAIL] x-report data: unknown 10 of 100 (10.0%), x 10, z 0\n\
+             \x20 [PASS] watchdog timeout: quote \" tab\t\n"
+        );
+        let json = report.to_json();
+        assert!(json.starts_with(r#"{"name":"smoke","passed":false,"failures":1,"items":[{"#));
+        assert!(json.ends_with(
+            r#"{"category":"watchdog","name":"timeout","passed":true,"detail":"quote \" tab\t"}]}"#
+        ));
+
+        let mut xreport = XReport::new();
+        xreport.add("data", &stats);
+        xreport.add("valid", &XStats::default());
+        let mut shard = TestReport::new("shard1");
+        shard.record("signals", &xreport);
+        report.merge(&shard);
+        assert_eq!(report.failures(), 2);
+        assert_eq!(report.items()[4].detail, "1 of 2 signals unknown: data");
+
+        let mut toggle = Sv4ToggleCoverage::<u8>::new(3);
+        for v in [0b000, 0b011, 0b001] {
+            toggle.sample(Sv4State::known(v));
+        }
+        report.record("addr", &toggle);
+        assert_eq!(
+            report.items()[5].detail,
+            "1 of 3 bits toggled (33.3%), missing: [0] fall, [2] rise fall"
+        );
+        let mut toggles = ToggleReport::new();
+        toggles.add("addr", toggle.counts());
+        assert_eq!(
+            toggles.result(),
+            (false, String::from("1 of 3 bits toggled, missing in: addr"))
+        );
+    }
+}
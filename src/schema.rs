@@ -0,0 +1,424 @@
+//! Versioned JSON documents of analysis results (`serde` feature)
+//!
+//! Each document has `schema` naming its kind and `version`, which is
+//! incremented on incompatible changes, so dashboards can consume results of
+//! any serde format without scraping text.
+//!
+//! ```
+//! use sv4state::schema::XReportDoc;
+//! use sv4state::xstats::{XReport, XStats};
+//!
+//! let mut report = XReport::new();
+//! report.add("data", &XStats { total: 10, known: 8, x: 2, z: 0 });
+//! let doc = XReportDoc::from(&report);
+//! assert!(doc.check().is_ok());
+//! assert_eq!(XReport::from(&doc), report);
+//! ```
+
+use crate::diff::Sv4Diff;
+use crate::report::{ReportItem, TestReport};
+use crate::scoreboard::Scoreboard;
+use crate::toggle::{ToggleCount, ToggleReport};
+use crate::xstats::{XReport, XStats};
+use num_traits::PrimInt;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Version of all schemas in this crate
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Error of [`XReportDoc::check`] and likewise
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaError {
+    /// The document is of another kind
+    Schema { expected: String, found: String },
+    /// The document is of a newer version
+    Version { supported: u32, found: u32 },
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Schema { expected, found } => {
+                write!(f, "expected schema {}, found {}", expected, found)
+            }
+            SchemaError::Version { supported, found } => write!(
+                f,
+                "schema version {} is newer than supported {}",
+                found, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+fn check(schema: &str, version: u32, expected: &str) -> Result<(), SchemaError> {
+    if schema != expected {
+        Err(SchemaError::Schema {
+            expected: String::from(expected),
+            found: String::from(schema),
+        })
+    } else if version > SCHEMA_VERSION {
+        Err(SchemaError::Version {
+            supported: SCHEMA_VERSION,
+            found: version,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Statistics of a signal in [`XReportDoc`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XSignalDoc {
+    pub name: String,
+    pub total: u64,
+    pub known: u64,
+    pub x: u64,
+    pub z: u64,
+}
+
+/// Document of an [`XReport`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XReportDoc {
+    /// `sv4state.x-report`
+    pub schema: String,
+    pub version: u32,
+    /// Signals sorted by name
+    pub signals: Vec<XSignalDoc>,
+}
+
+impl XReportDoc {
+    pub const SCHEMA: &'static str = "sv4state.x-report";
+
+    /// Check the schema and the version
+    pub fn check(&self) -> Result<(), SchemaError> {
+        check(&self.schema, self.version, Self::SCHEMA)
+    }
+}
+
+impl From<&XReport> for XReportDoc {
+    fn from(report: &XReport) -> Self {
+        XReportDoc {
+            schema: String::from(Self::SCHEMA),
+            version: SCHEMA_VERSION,
+            signals: report
+                .iter()
+                .map(|(name, x)| XSignalDoc {
+                    name: String::from(name),
+                    total: x.total,
+                    known: x.known,
+                    x: x.x,
+                    z: x.z,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&XReportDoc> for XReport {
+    fn from(doc: &XReportDoc) -> Self {
+        let mut ret = XReport::new();
+        for s in &doc.signals {
+            let stats = XStats {
+                total: s.total,
+                known: s.known,
+                x: s.x,
+                z: s.z,
+            };
+            ret.add(&s.name, &stats);
+        }
+        ret
+    }
+}
+
+/// Transitions of a bit in [`CoverageDoc`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToggleBitDoc {
+    pub rise: u64,
+    pub fall: u64,
+    pub to_unknown: u64,
+    pub from_unknown: u64,
+}
+
+/// Toggle counts of a signal in [`CoverageDoc`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageSignalDoc {
+    pub name: String,
+    /// Counts from bit 0
+    pub bits: Vec<ToggleBitDoc>,
+}
+
+/// Document of a [`ToggleReport`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageDoc {
+    /// `sv4state.toggle-coverage`
+    pub schema: String,
+    pub version: u32,
+    /// Signals sorted by name
+    pub signals: Vec<CoverageSignalDoc>,
+}
+
+impl CoverageDoc {
+    pub const SCHEMA: &'static str = "sv4state.toggle-coverage";
+
+    /// Check the schema and the version
+    pub fn check(&self) -> Result<(), SchemaError> {
+        check(&self.schema, self.version, Self::SCHEMA)
+    }
+}
+
+impl From<&ToggleReport> for CoverageDoc {
+    fn from(report: &ToggleReport) -> Self {
+        CoverageDoc {
+            schema: String::from(Self::SCHEMA),
+            version: SCHEMA_VERSION,
+            signals: report
+                .iter()
+                .map(|(name, counts)| CoverageSignalDoc {
+                    name: String::from(name),
+                    bits: counts
+                        .iter()
+                        .map(|c| ToggleBitDoc {
+                            rise: c.rise,
+                            fall: c.fall,
+                            to_unknown: c.to_unknown,
+                            from_unknown: c.from_unknown,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&CoverageDoc> for ToggleReport {
+    fn from(doc: &CoverageDoc) -> Self {
+        let mut ret = ToggleReport::new();
+        for s in &doc.signals {
+            let counts: Vec<ToggleCount> = s
+                .bits
+                .iter()
+                .map(|b| ToggleCount {
+                    rise: b.rise,
+                    fall: b.fall,
+                    to_unknown: b.to_unknown,
+                    from_unknown: b.from_unknown,
+                })
+                .collect();
+            ret.add(&s.name, &counts);
+        }
+        ret
+    }
+}
+
+/// Differing bit in [`DiffDoc`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MismatchDoc {
+    pub bit: usize,
+    /// `0`, `1`, `x` or `z`
+    pub left: char,
+    pub right: char,
+    /// Name of [`MismatchKind`](crate::diff::MismatchKind) like `x vs known`
+    pub kind: String,
+}
+
+/// Document of an [`Sv4Diff`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffDoc {
+    /// `sv4state.diff`
+    pub schema: String,
+    pub version: u32,
+    /// Differing bits from the least significant one
+    pub mismatches: Vec<MismatchDoc>,
+}
+
+impl DiffDoc {
+    pub const SCHEMA: &'static str = "sv4state.diff";
+
+    /// Check the schema and the version
+    pub fn check(&self) -> Result<(), SchemaError> {
+        check(&self.schema, self.version, Self::SCHEMA)
+    }
+}
+
+impl From<&Sv4Diff> for DiffDoc {
+    fn from(diff: &Sv4Diff) -> Self {
+        DiffDoc {
+            schema: String::from(Self::SCHEMA),
+            version: SCHEMA_VERSION,
+            mismatches: diff
+                .mismatches()
+                .iter()
+                .map(|x| MismatchDoc {
+                    bit: x.bit,
+                    left: x.left.to_char(),
+                    right: x.right.to_char(),
+                    kind: String::from(x.kind().name()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Document of the counts of a [`Scoreboard`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreboardDoc {
+    /// `sv4state.scoreboard`
+    pub schema: String,
+    pub version: u32,
+    pub matched: usize,
+    pub errors: usize,
+    pub pending: usize,
+}
+
+impl ScoreboardDoc {
+    pub const SCHEMA: &'static str = "sv4state.scoreboard";
+
+    /// Check the schema and the version
+    pub fn check(&self) -> Result<(), SchemaError> {
+        check(&self.schema, self.version, Self::SCHEMA)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + Hash, D: PartialEq + Debug> From<&Scoreboard<T, D>>
+    for ScoreboardDoc
+{
+    fn from(sb: &Scoreboard<T, D>) -> Self {
+        ScoreboardDoc {
+            schema: String::from(Self::SCHEMA),
+            version: SCHEMA_VERSION,
+            matched: sb.matched(),
+            errors: sb.errors(),
+            pending: sb.pending(),
+        }
+    }
+}
+
+/// Check result in [`TestReportDoc`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportItemDoc {
+    pub category: String,
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Document of a [`TestReport`]
+///
+/// The fields other than `schema` and `version` are the same as
+/// [`TestReport::to_json`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestReportDoc {
+    /// `sv4state.test-report`
+    pub schema: String,
+    pub version: u32,
+    pub name: String,
+    pub passed: bool,
+    pub failures: usize,
+    pub items: Vec<ReportItemDoc>,
+}
+
+impl TestReportDoc {
+    pub const SCHEMA: &'static str = "sv4state.test-report";
+
+    /// Check the schema and the version
+    pub fn check(&self) -> Result<(), SchemaError> {
+        check(&self.schema, self.version, Self::SCHEMA)
+    }
+}
+
+impl From<&TestReport> for TestReportDoc {
+    fn from(report: &TestReport) -> Self {
+        TestReportDoc {
+            schema: String::from(Self::SCHEMA),
+            version: SCHEMA_VERSION,
+            name: report.name.clone(),
+            passed: report.passed(),
+            failures: report.failures(),
+            items: report
+                .items()
+                .iter()
+                .map(|x: &ReportItem| ReportItemDoc {
+                    category: x.category.clone(),
+                    name: x.name.clone(),
+                    passed: x.passed,
+                    detail: x.detail.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&TestReportDoc> for TestReport {
+    fn from(doc: &TestReportDoc) -> Self {
+        let mut ret = TestReport::new(&doc.name);
+        for x in &doc.items {
+            ret.add(&x.category, &x.name, x.passed, &x.detail);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoreboard::{ScoreboardOrder, UnknownTagPolicy};
+    use crate::Sv4State;
+    use serde_test::{assert_tokens, Token};
+
+    #[test]
+    fn scoreboard_doc() {
+        let mut sb = Scoreboard::<u8, u32>::new(ScoreboardOrder::InOrder, UnknownTagPolicy::Reject);
+        sb.expect(Sv4State::known(1), 10).unwrap();
+        let doc = ScoreboardDoc::from(&sb);
+        assert_tokens(
+            &doc,
+            &[
+                Token::Struct {
+                    name: "ScoreboardDoc",
+                    len: 5,
+                },
+                Token::Str("schema"),
+                Token::Str("sv4state.scoreboard"),
+                Token::Str("version"),
+                Token::U32(1),
+                Token::Str("matched"),
+                Token::U64(0),
+                Token::Str("errors"),
+                Token::U64(0),
+                Token::Str("pending"),
+                Token::U64(1),
+                Token::StructEnd,
+            ],
+        );
+
+        let mut doc = TestReportDoc::from(&TestReport::new("smoke"));
+        assert!(doc.check().is_ok());
+        doc.version = SCHEMA_VERSION + 1;
+        assert_eq!(
+            doc.check(),
+            Err(SchemaError::Version {
+                supported: 1,
+                found: 2
+            })
+        );
+        doc.schema = String::from(XReportDoc::SCHEMA);
+        assert!(matches!(doc.check(), Err(SchemaError::Schema { .. })));
+
+        let mut toggles = ToggleReport::new();
+        let count = ToggleCount {
+            rise: 2,
+            fall: 1,
+            to_unknown: 0,
+            from_unknown: 1,
+        };
+        toggles.add("addr", &[count, ToggleCount::default()]);
+        let doc = CoverageDoc::from(&toggles);
+        assert!(doc.check().is_ok());
+        assert_eq!(doc.signals[0].bits.len(), 2);
+        assert_eq!(ToggleReport::from(&doc), toggles);
+    }
+}
@@ -0,0 +1,472 @@
+//! Scoreboard keyed by 4-state tags
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Order in which actual transactions are expected to arrive
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScoreboardOrder {
+    /// Actual transactions arrive in the same order as expected ones
+    InOrder,
+    /// Actual transactions may arrive in any order, and are matched by tag
+    OutOfOrder,
+}
+
+/// How tags containing x/z are matched
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnknownTagPolicy {
+    /// Tags containing x/z are reported as errors
+    Reject,
+    /// Tags containing x/z match only tags with the same x/z bits (`===`)
+    Exact,
+    /// x/z bits of either tag match any value
+    Wildcard,
+}
+
+/// Error reported by [`Scoreboard`]
+#[derive(Clone, Debug)]
+pub enum ScoreboardError<T: Copy + Debug, D> {
+    /// The tag contains x/z and the policy is [`UnknownTagPolicy::Reject`]
+    UnknownTag(Sv4State<T>),
+    /// No expected transaction matches the tag
+    Unexpected { tag: Sv4State<T>, data: D },
+    /// The tag doesn't match the oldest expected transaction in in-order mode
+    OutOfOrder {
+        expected: Sv4State<T>,
+        actual: Sv4State<T>,
+    },
+    /// The tag matches but the data differs
+    DataMismatch {
+        tag: Sv4State<T>,
+        expected: D,
+        actual: D,
+    },
+}
+
+impl<T: Copy + Debug + PrimInt, D: Debug> std::fmt::Display for ScoreboardError<T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScoreboardError::UnknownTag(tag) => write!(f, "tag contains x/z: {}", tag),
+            ScoreboardError::Unexpected { tag, data } => {
+                write!(f, "unexpected transaction: tag {}, data {:?}", tag, data)
+            }
+            ScoreboardError::OutOfOrder { expected, actual } => write!(
+                f,
+                "out of order transaction: expected tag {}, actual tag {}",
+                expected, actual
+            ),
+            ScoreboardError::DataMismatch {
+                tag,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "data mismatch: tag {}, expected {:?}, actual {:?}",
+                tag, expected, actual
+            ),
+        }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt, D: Debug> std::error::Error for ScoreboardError<T, D> {}
+
+/// Expected transaction never matched, reported by [`Scoreboard::prune`] and
+/// [`Scoreboard::drain_leaks`]
+#[derive(Clone, Debug)]
+pub struct Leak<T: Copy + Debug, D> {
+    pub tag: Sv4State<T>,
+    pub data: D,
+    /// time when the transaction was expected
+    pub time: u64,
+}
+
+impl<T: Copy + Debug + PrimInt, D> Leak<T, D> {
+    /// Whether the tag contains x/z
+    pub fn has_unknown_tag(&self) -> bool {
+        !self.tag.is_known()
+    }
+}
+
+impl<T: Copy + Debug + PrimInt, D: Debug> std::fmt::Display for Leak<T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unmatched transaction: tag {}, data {:?}, expected at {}",
+            self.tag, self.data, self.time
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry<T: Copy + Debug, D> {
+    seq: u64,
+    time: u64,
+    tag: Sv4State<T>,
+    data: D,
+}
+
+/// Scoreboard matching actual transactions against expected ones by tag
+///
+/// Known tags are matched exactly, and tags containing x/z are matched
+/// according to [`UnknownTagPolicy`].
+#[derive(Clone, Debug)]
+pub struct Scoreboard<T: Copy + Debug, D> {
+    order: ScoreboardOrder,
+    policy: UnknownTagPolicy,
+    seq: u64,
+    time: u64,
+    timeout: Option<u64>,
+    /// expected transactions in arrival order (in-order mode)
+    queue: VecDeque<Entry<T, D>>,
+    /// expected transactions with known tags (out-of-order mode)
+    known: HashMap<T, VecDeque<Entry<T, D>>>,
+    /// expected transactions with unknown tags (out-of-order mode)
+    unknown: Vec<Entry<T, D>>,
+    matched: usize,
+    errors: usize,
+}
+
+impl<T: Copy + Debug + PrimInt + Hash, D: PartialEq + Debug> Scoreboard<T, D> {
+    pub fn new(order: ScoreboardOrder, policy: UnknownTagPolicy) -> Self {
+        Scoreboard {
+            order,
+            policy,
+            seq: 0,
+            time: 0,
+            timeout: None,
+            queue: VecDeque::new(),
+            known: HashMap::new(),
+            unknown: Vec::new(),
+            matched: 0,
+            errors: 0,
+        }
+    }
+
+    /// Report expected transactions older than `timeout` by [`Scoreboard::prune`]
+    pub fn with_timeout(mut self, timeout: u64) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the current time, which is recorded with expected transactions
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    /// Add an expected transaction
+    pub fn expect(&mut self, tag: Sv4State<T>, data: D) -> Result<(), ScoreboardError<T, D>> {
+        if !tag.is_known() && self.policy == UnknownTagPolicy::Reject {
+            self.errors += 1;
+            return Err(ScoreboardError::UnknownTag(tag));
+        }
+
+        let entry = Entry {
+            seq: self.seq,
+            time: self.time,
+            tag,
+            data,
+        };
+        self.seq += 1;
+
+        match self.order {
+            ScoreboardOrder::InOrder => self.queue.push_back(entry),
+            ScoreboardOrder::OutOfOrder => {
+                if tag.is_known() {
+                    self.known.entry(tag.v).or_default().push_back(entry);
+                } else {
+                    self.unknown.push(entry);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check an actual transaction against the expected ones
+    ///
+    /// The matched expected transaction is removed even if the data differs.
+    pub fn check(&mut self, tag: Sv4State<T>, data: D) -> Result<(), ScoreboardError<T, D>> {
+        let ret = self.check_inner(tag, data);
+        if ret.is_ok() {
+            self.matched += 1;
+        } else {
+            self.errors += 1;
+        }
+        ret
+    }
+
+    fn check_inner(&mut self, tag: Sv4State<T>, data: D) -> Result<(), ScoreboardError<T, D>> {
+        if !tag.is_known() && self.policy == UnknownTagPolicy::Reject {
+            return Err(ScoreboardError::UnknownTag(tag));
+        }
+
+        let entry = match self.order {
+            ScoreboardOrder::InOrder => {
+                let front = match self.queue.front() {
+                    Some(x) => x,
+                    None => return Err(ScoreboardError::Unexpected { tag, data }),
+                };
+                if !self.tag_matches(&front.tag, &tag) {
+                    return Err(ScoreboardError::OutOfOrder {
+                        expected: front.tag,
+                        actual: tag,
+                    });
+                }
+                self.queue.pop_front()
+            }
+            ScoreboardOrder::OutOfOrder => self.take_out_of_order(&tag),
+        };
+
+        match entry {
+            Some(entry) if entry.data == data => Ok(()),
+            Some(entry) => Err(ScoreboardError::DataMismatch {
+                tag,
+                expected: entry.data,
+                actual: data,
+            }),
+            None => Err(ScoreboardError::Unexpected { tag, data }),
+        }
+    }
+
+    /// Remove the oldest expected transaction matching `tag`
+    fn take_out_of_order(&mut self, tag: &Sv4State<T>) -> Option<Entry<T, D>> {
+        let exact_known = tag.is_known() && self.policy != UnknownTagPolicy::Wildcard;
+
+        // (seq, key of known map or None for unknown list, index)
+        let mut oldest: Option<(u64, Option<T>, usize)> = None;
+        let mut update = |seq: u64, key: Option<T>, index: usize| {
+            if oldest.map(|(s, _, _)| seq < s).unwrap_or(true) {
+                oldest = Some((seq, key, index));
+            }
+        };
+
+        if tag.is_known() {
+            if let Some(entry) = self.known.get(&tag.v).and_then(|x| x.front()) {
+                update(entry.seq, Some(tag.v), 0);
+            }
+        }
+        if !exact_known {
+            if !tag.is_known() {
+                for (key, entries) in &self.known {
+                    if let Some(entry) = entries.front() {
+                        if tag_matches(self.policy, &entry.tag, tag) {
+                            update(entry.seq, Some(*key), 0);
+                        }
+                    }
+                }
+            }
+            for (i, entry) in self.unknown.iter().enumerate() {
+                if tag_matches(self.policy, &entry.tag, tag) {
+                    update(entry.seq, None, i);
+                }
+            }
+        }
+
+        match oldest? {
+            (_, Some(key), _) => {
+                let entries = self.known.get_mut(&key)?;
+                let entry = entries.pop_front();
+                if entries.is_empty() {
+                    self.known.remove(&key);
+                }
+                entry
+            }
+            (_, None, index) => Some(self.unknown.remove(index)),
+        }
+    }
+
+    fn tag_matches(&self, expected: &Sv4State<T>, actual: &Sv4State<T>) -> bool {
+        tag_matches(self.policy, expected, actual)
+    }
+
+    /// Remove expected transactions waiting longer than the timeout
+    ///
+    /// The removed transactions are counted as errors, and returned in the
+    /// order they were expected. Nothing is removed without a timeout.
+    pub fn prune(&mut self) -> Vec<Leak<T, D>> {
+        let (time, timeout) = match self.timeout {
+            Some(x) => (self.time, x),
+            None => return Vec::new(),
+        };
+        self.remove_where(|e| time.saturating_sub(e.time) > timeout)
+    }
+
+    /// Remove all expected transactions at the end of test
+    ///
+    /// The removed transactions are counted as errors, and returned in the
+    /// order they were expected.
+    pub fn drain_leaks(&mut self) -> Vec<Leak<T, D>> {
+        self.remove_where(|_| true)
+    }
+
+    fn remove_where(&mut self, f: impl Fn(&Entry<T, D>) -> bool) -> Vec<Leak<T, D>> {
+        let mut removed = Vec::new();
+        let mut split = |entries: Vec<Entry<T, D>>| {
+            let (out, keep): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| f(e));
+            removed.extend(out);
+            keep
+        };
+
+        self.queue = split(self.queue.drain(..).collect()).into();
+        self.unknown = split(std::mem::take(&mut self.unknown));
+        for entries in self.known.values_mut() {
+            *entries = split(entries.drain(..).collect()).into();
+        }
+        self.known.retain(|_, x| !x.is_empty());
+
+        removed.sort_by_key(|e| e.seq);
+        self.errors += removed.len();
+        removed
+            .into_iter()
+            .map(|e| Leak {
+                tag: e.tag,
+                data: e.data,
+                time: e.time,
+            })
+            .collect()
+    }
+
+    /// Number of expected transactions not yet matched
+    pub fn pending(&self) -> usize {
+        self.queue.len() + self.known.values().map(|x| x.len()).sum::<usize>() + self.unknown.len()
+    }
+
+    /// Whether all expected transactions are matched
+    pub fn is_empty(&self) -> bool {
+        self.pending() == 0
+    }
+
+    /// Number of successfully matched transactions
+    pub fn matched(&self) -> usize {
+        self.matched
+    }
+
+    /// Number of reported errors
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+}
+
+fn tag_matches<T: Copy + Debug + PrimInt>(
+    policy: UnknownTagPolicy,
+    expected: &Sv4State<T>,
+    actual: &Sv4State<T>,
+) -> bool {
+    if expected.is_known() && actual.is_known() {
+        return expected.v == actual.v;
+    }
+    match policy {
+        UnknownTagPolicy::Reject => false,
+        UnknownTagPolicy::Exact => expected == actual,
+        UnknownTagPolicy::Wildcard => {
+            let unknown = expected.z | expected.x | actual.z | actual.x;
+            (expected.v ^ actual.v) & !unknown == T::zero()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known(v: u8) -> Sv4State<u8> {
+        Sv4State { v, z: 0, x: 0 }
+    }
+
+    #[test]
+    fn in_order() {
+        let mut sb = Scoreboard::new(ScoreboardOrder::InOrder, UnknownTagPolicy::Reject);
+        sb.expect(known(1), 10).unwrap();
+        sb.expect(known(2), 20).unwrap();
+
+        assert!(matches!(
+            sb.check(known(2), 20),
+            Err(ScoreboardError::OutOfOrder { .. })
+        ));
+        assert!(sb.check(known(1), 10).is_ok());
+        assert!(matches!(
+            sb.check(known(2), 21),
+            Err(ScoreboardError::DataMismatch {
+                expected: 20,
+                actual: 21,
+                ..
+            })
+        ));
+        assert!(sb.is_empty());
+        assert_eq!(sb.matched(), 1);
+        assert_eq!(sb.errors(), 2);
+    }
+
+    #[test]
+    fn out_of_order() {
+        let mut sb = Scoreboard::new(ScoreboardOrder::OutOfOrder, UnknownTagPolicy::Reject);
+        sb.expect(known(1), 10).unwrap();
+        sb.expect(known(2), 20).unwrap();
+        sb.expect(known(1), 11).unwrap();
+
+        assert!(sb.check(known(2), 20).is_ok());
+        assert!(sb.check(known(1), 10).is_ok());
+        assert!(sb.check(known(1), 11).is_ok());
+        assert!(matches!(
+            sb.check(known(3), 30),
+            Err(ScoreboardError::Unexpected { data: 30, .. })
+        ));
+        let x_tag = Sv4State { v: 0, z: 0, x: 1 };
+        assert!(matches!(
+            sb.check(x_tag, 30),
+            Err(ScoreboardError::UnknownTag(_))
+        ));
+        assert!(sb.is_empty());
+    }
+
+    #[test]
+    fn unknown_tag_policy() {
+        let x_tag = Sv4State {
+            v: 0x10,
+            z: 0,
+            x: 0x01,
+        };
+
+        let mut sb = Scoreboard::new(ScoreboardOrder::OutOfOrder, UnknownTagPolicy::Exact);
+        sb.expect(known(0x10), 1).unwrap();
+        sb.expect(x_tag, 2).unwrap();
+        assert!(sb.check(x_tag, 2).is_ok());
+        assert_eq!(sb.pending(), 1);
+
+        let mut sb = Scoreboard::new(ScoreboardOrder::OutOfOrder, UnknownTagPolicy::Wildcard);
+        sb.expect(known(0x11), 1).unwrap();
+        sb.expect(known(0x10), 2).unwrap();
+        assert!(sb.check(x_tag, 1).is_ok());
+        assert!(sb.check(known(0x10), 2).is_ok());
+        assert!(sb.is_empty());
+    }
+
+    #[test]
+    fn leaks() {
+        let x_tag = Sv4State { v: 0, z: 0, x: 1 };
+        let mut sb =
+            Scoreboard::new(ScoreboardOrder::OutOfOrder, UnknownTagPolicy::Exact).with_timeout(100);
+        sb.expect(known(1), 10).unwrap();
+        sb.set_time(50);
+        sb.expect(x_tag, 20).unwrap();
+        sb.expect(known(3), 30).unwrap();
+
+        sb.set_time(120);
+        let pruned = sb.prune();
+        assert_eq!(pruned.len(), 1);
+        assert_eq!((pruned[0].data, pruned[0].time), (10, 0));
+        assert!(sb.check(known(3), 30).is_ok());
+
+        let leaks = sb.drain_leaks();
+        assert_eq!(leaks.len(), 1);
+        assert!(leaks[0].has_unknown_tag());
+        assert_eq!(
+            leaks[0].to_string(),
+            "unmatched transaction: tag 0X, data 20, expected at 50"
+        );
+        assert!(sb.is_empty());
+        assert_eq!(sb.errors(), 2);
+    }
+}
@@ -0,0 +1,88 @@
+//! Serde support
+//!
+//! Human-readable formats use a SystemVerilog literal string like
+//! `"8'b10xz1010"`, and compact formats use a `(v, z, x)` tuple. The string
+//! is not affected by formatting profiles.
+
+use crate::Sv4State;
+use num_traits::{FromPrimitive, PrimInt, WrappingShr};
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+use std::fmt::Debug;
+
+impl<T> Serialize for Sv4State<T>
+where
+    T: Copy + Debug + PrimInt + WrappingShr + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let payload_width = T::zero().count_zeros();
+            let mut s = format!("{}'b", payload_width);
+            s.extend(
+                (0..payload_width as usize)
+                    .rev()
+                    .map(|i| self.bit(i).to_char()),
+            );
+            serializer.serialize_str(&s)
+        } else {
+            (self.v, self.z, self.x).serialize(serializer)
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Sv4State<T>
+where
+    T: Copy + Debug + PrimInt + FromPrimitive + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            s.parse().map_err(D::Error::custom)
+        } else {
+            let (v, z, x) = <(T, T, T)>::deserialize(deserializer)?;
+            Ok(Sv4State::new(v, z, x))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_test::{assert_de_tokens, assert_tokens, Configure, Token};
+
+    #[test]
+    fn readable() {
+        let sv = Sv4State::<u8>::new(0b1000_1010, 0b0001_0000, 0b0010_0000);
+        assert_tokens(&sv.readable(), &[Token::Str("8'b10xz1010")]);
+        assert_de_tokens(&sv.readable(), &[Token::Str("8'b10xz_1010")]);
+
+        let profile = crate::format::FormatProfile {
+            unknown_chars: crate::format::UnknownChars {
+                x: '?',
+                x_partial: '?',
+                z: '#',
+                z_partial: '#',
+            },
+            ..Default::default()
+        };
+        crate::format::with_profile(profile, || {
+            assert_eq!(format!("{:b}", sv), "10?#1010");
+            assert_tokens(&sv.readable(), &[Token::Str("8'b10xz1010")]);
+        });
+    }
+
+    #[test]
+    fn compact() {
+        let sv = Sv4State::<u8>::new(0b1000_1010, 0b0001_0000, 0b0010_0000);
+        assert_tokens(
+            &sv.compact(),
+            &[
+                Token::Tuple { len: 3 },
+                Token::U8(0b1000_1010),
+                Token::U8(0b0001_0000),
+                Token::U8(0b0010_0000),
+                Token::TupleEnd,
+            ],
+        );
+    }
+}
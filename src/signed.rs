@@ -0,0 +1,137 @@
+//! Two's-complement view like SystemVerilog `signed` casts
+
+use crate::logic::SvLogic;
+use crate::Sv4State;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use num_traits::PrimInt;
+
+/// Signed view of the lower `width` bits of [`Sv4State`]
+#[derive(Copy, Clone, Debug)]
+pub struct Sv4Signed<T: Copy + Debug> {
+    value: Sv4State<T>,
+    width: u32,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Interpret the lower `width` bits as a two's-complement value
+    ///
+    /// `width` is saturated to the payload width.
+    pub fn as_signed(&self, width: u32) -> Sv4Signed<T> {
+        let width = width.clamp(1, T::zero().count_zeros());
+        Sv4Signed {
+            value: self.zero_extend(width),
+            width,
+        }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4Signed<T> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Value with the bits above `width` cleared
+    pub fn value(&self) -> Sv4State<T> {
+        self.value
+    }
+
+    /// Sign bit
+    pub fn sign(&self) -> SvLogic {
+        self.value.bit(self.width as usize - 1)
+    }
+
+    /// Value sign-extended to the payload width
+    pub fn sign_extended(&self) -> Sv4State<T> {
+        self.value.sign_extend(self.width)
+    }
+
+    /// Arithmetic shift right (`>>>`) by `n` bits
+    ///
+    /// Vacated bits are filled with the sign bit even if it is x/z.
+    pub fn shr(&self, n: u32) -> Self {
+        let ext = self.sign_extended();
+        let mut ret = Sv4State::zeros();
+        for i in 0..self.width as usize {
+            let src = (i + n as usize).min(self.width as usize - 1);
+            ret.set_bit(i, ext.bit(src));
+        }
+        Sv4Signed {
+            value: ret,
+            width: self.width,
+        }
+    }
+
+    /// Signed comparison, or `None` if either value has x/z bits
+    ///
+    /// Values of different widths are compared after sign extension.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self.to_i128(), other.to_i128()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => None,
+        }
+    }
+
+    /// `self < other`, which is x if either value has x/z bits
+    pub fn lt(&self, other: &Self) -> SvLogic {
+        self.compare(other)
+            .map_or(SvLogic::X, |o| SvLogic::from(o == Ordering::Less))
+    }
+
+    /// `self > other`, which is x if either value has x/z bits
+    pub fn gt(&self, other: &Self) -> SvLogic {
+        self.compare(other)
+            .map_or(SvLogic::X, |o| SvLogic::from(o == Ordering::Greater))
+    }
+
+    /// Value as `i128`, or `None` if it has x/z bits or is wider than 128 bits
+    pub fn to_i128(&self) -> Option<i128> {
+        if !self.value.is_known() || self.width > 128 {
+            return None;
+        }
+        let v = self.value.v.to_u128()?;
+        let shift = 128 - self.width;
+        Some(((v << shift) as i128) >> shift)
+    }
+}
+
+/// Case equality of the sign-extended values
+impl<T: Copy + Debug + PrimInt> PartialEq for Sv4Signed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.sign_extended() == other.sign_extended()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn signed() {
+        let a = sv("8'b0000_1010").as_signed(4);
+
+        assert_eq!(a.sign(), SvLogic::One);
+        assert_eq!(a.to_i128(), Some(-6));
+        assert_eq!(a.sign_extended(), sv("8'b1111_1010"));
+        assert_eq!(a.shr(2).value(), sv("8'b0000_1110"));
+        assert_eq!(a, sv("8'b1111_1010").as_signed(8));
+
+        let b = sv("8'b0000_0011").as_signed(4);
+        assert_eq!(a.lt(&b), SvLogic::One);
+        assert_eq!(a.gt(&b), SvLogic::Zero);
+        assert_eq!(a.compare(&b), Some(Ordering::Less));
+
+        let c = sv("8'b0000_x011").as_signed(4);
+        assert_eq!(c.shr(1).value(), sv("8'b0000_xx01"));
+        assert_eq!(c.lt(&b), SvLogic::X);
+        assert_eq!(c.to_i128(), None);
+        assert_eq!(
+            Sv4State::<u128>::known(!0).as_signed(128).to_i128(),
+            Some(-1)
+        );
+    }
+}
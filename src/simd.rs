@@ -0,0 +1,117 @@
+//! SIMD conversion of DPI canonical buffers
+//!
+//! The decode uses SSE2 on x86_64 and NEON on aarch64, which deinterleave
+//! four `aval`/`bval` pairs at once, and falls back to scalar code on other
+//! targets.
+
+use crate::{svLogicVecVal, Sv4State};
+
+/// Decode `data` to the planes of 32-bit words
+///
+/// `v[i]`, `z[i]` and `x[i]` are the planes of `data[i]`, as
+/// [`Sv4State::from_dpi`] of `u32`. As many words as fit in all planes are
+/// decoded, and the number is returned.
+pub fn decode_dpi_planes(
+    data: &[svLogicVecVal],
+    v: &mut [u32],
+    z: &mut [u32],
+    x: &mut [u32],
+) -> usize {
+    let len = data.len().min(v.len()).min(z.len()).min(x.len());
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    let start = len - len % 4;
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let start = 0;
+
+    #[cfg(target_arch = "x86_64")]
+    // SSE2 is a baseline feature of x86_64, and the loads/stores are unaligned
+    // accesses within `start` words of each slice. svLogicVecVal is repr(C) of
+    // two u32, so four elements are eight contiguous u32.
+    unsafe {
+        use std::arch::x86_64::*;
+        for i in (0..start).step_by(4) {
+            let p = data.as_ptr().add(i) as *const __m128i;
+            let lo = _mm_shuffle_epi32(_mm_loadu_si128(p), 0b11_01_10_00);
+            let hi = _mm_shuffle_epi32(_mm_loadu_si128(p.add(1)), 0b11_01_10_00);
+            let a = _mm_unpacklo_epi64(lo, hi);
+            let b = _mm_unpackhi_epi64(lo, hi);
+            _mm_storeu_si128(
+                v.as_mut_ptr().add(i) as *mut __m128i,
+                _mm_andnot_si128(b, a),
+            );
+            _mm_storeu_si128(
+                z.as_mut_ptr().add(i) as *mut __m128i,
+                _mm_andnot_si128(a, b),
+            );
+            _mm_storeu_si128(x.as_mut_ptr().add(i) as *mut __m128i, _mm_and_si128(a, b));
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    // NEON is a baseline feature of aarch64, and vld2q_u32 deinterleaves the
+    // eight u32 of four elements to aval and bval.
+    unsafe {
+        use std::arch::aarch64::*;
+        for i in (0..start).step_by(4) {
+            let ab = vld2q_u32(data.as_ptr().add(i) as *const u32);
+            vst1q_u32(v.as_mut_ptr().add(i), vbicq_u32(ab.0, ab.1));
+            vst1q_u32(z.as_mut_ptr().add(i), vbicq_u32(ab.1, ab.0));
+            vst1q_u32(x.as_mut_ptr().add(i), vandq_u32(ab.0, ab.1));
+        }
+    }
+
+    let planes = v[start..len]
+        .iter_mut()
+        .zip(&mut z[start..len])
+        .zip(&mut x[start..len]);
+    for (w, ((v, z), x)) in data[start..len].iter().zip(planes) {
+        *v = w.aval & !w.bval;
+        *z = w.bval & !w.aval;
+        *x = w.aval & w.bval;
+    }
+    len
+}
+
+impl Sv4State<u32> {
+    /// Convert from DPI canonical representation with SIMD
+    ///
+    /// The values are the same as [`Sv4State::from_dpi`].
+    pub fn from_dpi_simd(data: &[svLogicVecVal]) -> Vec<Self> {
+        let mut v = vec![0; data.len()];
+        let mut z = vec![0; data.len()];
+        let mut x = vec![0; data.len()];
+        decode_dpi_planes(data, &mut v, &mut z, &mut x);
+        v.into_iter()
+            .zip(z)
+            .zip(x)
+            .map(|((v, z), x)| Sv4State { v, z, x })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_dpi_simd() {
+        let data: Vec<_> = (0..7u32)
+            .map(|i| svLogicVecVal {
+                aval: 0x0123_4567u32.rotate_left(4 * i),
+                bval: 0x00ff_f00fu32.rotate_left(i),
+            })
+            .collect();
+
+        assert_eq!(
+            Sv4State::<u32>::from_dpi_simd(&data),
+            Sv4State::from_dpi(&data)
+        );
+
+        let (mut v, mut z, mut x) = ([0; 8], [0; 8], [0; 5]);
+        assert_eq!(decode_dpi_planes(&data, &mut v, &mut z, &mut x), 5);
+        assert_eq!(
+            Sv4State::new(v[4], z[4], x[4]),
+            Sv4State::from_dpi(&data)[4]
+        );
+    }
+}
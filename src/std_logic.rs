@@ -0,0 +1,151 @@
+//! VHDL `std_logic` (MVL9) values
+//!
+//! Conversion to 4-state values is lossy: strengths are dropped (`L`/`H` are
+//! 0/1), and `U`, `W` and `-` are x. Conversion from 4-state values is
+//! lossless and gives only `0`, `1`, `X` and `Z`.
+
+use crate::literal::ParseError;
+use crate::logic::SvLogic;
+use crate::Sv4State;
+use alloc::string::String;
+use core::fmt::Debug;
+use num_traits::PrimInt;
+
+/// VHDL `std_ulogic` value
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StdLogic {
+    /// `U`: uninitialized
+    U,
+    /// `X`: forcing unknown
+    X,
+    /// `0`: forcing 0
+    Zero,
+    /// `1`: forcing 1
+    One,
+    /// `Z`: high impedance
+    Z,
+    /// `W`: weak unknown
+    W,
+    /// `L`: weak 0
+    L,
+    /// `H`: weak 1
+    H,
+    /// `-`: don't care
+    DontCare,
+}
+
+impl StdLogic {
+    /// Parse a `std_logic` character (case-insensitive)
+    pub fn from_char(c: char) -> Option<Self> {
+        let ret = match c.to_ascii_uppercase() {
+            'U' => StdLogic::U,
+            'X' => StdLogic::X,
+            '0' => StdLogic::Zero,
+            '1' => StdLogic::One,
+            'Z' => StdLogic::Z,
+            'W' => StdLogic::W,
+            'L' => StdLogic::L,
+            'H' => StdLogic::H,
+            '-' => StdLogic::DontCare,
+            _ => return None,
+        };
+        Some(ret)
+    }
+
+    /// `std_logic` character in upper case
+    pub fn to_char(self) -> char {
+        match self {
+            StdLogic::U => 'U',
+            StdLogic::X => 'X',
+            StdLogic::Zero => '0',
+            StdLogic::One => '1',
+            StdLogic::Z => 'Z',
+            StdLogic::W => 'W',
+            StdLogic::L => 'L',
+            StdLogic::H => 'H',
+            StdLogic::DontCare => '-',
+        }
+    }
+}
+
+impl From<StdLogic> for SvLogic {
+    fn from(x: StdLogic) -> Self {
+        match x {
+            StdLogic::Zero | StdLogic::L => SvLogic::Zero,
+            StdLogic::One | StdLogic::H => SvLogic::One,
+            StdLogic::Z => SvLogic::Z,
+            StdLogic::U | StdLogic::X | StdLogic::W | StdLogic::DontCare => SvLogic::X,
+        }
+    }
+}
+
+impl From<SvLogic> for StdLogic {
+    fn from(x: SvLogic) -> Self {
+        match x {
+            SvLogic::Zero => StdLogic::Zero,
+            SvLogic::One => StdLogic::One,
+            SvLogic::X => StdLogic::X,
+            SvLogic::Z => StdLogic::Z,
+        }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// `std_logic_vector` string of the lower `width` bits from the most
+    /// significant bit
+    pub fn to_std_logic_vector(&self, width: u32) -> String {
+        let width = width.min(T::zero().count_zeros()) as usize;
+        (0..width)
+            .rev()
+            .map(|i| StdLogic::from(self.bit(i)).to_char())
+            .collect()
+    }
+
+    /// Parse a `std_logic_vector` string from the most significant bit
+    ///
+    /// Bits above the string are 0.
+    pub fn from_std_logic_vector(s: &str) -> Result<Self, ParseError> {
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        if s.chars().count() > T::zero().count_zeros() as usize {
+            return Err(ParseError::Overflow);
+        }
+        let mut ret = Sv4State::zeros();
+        for (i, c) in s.chars().rev().enumerate() {
+            let b = StdLogic::from_char(c).ok_or(ParseError::InvalidDigit(c))?;
+            ret.set_bit(i, b.into());
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn std_logic() {
+        let sv = Sv4State::<u8>::from_std_logic_vector("UXWLH-Z1").unwrap();
+
+        assert_eq!(format!("{:b}", sv), "xxx01xz1");
+        assert_eq!(sv.to_std_logic_vector(8), "XXX01XZ1");
+        assert_eq!(sv.to_std_logic_vector(3), "XZ1");
+        assert_eq!(
+            Sv4State::<u8>::from_std_logic_vector("hl"),
+            Ok(Sv4State::known(0b10))
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_std_logic_vector("01Q"),
+            Err(ParseError::InvalidDigit('Q'))
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_std_logic_vector("000000000"),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(
+            StdLogic::from_char('-').map(SvLogic::from),
+            Some(SvLogic::X)
+        );
+    }
+}
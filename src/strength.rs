@@ -0,0 +1,140 @@
+//! Drive strengths and resolution of multiple drivers
+
+use crate::logic::SvLogic;
+use crate::Sv4State;
+use core::fmt::Debug;
+use num_traits::PrimInt;
+
+/// Drive strength in ascending order
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Strength {
+    HighZ,
+    Small,
+    Medium,
+    Weak,
+    Large,
+    Pull,
+    Strong,
+    Supply,
+}
+
+/// [`Sv4State`] driven with strengths like `assign (strong0, pull1) w = value`
+#[derive(Copy, Clone, Debug)]
+pub struct SvDriven<T: Copy + Debug> {
+    pub value: Sv4State<T>,
+    /// Strength of 0 bits
+    pub strength0: Strength,
+    /// Strength of 1 bits
+    pub strength1: Strength,
+}
+
+impl<T: Copy + Debug + PrimInt> SvDriven<T> {
+    pub fn new(value: Sv4State<T>, strength0: Strength, strength1: Strength) -> Self {
+        SvDriven {
+            value,
+            strength0,
+            strength1,
+        }
+    }
+
+    /// Driven with `(strong0, strong1)`, the default of continuous assignments
+    pub fn strong(value: Sv4State<T>) -> Self {
+        Self::new(value, Strength::Strong, Strength::Strong)
+    }
+
+    /// Pull resistor to `value` with `(pull0, pull1)`
+    pub fn pull(value: Sv4State<T>) -> Self {
+        Self::new(value, Strength::Pull, Strength::Pull)
+    }
+
+    /// Open-drain driver with `(strong0, highz1)`
+    pub fn open_drain(value: Sv4State<T>) -> Self {
+        Self::new(value, Strength::Strong, Strength::HighZ)
+    }
+
+    /// Value and strength driven on bit `i`
+    ///
+    /// x bits are driven with the stronger of `strength0` and `strength1`.
+    /// `None` is returned if the bit is not driven.
+    pub fn drive(&self, i: usize) -> Option<(SvLogic, Strength)> {
+        let (logic, strength) = match self.value.bit(i) {
+            SvLogic::Zero => (SvLogic::Zero, self.strength0),
+            SvLogic::One => (SvLogic::One, self.strength1),
+            SvLogic::X => (SvLogic::X, self.strength0.max(self.strength1)),
+            SvLogic::Z => return None,
+        };
+        if strength == Strength::HighZ {
+            None
+        } else {
+            Some((logic, strength))
+        }
+    }
+}
+
+/// Resolve bit `i` of `drivers`
+///
+/// The strongest drivers win, and x is given if they disagree. z with
+/// `HighZ` is given if no driver drives the bit. Ambiguous strength ranges of
+/// the LRM are not modeled.
+pub fn resolve_bit<T: Copy + Debug + PrimInt>(
+    drivers: &[SvDriven<T>],
+    i: usize,
+) -> (SvLogic, Strength) {
+    let mut ret: Option<(SvLogic, Strength)> = None;
+    for (logic, strength) in drivers.iter().filter_map(|d| d.drive(i)) {
+        ret = match ret {
+            Some((l, s)) if s > strength => Some((l, s)),
+            Some((l, s)) if s == strength && l != logic => Some((SvLogic::X, s)),
+            Some((l, s)) if s == strength => Some((l, s)),
+            _ => Some((logic, strength)),
+        };
+    }
+    ret.unwrap_or((SvLogic::Z, Strength::HighZ))
+}
+
+/// Resolve the value of `drivers` on a wire
+pub fn resolve<T: Copy + Debug + PrimInt>(drivers: &[SvDriven<T>]) -> Sv4State<T> {
+    let mut ret = Sv4State::zeros();
+    for i in 0..T::zero().count_zeros() as usize {
+        ret.set_bit(i, resolve_bit(drivers, i).0);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn resolve() {
+        let pullup = SvDriven::pull(Sv4State::<u8>::ones());
+        let drivers = [
+            pullup,
+            SvDriven::open_drain(sv("8'b1100_0101")),
+            SvDriven::open_drain(sv("8'b1010_zzzz")),
+        ];
+        assert_eq!(super::resolve(&drivers), sv("8'b1000_0101"));
+        assert_eq!(resolve_bit(&drivers, 0), (SvLogic::One, Strength::Pull));
+        assert_eq!(resolve_bit(&drivers, 1), (SvLogic::Zero, Strength::Strong));
+
+        let drivers = [
+            SvDriven::strong(sv("8'b01xz_01xz")),
+            SvDriven::strong(sv("8'b0000_zzzz")),
+            SvDriven::new(sv("8'b1111_1111"), Strength::Weak, Strength::Weak),
+        ];
+        assert_eq!(super::resolve(&drivers), sv("8'b0xx0_01x1"));
+        assert_eq!(super::resolve::<u8>(&[]), Sv4State::all_z());
+        assert_eq!(
+            super::resolve(&[SvDriven::new(
+                sv("8'b0000_0000"),
+                Strength::Supply,
+                Strength::Supply
+            )]),
+            Sv4State::zeros()
+        );
+    }
+}
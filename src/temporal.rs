@@ -0,0 +1,244 @@
+//! Declarative temporal constraints evaluated over traces
+
+use crate::trace::Sv4Trace;
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Temporal constraint on a signal
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Constraint {
+    /// The signal must become known within `within` time units after bit 0
+    /// of the `reset` trace is deasserted
+    KnownAfterReset {
+        reset: String,
+        active_low: bool,
+        within: u64,
+    },
+    /// The signal must not contain x/z at or after `time`
+    NoUnknownAfter { time: u64 },
+}
+
+/// Kind of [`Violation`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The named trace is not given
+    MissingTrace(String),
+    /// The signal was not known at the deadline
+    NotKnownInTime,
+    /// The signal contained x/z after the given time
+    UnknownAfter,
+}
+
+/// Violation of a [`Constraint`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation<T: Copy + Debug + PrimInt> {
+    pub signal: String,
+    pub constraint: Constraint,
+    pub kind: ViolationKind,
+    /// time at which the violation is detected
+    pub time: u64,
+    /// value of the signal at `time`
+    pub value: Sv4State<T>,
+}
+
+impl<T: Copy + Debug + PrimInt> std::fmt::Display for Violation<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ViolationKind::MissingTrace(name) => {
+                write!(f, "{}: trace {} is missing", self.signal, name)
+            }
+            ViolationKind::NotKnownInTime => write!(
+                f,
+                "{}: not known in time at {}: {}",
+                self.signal, self.time, self.value
+            ),
+            ViolationKind::UnknownAfter => {
+                write!(
+                    f,
+                    "{}: unknown at {}: {}",
+                    self.signal, self.time, self.value
+                )
+            }
+        }
+    }
+}
+
+/// Set of temporal constraints on named signals
+#[derive(Clone, Debug, Default)]
+pub struct TemporalChecker {
+    checks: Vec<(String, Constraint)>,
+}
+
+impl TemporalChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a constraint on `signal`
+    pub fn check(mut self, signal: &str, constraint: Constraint) -> Self {
+        self.checks.push((String::from(signal), constraint));
+        self
+    }
+
+    /// `signal` must become known within `within` after `reset` is deasserted
+    pub fn known_after_reset(
+        self,
+        signal: &str,
+        reset: &str,
+        active_low: bool,
+        within: u64,
+    ) -> Self {
+        self.check(
+            signal,
+            Constraint::KnownAfterReset {
+                reset: String::from(reset),
+                active_low,
+                within,
+            },
+        )
+    }
+
+    /// `signal` must not contain x/z at or after `time`
+    pub fn no_unknown_after(self, signal: &str, time: u64) -> Self {
+        self.check(signal, Constraint::NoUnknownAfter { time })
+    }
+
+    /// Evaluate all constraints over `traces`, which are looked up by name
+    pub fn evaluate<T: Copy + Debug + PrimInt>(
+        &self,
+        traces: &[&Sv4Trace<T>],
+    ) -> Vec<Violation<T>> {
+        let find = |name: &str| traces.iter().find(|t| t.name == name);
+
+        let mut ret = Vec::new();
+        for (signal, constraint) in &self.checks {
+            let violation = |kind, time, value| Violation {
+                signal: signal.clone(),
+                constraint: constraint.clone(),
+                kind,
+                time,
+                value,
+            };
+
+            let trace = match find(signal) {
+                Some(x) => x,
+                None => {
+                    let kind = ViolationKind::MissingTrace(signal.clone());
+                    ret.push(violation(kind, 0, Sv4State::all_x()));
+                    continue;
+                }
+            };
+
+            match constraint {
+                Constraint::KnownAfterReset {
+                    reset,
+                    active_low,
+                    within,
+                } => {
+                    let reset = match find(reset) {
+                        Some(x) => x,
+                        None => {
+                            let kind = ViolationKind::MissingTrace(reset.clone());
+                            ret.push(violation(kind, 0, Sv4State::all_x()));
+                            continue;
+                        }
+                    };
+                    let start = match deassertion(reset, *active_low) {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    let deadline = start.saturating_add(*within);
+                    let known = trace.value_at(start).is_known()
+                        || trace
+                            .samples()
+                            .iter()
+                            .any(|s| s.time > start && s.time <= deadline && s.value.is_known());
+                    if !known {
+                        let value = trace.value_at(deadline);
+                        ret.push(violation(ViolationKind::NotKnownInTime, deadline, value));
+                    }
+                }
+                Constraint::NoUnknownAfter { time } => {
+                    let value = trace.value_at(*time);
+                    if !value.is_known() {
+                        ret.push(violation(ViolationKind::UnknownAfter, *time, value));
+                    }
+                    for s in trace.samples().iter().filter(|s| s.time > *time) {
+                        if !s.value.is_known() {
+                            ret.push(violation(ViolationKind::UnknownAfter, s.time, s.value));
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// First time bit 0 of `reset` becomes known inactive
+fn deassertion<T: Copy + Debug + PrimInt>(reset: &Sv4Trace<T>, active_low: bool) -> Option<u64> {
+    let inactive = if active_low { T::one() } else { T::zero() };
+    reset
+        .samples()
+        .iter()
+        .find(|s| {
+            (s.value.z | s.value.x) & T::one() == T::zero() && s.value.v & T::one() == inactive
+        })
+        .map(|s| s.time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn traces() -> (Sv4Trace<u8>, Sv4Trace<u8>) {
+        let mut rst_n = Sv4Trace::new("rst_n");
+        rst_n.push(0, Sv4State::known(0));
+        rst_n.push(10, Sv4State::known(1));
+
+        let mut data = Sv4Trace::new("data");
+        data.push(0, Sv4State::all_x());
+        data.push(14, Sv4State::known(5));
+        data.push(30, Sv4State::new(0, 0, 1));
+        data.push(40, Sv4State::known(6));
+        (rst_n, data)
+    }
+
+    #[test]
+    fn known_after_reset() {
+        let (rst_n, data) = traces();
+
+        let ok = TemporalChecker::new().known_after_reset("data", "rst_n", true, 4);
+        assert!(ok.evaluate(&[&rst_n, &data]).is_empty());
+
+        let ng = TemporalChecker::new().known_after_reset("data", "rst_n", true, 3);
+        let violations = ng.evaluate(&[&rst_n, &data]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, ViolationKind::NotKnownInTime);
+        assert_eq!(violations[0].time, 13);
+        assert_eq!(violations[0].value, Sv4State::all_x());
+        assert_eq!(
+            violations[0].to_string(),
+            "data: not known in time at 13: xx"
+        );
+    }
+
+    #[test]
+    fn no_unknown_after() {
+        let (rst_n, data) = traces();
+
+        let checker = TemporalChecker::new()
+            .no_unknown_after("data", 12)
+            .no_unknown_after("addr", 0);
+        let violations = checker.evaluate(&[&rst_n, &data]);
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0].time, 12);
+        assert_eq!(violations[1].time, 30);
+        assert_eq!(violations[1].value, Sv4State::new(0, 0, 1));
+        assert_eq!(
+            violations[2].kind,
+            ViolationKind::MissingTrace(String::from("addr"))
+        );
+    }
+}
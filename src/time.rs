@@ -0,0 +1,151 @@
+//! Simulation time with units
+
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+
+/// Unit of simulation time
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeUnit {
+    Fs,
+    Ps,
+    Ns,
+    Us,
+    Ms,
+    S,
+}
+
+impl TimeUnit {
+    /// Femtoseconds per unit
+    pub fn femtoseconds(self) -> u64 {
+        match self {
+            TimeUnit::Fs => 1,
+            TimeUnit::Ps => 1_000,
+            TimeUnit::Ns => 1_000_000,
+            TimeUnit::Us => 1_000_000_000,
+            TimeUnit::Ms => 1_000_000_000_000,
+            TimeUnit::S => 1_000_000_000_000_000,
+        }
+    }
+
+    /// Suffix as `$timescale`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimeUnit::Fs => "fs",
+            TimeUnit::Ps => "ps",
+            TimeUnit::Ns => "ns",
+            TimeUnit::Us => "us",
+            TimeUnit::Ms => "ms",
+            TimeUnit::S => "s",
+        }
+    }
+}
+
+/// Time of `value` x `unit`
+///
+/// Comparison is by the absolute time, so `1ns == 1000ps`.
+#[derive(Copy, Clone, Debug)]
+pub struct SimTime {
+    pub value: u64,
+    pub unit: TimeUnit,
+}
+
+impl SimTime {
+    pub fn new(value: u64, unit: TimeUnit) -> Self {
+        SimTime { value, unit }
+    }
+
+    /// Time in femtoseconds
+    pub fn as_femtoseconds(&self) -> u128 {
+        u128::from(self.value) * u128::from(self.unit.femtoseconds())
+    }
+
+    /// Convert to counts of `timescale`, e.g. `10ps` for `$timescale 10ps`
+    ///
+    /// The result is rounded down, and saturated to `u64::MAX`. A zero
+    /// timescale gives 0.
+    pub fn to_ticks(&self, timescale: SimTime) -> u64 {
+        let scale = timescale.as_femtoseconds();
+        if scale == 0 {
+            return 0;
+        }
+        u64::try_from(self.as_femtoseconds() / scale).unwrap_or(u64::MAX)
+    }
+
+    /// Convert to counts of `timescale` if the time is a multiple of it
+    pub fn to_ticks_exact(&self, timescale: SimTime) -> Option<u64> {
+        let scale = timescale.as_femtoseconds();
+        if scale == 0 || !self.as_femtoseconds().is_multiple_of(scale) {
+            return None;
+        }
+        u64::try_from(self.as_femtoseconds() / scale).ok()
+    }
+
+    /// Time of `ticks` counts of `timescale`
+    ///
+    /// The value is saturated to `u64::MAX`.
+    pub fn from_ticks(ticks: u64, timescale: SimTime) -> Self {
+        SimTime {
+            value: ticks.saturating_mul(timescale.value),
+            unit: timescale.unit,
+        }
+    }
+
+    /// Same time in `unit`, rounded down
+    pub fn to_unit(&self, unit: TimeUnit) -> Self {
+        SimTime {
+            value: self.to_ticks(SimTime::new(1, unit)),
+            unit,
+        }
+    }
+}
+
+impl PartialEq for SimTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_femtoseconds() == other.as_femtoseconds()
+    }
+}
+
+impl Eq for SimTime {}
+
+impl PartialOrd for SimTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SimTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_femtoseconds().cmp(&other.as_femtoseconds())
+    }
+}
+
+impl core::fmt::Display for SimTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{}", self.value, self.unit.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sim_time() {
+        let t = SimTime::new(1500, TimeUnit::Ps);
+
+        assert_eq!(t, SimTime::new(1_500_000, TimeUnit::Fs));
+        assert!(t > SimTime::new(1, TimeUnit::Ns));
+        assert_eq!(t.to_unit(TimeUnit::Ns), SimTime::new(1, TimeUnit::Ns));
+        assert_eq!(t.to_ticks(SimTime::new(10, TimeUnit::Ps)), 150);
+        assert_eq!(t.to_ticks_exact(SimTime::new(1, TimeUnit::Ns)), None);
+        assert_eq!(t.to_ticks_exact(SimTime::new(500, TimeUnit::Ps)), Some(3));
+        assert_eq!(
+            SimTime::from_ticks(3, SimTime::new(10, TimeUnit::Ns)).to_string(),
+            "30ns"
+        );
+        assert_eq!(
+            SimTime::new(u64::MAX, TimeUnit::S).to_ticks(SimTime::new(1, TimeUnit::Fs)),
+            u64::MAX
+        );
+    }
+}
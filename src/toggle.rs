@@ -0,0 +1,347 @@
+//! Toggle coverage of signals sampled by checkers
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Write};
+
+/// Transitions of a bit
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToggleCount {
+    /// 0 to 1
+    pub rise: u64,
+    /// 1 to 0
+    pub fall: u64,
+    /// 0/1 to x/z, counted if unknown transitions are tracked
+    pub to_unknown: u64,
+    /// x/z to 0/1, counted if unknown transitions are tracked
+    pub from_unknown: u64,
+}
+
+impl ToggleCount {
+    /// Whether the bit rose and fell
+    pub fn is_covered(&self) -> bool {
+        self.rise > 0 && self.fall > 0
+    }
+
+    /// Add the transitions of `other`
+    ///
+    /// Counts saturate at `u64::MAX`, as they may come from untrusted text.
+    pub fn merge(&mut self, other: &ToggleCount) {
+        self.rise = self.rise.saturating_add(other.rise);
+        self.fall = self.fall.saturating_add(other.fall);
+        self.to_unknown = self.to_unknown.saturating_add(other.to_unknown);
+        self.from_unknown = self.from_unknown.saturating_add(other.from_unknown);
+    }
+}
+
+/// Collector of per-bit toggle coverage over successive values of a signal
+///
+/// Only transitions between consecutive samples count, so 0 to x to 1 is not
+/// a rise.
+///
+/// ```
+/// use sv4state::toggle::Sv4ToggleCoverage;
+/// use sv4state::Sv4State;
+///
+/// let mut cov = Sv4ToggleCoverage::<u8>::new(2);
+/// for v in [0b00, 0b01, 0b10, 0b00] {
+///     cov.sample(Sv4State::known(v));
+/// }
+/// assert!(cov.is_covered());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sv4ToggleCoverage<T: Copy + Debug> {
+    counts: Vec<ToggleCount>,
+    track_unknown: bool,
+    last: Option<Sv4State<T>>,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4ToggleCoverage<T> {
+    /// Create a collector of the lower `width` bits, clamped to the payload
+    /// width
+    pub fn new(width: u32) -> Self {
+        let width = width.min(T::zero().count_zeros());
+        Sv4ToggleCoverage {
+            counts: vec![ToggleCount::default(); width as usize],
+            track_unknown: false,
+            last: None,
+        }
+    }
+
+    /// Count transitions to and from x/z
+    pub fn track_unknown(mut self, enable: bool) -> Self {
+        self.track_unknown = enable;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.counts.len() as u32
+    }
+
+    /// Add the next value of the signal
+    pub fn sample(&mut self, value: Sv4State<T>) {
+        let value = value.normalize();
+        if let Some(last) = self.last {
+            let (last_unknown, unknown) = (last.z | last.x, value.z | value.x);
+            let known = !last_unknown & !unknown;
+            let rise = known & !last.v & value.v;
+            let fall = known & last.v & !value.v;
+            let to_unknown = !last_unknown & unknown;
+            let from_unknown = last_unknown & !unknown;
+            for (i, c) in self.counts.iter_mut().enumerate() {
+                let bit = |x: T| (x >> i) & T::one() != T::zero();
+                c.rise += bit(rise) as u64;
+                c.fall += bit(fall) as u64;
+                if self.track_unknown {
+                    c.to_unknown += bit(to_unknown) as u64;
+                    c.from_unknown += bit(from_unknown) as u64;
+                }
+            }
+        }
+        self.last = Some(value);
+    }
+
+    /// Forget the last value, e.g. at reset, keeping the counts
+    pub fn restart(&mut self) {
+        self.last = None;
+    }
+
+    /// Transitions of bit `i`
+    pub fn bit(&self, i: usize) -> Option<&ToggleCount> {
+        self.counts.get(i)
+    }
+
+    /// Transitions of each bit from bit 0
+    pub fn counts(&self) -> &[ToggleCount] {
+        &self.counts
+    }
+
+    /// Number of bits which rose and fell
+    pub fn covered(&self) -> usize {
+        self.counts.iter().filter(|c| c.is_covered()).count()
+    }
+
+    /// Whether all bits rose and fell
+    pub fn is_covered(&self) -> bool {
+        self.covered() == self.counts.len()
+    }
+
+    /// Bits which didn't rise or fall
+    pub fn uncovered(&self) -> impl Iterator<Item = usize> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_covered())
+            .map(|(i, _)| i)
+    }
+
+    /// Ratio of covered bits, 1 if the width is 0
+    pub fn ratio(&self) -> f64 {
+        if self.counts.is_empty() {
+            1.0
+        } else {
+            self.covered() as f64 / self.counts.len() as f64
+        }
+    }
+
+    /// Add the counts of `other`, e.g. of the same signal in another test
+    ///
+    /// Bits beyond the width of `self` are ignored.
+    pub fn merge(&mut self, other: &Self) {
+        for (c, o) in self.counts.iter_mut().zip(&other.counts) {
+            c.merge(o);
+        }
+    }
+}
+
+/// Error of parsing [`ToggleReport::from_text`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToggleReportError {
+    /// 1-based line number
+    pub line: usize,
+}
+
+impl std::fmt::Display for ToggleReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: invalid toggle record", self.line)
+    }
+}
+
+impl std::error::Error for ToggleReportError {}
+
+/// Collector of toggle counts by signal name
+///
+/// Reports of parallel simulation shards are serialized by
+/// [`ToggleReport::to_text`], and combined by [`ToggleReport::merge`] after
+/// [`ToggleReport::from_text`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToggleReport {
+    counts: BTreeMap<String, Vec<ToggleCount>>,
+}
+
+impl ToggleReport {
+    pub fn new() -> Self {
+        ToggleReport::default()
+    }
+
+    /// Add `counts` of the signal `name` from bit 0, merged if already added
+    ///
+    /// A signal added with different widths has the widest one.
+    pub fn add(&mut self, name: &str, counts: &[ToggleCount]) {
+        let entry = self.counts.entry(String::from(name)).or_default();
+        if entry.len() < counts.len() {
+            entry.resize(counts.len(), ToggleCount::default());
+        }
+        for (c, o) in entry.iter_mut().zip(counts) {
+            c.merge(o);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[ToggleCount]> {
+        self.counts.get(name).map(|x| x.as_slice())
+    }
+
+    /// Signals and their counts sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[ToggleCount])> + '_ {
+        self.counts.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Add all counts of `other`
+    pub fn merge(&mut self, other: &ToggleReport) {
+        for (name, counts) in &other.counts {
+            self.add(name, counts);
+        }
+    }
+
+    /// Text with one line of `bit rise fall to_unknown from_unknown name`
+    /// separated by tabs per bit
+    ///
+    /// Names must not contain newlines.
+    pub fn to_text(&self) -> String {
+        let mut ret = String::new();
+        for (name, counts) in &self.counts {
+            for (i, c) in counts.iter().enumerate() {
+                let _ = writeln!(
+                    ret,
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    i, c.rise, c.fall, c.to_unknown, c.from_unknown, name
+                );
+            }
+        }
+        ret
+    }
+
+    /// Parse the text of [`ToggleReport::to_text`]
+    ///
+    /// Empty lines are ignored, and duplicated bits are merged. Bits missing
+    /// below the largest bit of a signal have no transitions.
+    pub fn from_text(s: &str) -> Result<Self, ToggleReportError> {
+        let mut ret = ToggleReport::new();
+        for (i, line) in s.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let error = ToggleReportError { line: i + 1 };
+            let mut columns = line.splitn(6, '\t');
+            let mut number = || -> Result<u64, ToggleReportError> {
+                columns
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or_else(|| error.clone())
+            };
+            let bit = number()?;
+            let count = ToggleCount {
+                rise: number()?,
+                fall: number()?,
+                to_unknown: number()?,
+                from_unknown: number()?,
+            };
+            let name = columns.next().ok_or_else(|| error.clone())?;
+            // bits beyond the widest payload type are corrupt
+            if bit >= 1024 {
+                return Err(error);
+            }
+            let mut counts = vec![ToggleCount::default(); bit as usize + 1];
+            counts[bit as usize] = count;
+            ret.add(name, &counts);
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle() {
+        let mut cov = Sv4ToggleCoverage::<u8>::new(4).track_unknown(true);
+        for s in ["4'b0000", "4'b0011", "4'b0x10", "4'b0110", "4'b0000"] {
+            cov.sample(s.parse().unwrap());
+        }
+        let rise_fall = |i| {
+            let c = cov.bit(i).unwrap();
+            (c.rise, c.fall, c.to_unknown, c.from_unknown)
+        };
+        assert_eq!(rise_fall(0), (1, 1, 0, 0));
+        assert_eq!(rise_fall(1), (1, 1, 0, 0));
+        assert_eq!(rise_fall(2), (0, 1, 1, 1));
+        assert_eq!(cov.uncovered().collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(cov.ratio(), 0.5);
+
+        let mut other = Sv4ToggleCoverage::<u8>::new(4);
+        for v in [0b1100, 0b0000, 0b1100] {
+            other.sample(Sv4State::known(v));
+        }
+        assert_eq!(other.bit(2).unwrap().to_unknown, 0);
+        cov.merge(&other);
+        assert!(cov.is_covered());
+
+        cov.restart();
+        cov.sample(Sv4State::known(0xf));
+        assert_eq!(cov.bit(0).unwrap().fall, 1);
+        assert_eq!(Sv4ToggleCoverage::<u8>::new(12).width(), 8);
+    }
+
+    #[test]
+    fn merge_shards() {
+        let mut shard0 = Sv4ToggleCoverage::<u8>::new(2);
+        let mut shard1 = Sv4ToggleCoverage::<u8>::new(2);
+        for v in [0b00, 0b01] {
+            shard0.sample(Sv4State::known(v));
+        }
+        for v in [0b11, 0b00] {
+            shard1.sample(Sv4State::known(v));
+        }
+        let (mut a, mut b) = (ToggleReport::new(), ToggleReport::new());
+        a.add("addr", shard0.counts());
+        b.add("addr", shard1.counts());
+        b.add("wide", &[ToggleCount::default(); 3]);
+
+        let mut merged = ToggleReport::from_text(&a.to_text()).unwrap();
+        merged.merge(&ToggleReport::from_text(&b.to_text()).unwrap());
+        let addr = merged.get("addr").unwrap();
+        assert!(addr[0].is_covered() && !addr[1].is_covered());
+        assert_eq!((addr[1].rise, addr[1].fall), (0, 1));
+        assert_eq!(merged.get("wide").map(|x| x.len()), Some(3));
+        assert_eq!(ToggleReport::from_text(&merged.to_text()), Ok(merged));
+        assert_eq!(
+            ToggleReport::from_text("0\t1\t1\t0\t0\tok\n\n0\t1\t1\n"),
+            Err(ToggleReportError { line: 3 })
+        );
+        let saturated =
+            ToggleReport::from_text("0\t18446744073709551615\t0\t0\t0\ta\n0\t1\t2\t0\t0\ta\n")
+                .unwrap();
+        let a = saturated.get("a").unwrap();
+        assert_eq!((a[0].rise, a[0].fall), (u64::MAX, 2));
+    }
+}
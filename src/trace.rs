@@ -0,0 +1,247 @@
+//! Timestamped traces of 4-state values
+
+use crate::time::{SimTime, TimeUnit};
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Value of a signal from `time` until the next sample
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sample<T: Copy + Debug + PrimInt> {
+    pub time: u64,
+    pub value: Sv4State<T>,
+}
+
+/// How [`Sv4Trace::decimate`] reduces the values within a window
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecimatePolicy {
+    /// The value at the start of the window, which may hide x/z pulses
+    Sample,
+    /// All-x if any value within the window has x/z bits, otherwise the
+    /// value at the start
+    AnyX,
+    /// Bits which are x/z in any value within the window are x, and the other
+    /// bits are the value at the start
+    BitwiseX,
+}
+
+/// Value changes of a named signal ordered by time
+///
+/// Sample times are counts of `timescale`, which is `1ns` by default. Before
+/// the first sample, the signal is regarded as all-x.
+#[derive(Clone, Debug)]
+pub struct Sv4Trace<T: Copy + Debug + PrimInt> {
+    pub name: String,
+    pub timescale: SimTime,
+    samples: Vec<Sample<T>>,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4Trace<T> {
+    pub fn new(name: &str) -> Self {
+        Sv4Trace {
+            name: String::from(name),
+            timescale: SimTime::new(1, TimeUnit::Ns),
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn with_timescale(mut self, timescale: SimTime) -> Self {
+        self.timescale = timescale;
+        self
+    }
+
+    /// Absolute time of sample time `time`
+    pub fn sim_time(&self, time: u64) -> SimTime {
+        SimTime::from_ticks(time, self.timescale)
+    }
+
+    /// Same trace with sample times converted to counts of `timescale`
+    ///
+    /// Times are rounded down, and samples falling on the same time are
+    /// merged to the last one.
+    pub fn rescale(&self, timescale: SimTime) -> Self {
+        let mut ret = Sv4Trace {
+            name: self.name.clone(),
+            timescale,
+            samples: Vec::new(),
+        };
+        for s in &self.samples {
+            ret.push(self.sim_time(s.time).to_ticks(timescale), s.value);
+        }
+        ret
+    }
+
+    /// Append a sample
+    ///
+    /// A sample at the same time as the last one replaces it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is earlier than the last sample.
+    pub fn push(&mut self, time: u64, value: Sv4State<T>) {
+        match self.samples.last_mut() {
+            Some(last) if last.time == time => last.value = value,
+            Some(last) if last.time > time => panic!(
+                "sample time {} is earlier than the last sample {}",
+                time, last.time
+            ),
+            _ => self.samples.push(Sample { time, value }),
+        }
+    }
+
+    pub fn samples(&self) -> &[Sample<T>] {
+        &self.samples
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Trace sampled every `n` sample times
+    ///
+    /// The value of each window `k * n..(k + 1) * n` is reduced by `policy`
+    /// and placed at `k * n`, and only changes are kept. `n` of 0 is 1.
+    pub fn decimate(&self, n: u64, policy: DecimatePolicy) -> Self {
+        let n = n.max(1);
+        let reduce = |values: &[Sv4State<T>]| -> Sv4State<T> {
+            let first = values[0];
+            let unknown = values
+                .iter()
+                .fold(T::zero(), |acc, x| acc | x.normalize().z | x.x);
+            match policy {
+                DecimatePolicy::Sample => first,
+                DecimatePolicy::AnyX if unknown != T::zero() => Sv4State::all_x(),
+                DecimatePolicy::AnyX => first,
+                DecimatePolicy::BitwiseX => Sv4State::new(first.v, T::zero(), unknown),
+            }
+        };
+        let mut ret = Sv4Trace {
+            name: self.name.clone(),
+            timescale: self.timescale,
+            samples: Vec::new(),
+        };
+        let mut change = |time: u64, value: Sv4State<T>| {
+            if ret.samples.last().is_none_or(|x| x.value != value) {
+                ret.samples.push(Sample { time, value });
+            }
+        };
+
+        let mut i = 0;
+        while i < self.samples.len() {
+            let start = self.samples[i].time / n * n;
+            let end = start.saturating_add(n);
+            let j = self.samples.partition_point(|s| s.time < end);
+            let mut values = vec![self.value_at(start)];
+            values.extend(self.samples[i..j].iter().map(|s| s.value));
+            change(start, reduce(&values));
+
+            // the following windows without samples hold the last value
+            let next = self.samples.get(j).map_or(u64::MAX, |s| s.time);
+            if end != u64::MAX && next >= end.saturating_add(n) {
+                change(end, reduce(&[self.samples[j - 1].value]));
+            }
+            i = j;
+        }
+        ret
+    }
+
+    /// Value at `time`
+    pub fn value_at(&self, time: u64) -> Sv4State<T> {
+        let index = self.samples.partition_point(|s| s.time <= time);
+        if index == 0 {
+            Sv4State::all_x()
+        } else {
+            self.samples[index - 1].value
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_at() {
+        let mut trace = Sv4Trace::<u8>::new("data");
+        trace.push(10, Sv4State::known(1));
+        trace.push(20, Sv4State::known(2));
+        trace.push(20, Sv4State::known(3));
+
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace.value_at(0), Sv4State::all_x());
+        assert_eq!(trace.value_at(10), Sv4State::known(1));
+        assert_eq!(trace.value_at(19), Sv4State::known(1));
+        assert_eq!(trace.value_at(100), Sv4State::known(3));
+    }
+
+    #[test]
+    fn decimate() {
+        let mut trace = Sv4Trace::<u8>::new("data");
+        trace.push(0, Sv4State::known(1));
+        trace.push(12, Sv4State::new(0, 0x01, 0x02));
+        trace.push(13, Sv4State::known(2));
+        trace.push(25, Sv4State::known(3));
+        trace.push(100, Sv4State::known(4));
+
+        let times = |policy| -> Vec<(u64, Sv4State<u8>)> {
+            trace
+                .decimate(10, policy)
+                .samples()
+                .iter()
+                .map(|s| (s.time, s.value))
+                .collect()
+        };
+        assert_eq!(
+            times(DecimatePolicy::Sample),
+            [
+                (0, Sv4State::known(1)),
+                (20, Sv4State::known(2)),
+                (30, Sv4State::known(3)),
+                (100, Sv4State::known(4)),
+            ]
+        );
+        assert_eq!(
+            times(DecimatePolicy::AnyX),
+            [
+                (0, Sv4State::known(1)),
+                (10, Sv4State::all_x()),
+                (20, Sv4State::known(2)),
+                (30, Sv4State::known(3)),
+                (100, Sv4State::known(4)),
+            ]
+        );
+        assert_eq!(
+            times(DecimatePolicy::BitwiseX)[1],
+            (10, Sv4State::new(0x01, 0, 0x03))
+        );
+    }
+
+    #[test]
+    fn rescale() {
+        let mut trace = Sv4Trace::<u8>::new("data").with_timescale(SimTime::new(10, TimeUnit::Ps));
+        trace.push(150, Sv4State::known(1));
+        trace.push(190, Sv4State::known(2));
+        trace.push(210, Sv4State::known(3));
+
+        assert_eq!(trace.sim_time(150), SimTime::new(1500, TimeUnit::Ps));
+        let ns = trace.rescale(SimTime::new(1, TimeUnit::Ns));
+        assert_eq!(
+            ns.samples(),
+            [
+                Sample {
+                    time: 1,
+                    value: Sv4State::known(2)
+                },
+                Sample {
+                    time: 2,
+                    value: Sv4State::known(3)
+                }
+            ]
+        );
+        assert_eq!(ns.timescale, SimTime::new(1000, TimeUnit::Ps));
+    }
+}
@@ -0,0 +1,148 @@
+//! VCD value strings, and VCD reader/writer (`vcd` feature)
+
+use crate::literal::ParseError;
+use crate::Sv4State;
+use num_traits::{PrimInt, WrappingShr};
+use std::fmt::Debug;
+
+#[cfg(feature = "vcd")]
+mod stream;
+#[cfg(feature = "vcd")]
+pub use stream::{VcdChange, VcdReader, VcdSignal, VcdWriter};
+#[cfg(feature = "vcd")]
+pub use vcd_rs::TimescaleUnit;
+
+impl<T: Copy + Debug + PrimInt + WrappingShr> Sv4State<T> {
+    /// VCD value string of the lower `width` bits
+    ///
+    /// `width == 1` gives a scalar value like `x`, and others give a vector
+    /// value like `b1z0`. Leading digits which are restored by the VCD
+    /// left-extension rule are omitted.
+    pub fn to_vcd_value(&self, width: u32) -> String {
+        let payload_width = T::zero().count_zeros();
+        let width = width.clamp(1, payload_width);
+        // not `{:b}`, whose x/z characters follow the format profile
+        let bits: String = (0..width as usize)
+            .rev()
+            .map(|i| self.bit(i).to_char())
+            .collect();
+        let bits = bits.as_str();
+
+        if width == 1 {
+            return String::from(bits);
+        }
+
+        let bytes = bits.as_bytes();
+        let mut start = 0;
+        while start + 1 < bytes.len() && bytes[start] == vcd_extension(bytes[start + 1]) {
+            start += 1;
+        }
+        format!("b{}", &bits[start..])
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Parse a VCD scalar (`0`, `1`, `x`, `z`) or vector (`b10xz`) value
+    ///
+    /// Vectors shorter than `width` are left-extended by the VCD rule: with x/z
+    /// if the leftmost digit is x/z, and with 0 otherwise. Bits above `width`
+    /// are 0.
+    pub fn from_vcd_value(s: &str, width: u32) -> Result<Self, ParseError> {
+        let width = width.min(T::zero().count_zeros());
+        let s = s.trim();
+        let digits = match s.chars().next() {
+            Some('b') | Some('B') => &s[1..],
+            Some(c @ 'r') | Some(c @ 'R') => return Err(ParseError::InvalidBase(c)),
+            Some(_) => s,
+            None => return Err(ParseError::Empty),
+        };
+        if digits.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        if digits.len() > width as usize {
+            return Err(ParseError::Overflow);
+        }
+
+        let bytes = digits.as_bytes();
+        let ext = vcd_extension(bytes[0]);
+        let mut ret = Sv4State::zeros();
+        for i in 0..width as usize {
+            let c = if i < bytes.len() {
+                bytes[bytes.len() - 1 - i]
+            } else {
+                ext
+            };
+            let b = T::one() << i;
+            match c {
+                b'0' => (),
+                b'1' => ret.v = ret.v | b,
+                b'x' | b'X' => ret.x = ret.x | b,
+                b'z' | b'Z' => ret.z = ret.z | b,
+                _ => return Err(ParseError::InvalidDigit(c as char)),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+/// Digit used to left-extend a VCD vector starting with `c`
+fn vcd_extension(c: u8) -> u8 {
+    match c {
+        b'x' | b'X' => b'x',
+        b'z' | b'Z' => b'z',
+        _ => b'0',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_vcd_value() {
+        let sv: Sv4State<u8> = "8'b0000_01x0".parse().unwrap();
+        assert_eq!(sv.to_vcd_value(8), "b1x0");
+        let sv: Sv4State<u8> = "8'b0000_0x10".parse().unwrap();
+        assert_eq!(sv.to_vcd_value(8), "b0x10");
+        let sv: Sv4State<u8> = "8'bzzzz_z010".parse().unwrap();
+        assert_eq!(sv.to_vcd_value(8), "bz010");
+        assert_eq!(Sv4State::<u8>::zeros().to_vcd_value(8), "b0");
+        assert_eq!(Sv4State::<u8>::all_x().to_vcd_value(4), "bx");
+        assert_eq!(Sv4State::<u8>::all_z().to_vcd_value(1), "z");
+        assert_eq!(Sv4State::<u8>::known(1).to_vcd_value(1), "1");
+    }
+
+    #[test]
+    fn from_vcd_value() {
+        let sv = Sv4State::<u8>::from_vcd_value("b1x0", 8).unwrap();
+        assert_eq!(format!("{:b}", sv), "000001x0");
+        let sv = Sv4State::<u8>::from_vcd_value("bx10", 6).unwrap();
+        assert_eq!(format!("{:b}", sv), "00xxxx10");
+        let sv = Sv4State::<u8>::from_vcd_value("z", 1).unwrap();
+        assert_eq!(format!("{:b}", sv), "0000000z");
+        let sv = Sv4State::<u8>::from_vcd_value("Z", 8).unwrap();
+        assert_eq!(sv, Sv4State::all_z());
+
+        assert_eq!(
+            Sv4State::<u8>::from_vcd_value("b", 8),
+            Err(ParseError::Empty)
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_vcd_value("b102", 8),
+            Err(ParseError::InvalidDigit('2'))
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_vcd_value("b101", 2),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_vcd_value("r1.5", 8),
+            Err(ParseError::InvalidBase('r'))
+        );
+
+        for s in ["b1x0", "b0x10", "bz010", "b0", "bx"].iter() {
+            let sv = Sv4State::<u8>::from_vcd_value(s, 8).unwrap();
+            assert_eq!(sv.to_vcd_value(8), *s);
+        }
+    }
+}
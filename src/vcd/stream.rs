@@ -0,0 +1,424 @@
+//! Streaming VCD reader and writer
+
+use crate::capture::CaptureMeta;
+use crate::time::{SimTime, TimeUnit};
+use crate::trace::Sv4Trace;
+use crate::Sv4State;
+use num_traits::{PrimInt, WrappingShr};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::io::{self, BufRead, Write};
+use std::marker::PhantomData;
+use vcd_rs::{Command, IdCode, Parser, ScopeItem, ScopeType, TimescaleUnit};
+
+/// Variable declared in a VCD file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VcdSignal {
+    /// hierarchical name joined with `.`
+    pub name: String,
+    pub width: u32,
+}
+
+/// Value change read from a VCD file
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VcdChange<T: Copy + Debug + PrimInt> {
+    pub time: u64,
+    /// index of [`VcdReader::signals`]
+    pub signal: usize,
+    pub value: Sv4State<T>,
+}
+
+/// Streaming VCD reader yielding [`VcdChange`]s
+///
+/// Variables wider than the payload width are truncated to the lower bits,
+/// and real/string variables are skipped.
+pub struct VcdReader<R: BufRead, T> {
+    parser: Parser<R>,
+    signals: Vec<VcdSignal>,
+    ids: HashMap<IdCode, Vec<usize>>,
+    timescale: Option<(u32, TimescaleUnit)>,
+    meta: Option<CaptureMeta>,
+    time: u64,
+    pending: VecDeque<(usize, String)>,
+    phantom: PhantomData<T>,
+}
+
+impl<R: BufRead, T: Copy + Debug + PrimInt> VcdReader<R, T> {
+    /// Parse the header of a VCD file
+    pub fn new(reader: R) -> io::Result<Self> {
+        let mut parser = Parser::new(reader);
+        let header = parser.parse_header()?;
+
+        let mut ret = VcdReader {
+            parser,
+            signals: Vec::new(),
+            ids: HashMap::new(),
+            timescale: header.timescale,
+            meta: header.items.iter().find_map(|item| match item {
+                ScopeItem::Comment(x) => CaptureMeta::parse_line(x.trim()).ok(),
+                _ => None,
+            }),
+            time: 0,
+            pending: VecDeque::new(),
+            phantom: PhantomData,
+        };
+        ret.collect_signals(&header.items, "");
+        Ok(ret)
+    }
+
+    fn collect_signals(&mut self, items: &[ScopeItem], prefix: &str) {
+        for item in items {
+            match item {
+                ScopeItem::Scope(scope) => {
+                    let prefix = format!("{}{}.", prefix, scope.identifier);
+                    self.collect_signals(&scope.items, &prefix);
+                }
+                ScopeItem::Var(var) => {
+                    let name = match var.index {
+                        Some(index) => format!("{}{}{}", prefix, var.reference, index),
+                        None => format!("{}{}", prefix, var.reference),
+                    };
+                    self.ids
+                        .entry(var.code)
+                        .or_default()
+                        .push(self.signals.len());
+                    self.signals.push(VcdSignal {
+                        name,
+                        width: var.size,
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Declared signals
+    pub fn signals(&self) -> &[VcdSignal] {
+        &self.signals
+    }
+
+    /// Index of the signal named `name`
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.signals.iter().position(|s| s.name == name)
+    }
+
+    /// `$timescale` of the file
+    pub fn timescale(&self) -> Option<(u32, TimescaleUnit)> {
+        self.timescale
+    }
+
+    /// `$timescale` of the file as [`SimTime`]
+    pub fn sim_timescale(&self) -> Option<SimTime> {
+        self.timescale
+            .map(|(value, unit)| SimTime::new(u64::from(value), unit.into()))
+    }
+
+    /// Metadata header in a `$comment` of the file
+    pub fn meta(&self) -> Option<&CaptureMeta> {
+        self.meta.as_ref()
+    }
+
+    fn push_change(&mut self, id: IdCode, value: String) {
+        if let Some(signals) = self.ids.get(&id) {
+            for signal in signals {
+                self.pending.push_back((*signal, value.clone()));
+            }
+        }
+    }
+}
+
+impl<R: BufRead, T: Copy + Debug + PrimInt> Iterator for VcdReader<R, T> {
+    type Item = io::Result<VcdChange<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((signal, value)) = self.pending.pop_front() {
+                let payload_width = T::zero().count_zeros();
+                let width = self.signals[signal].width;
+                // keep the lower bits of values wider than the payload
+                let skip = value.len().saturating_sub(payload_width as usize);
+                let value = Sv4State::from_vcd_value(&value[skip..], width)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+                return Some(value.map(|value| VcdChange {
+                    time: self.time,
+                    signal,
+                    value,
+                }));
+            }
+
+            match self.parser.next()? {
+                Ok(Command::Timestamp(time)) => self.time = time,
+                Ok(Command::ChangeScalar(id, value)) => self.push_change(id, value.to_string()),
+                Ok(Command::ChangeVector(id, value)) => self.push_change(id, value.to_string()),
+                Ok(_) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl From<TimescaleUnit> for TimeUnit {
+    fn from(x: TimescaleUnit) -> Self {
+        match x {
+            TimescaleUnit::S => TimeUnit::S,
+            TimescaleUnit::MS => TimeUnit::Ms,
+            TimescaleUnit::US => TimeUnit::Us,
+            TimescaleUnit::NS => TimeUnit::Ns,
+            TimescaleUnit::PS => TimeUnit::Ps,
+            TimescaleUnit::FS => TimeUnit::Fs,
+        }
+    }
+}
+
+/// VCD writer of [`Sv4State`] value changes
+///
+/// All signals are placed in one module scope. The header is written when
+/// the first value change is written.
+pub struct VcdWriter<W: Write> {
+    writer: vcd_rs::Writer<W>,
+    scope: String,
+    timescale: Option<(u32, TimescaleUnit)>,
+    meta: Option<CaptureMeta>,
+    signals: Vec<VcdSignal>,
+    ids: Vec<IdCode>,
+    time: Option<u64>,
+}
+
+impl<W: Write> VcdWriter<W> {
+    pub fn new(writer: W) -> Self {
+        VcdWriter {
+            writer: vcd_rs::Writer::new(writer),
+            scope: String::from("top"),
+            timescale: None,
+            meta: None,
+            signals: Vec::new(),
+            ids: Vec::new(),
+            time: None,
+        }
+    }
+
+    /// Name of the module scope (`top` by default)
+    pub fn scope(mut self, name: &str) -> Self {
+        self.scope = String::from(name);
+        self
+    }
+
+    /// `$timescale` of the file
+    pub fn timescale(mut self, value: u32, unit: TimescaleUnit) -> Self {
+        self.timescale = Some((value, unit));
+        self
+    }
+
+    /// Metadata header written as a `$comment`
+    pub fn meta(mut self, meta: CaptureMeta) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Declare a signal, and return its index
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value change is already written.
+    pub fn add_signal(&mut self, name: &str, width: u32) -> usize {
+        assert!(
+            self.time.is_none(),
+            "signals must be added before value changes"
+        );
+        self.signals.push(VcdSignal {
+            name: String::from(name),
+            width,
+        });
+        self.signals.len() - 1
+    }
+
+    fn header(&mut self) -> io::Result<()> {
+        if let Some(meta) = &self.meta {
+            self.writer.comment(&meta.to_line())?;
+        }
+        if let Some((value, unit)) = self.timescale {
+            self.writer.timescale(value, unit)?;
+        }
+        self.writer.scope_def(ScopeType::Module, &self.scope)?;
+        for signal in &self.signals {
+            let id = self.writer.add_wire(signal.width, &signal.name)?;
+            self.ids.push(id);
+        }
+        self.writer.upscope()?;
+        self.writer.enddefinitions()
+    }
+
+    /// Write a value change of `signal` at `time`
+    ///
+    /// `time` must not be earlier than the previous change.
+    pub fn change<T: Copy + Debug + PrimInt + WrappingShr>(
+        &mut self,
+        time: u64,
+        signal: usize,
+        value: &Sv4State<T>,
+    ) -> io::Result<()> {
+        match self.time {
+            None => {
+                self.header()?;
+                self.writer.timestamp(time)?;
+            }
+            Some(t) if t < time => self.writer.timestamp(time)?,
+            Some(t) if t > time => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("time {} is earlier than the previous change {}", time, t),
+                ))
+            }
+            _ => (),
+        }
+        self.time = Some(time);
+
+        let (id, width) = match (self.ids.get(signal), self.signals.get(signal)) {
+            (Some(id), Some(s)) => (*id, s.width),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown signal index {}", signal),
+                ))
+            }
+        };
+        let value = value.to_vcd_value(width);
+        if width == 1 {
+            writeln!(self.writer.writer(), "{}{}", value, id)
+        } else {
+            writeln!(self.writer.writer(), "{} {}", value, id)
+        }
+    }
+
+    /// Write `traces` as signals of the given widths, and finish the file
+    ///
+    /// The output is loadable by waveform viewers such as Surfer. Samples of
+    /// all traces are merged in time order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value change is already written.
+    pub fn write_traces<T: Copy + Debug + PrimInt + WrappingShr>(
+        mut self,
+        traces: &[(&Sv4Trace<T>, u32)],
+    ) -> io::Result<()> {
+        let mut changes = Vec::new();
+        for (trace, width) in traces {
+            let signal = self.add_signal(&trace.name, *width);
+            changes.extend(trace.samples().iter().map(|s| (s.time, signal, s.value)));
+        }
+        changes.sort_by_key(|(time, signal, _)| (*time, *signal));
+        for (time, signal, value) in &changes {
+            self.change(*time, *signal, value)?;
+        }
+        self.finish()
+    }
+
+    /// Write the header if no value change is written, and flush
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.time.is_none() {
+            self.header()?;
+        }
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_read() {
+        let mut buf = Vec::new();
+        let meta = CaptureMeta::new().simulator("verilator").seed(7);
+        let mut writer = VcdWriter::new(&mut buf)
+            .timescale(1, TimescaleUnit::NS)
+            .meta(meta.clone());
+        let clk = writer.add_signal("clk", 1);
+        let data = writer.add_signal("data", 8);
+        writer.change(0, clk, &Sv4State::<u8>::zeros()).unwrap();
+        writer.change(0, data, &Sv4State::<u8>::all_x()).unwrap();
+        writer.change(5, clk, &Sv4State::<u8>::known(1)).unwrap();
+        writer
+            .change(5, data, &"8'b0000_z101".parse::<Sv4State<u8>>().unwrap())
+            .unwrap();
+        assert!(writer.change(4, clk, &Sv4State::<u8>::zeros()).is_err());
+        writer.finish().unwrap();
+
+        let reader = VcdReader::<_, u8>::new(&buf[..]).unwrap();
+        assert_eq!(reader.timescale(), Some((1, TimescaleUnit::NS)));
+        assert_eq!(reader.sim_timescale(), Some(SimTime::new(1, TimeUnit::Ns)));
+        assert_eq!(reader.meta(), Some(&meta));
+        assert_eq!(CaptureMeta::read_header(&buf[..]).unwrap(), Some(meta));
+        assert_eq!(
+            reader.signals(),
+            &[
+                VcdSignal {
+                    name: String::from("top.clk"),
+                    width: 1
+                },
+                VcdSignal {
+                    name: String::from("top.data"),
+                    width: 8
+                },
+            ]
+        );
+        assert_eq!(reader.find("top.data"), Some(1));
+
+        let changes: Vec<_> = reader.map(|x| x.unwrap()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                VcdChange {
+                    time: 0,
+                    signal: 0,
+                    value: Sv4State::zeros()
+                },
+                VcdChange {
+                    time: 0,
+                    signal: 1,
+                    value: Sv4State::all_x()
+                },
+                VcdChange {
+                    time: 5,
+                    signal: 0,
+                    value: Sv4State::known(1)
+                },
+                VcdChange {
+                    time: 5,
+                    signal: 1,
+                    value: Sv4State::new(0b101, 0b1000, 0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn write_traces() {
+        let mut clk = Sv4Trace::<u8>::new("clk");
+        clk.push(0, Sv4State::zeros());
+        clk.push(5, Sv4State::known(1));
+        let mut data = Sv4Trace::<u8>::new("data");
+        data.push(3, Sv4State::all_z());
+
+        let mut buf = Vec::new();
+        VcdWriter::new(&mut buf)
+            .write_traces(&[(&clk, 1), (&data, 4)])
+            .unwrap();
+
+        let reader = VcdReader::<_, u8>::new(&buf[..]).unwrap();
+        assert_eq!(reader.find("top.data"), Some(1));
+        let changes: Vec<_> = reader
+            .map(|x| x.unwrap())
+            .map(|c| (c.time, c.signal, c.value))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (0, 0, Sv4State::zeros()),
+                (3, 1, Sv4State::new(0, 0x0f, 0)),
+                (5, 0, Sv4State::known(1)),
+            ]
+        );
+    }
+}
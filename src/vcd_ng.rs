@@ -0,0 +1,94 @@
+//! Conversion to/from value types of the `vcd-ng` crate (`vcd-ng` feature)
+
+use crate::literal::ParseError;
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+use vcd_ng::{Value, VecValue};
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// `vcd-ng` scalar value of the least significant bit
+    pub fn to_vcd_ng_scalar(&self) -> Value {
+        self.vcd_ng_bit(0)
+    }
+
+    /// `vcd-ng` vector value of the lower `width` bits
+    pub fn to_vcd_ng_vector(&self, width: u32) -> VecValue {
+        let width = width.min(T::zero().count_zeros());
+        let mut ret = VecValue::new();
+        for i in (0..width as usize).rev() {
+            ret.push(self.vcd_ng_bit(i));
+        }
+        ret
+    }
+
+    /// Convert from a `vcd-ng` scalar value to the least significant bit
+    pub fn from_vcd_ng_scalar(value: Value) -> Self {
+        let mut ret = Sv4State::zeros();
+        ret.set_vcd_ng_bit(0, value);
+        ret
+    }
+
+    /// Convert from a `vcd-ng` vector value
+    ///
+    /// Bits above the vector are 0.
+    pub fn from_vcd_ng_vector(value: &VecValue) -> Result<Self, ParseError> {
+        if value.len() > T::zero().count_zeros() as usize {
+            return Err(ParseError::Overflow);
+        }
+        let mut ret = Sv4State::zeros();
+        for (i, b) in value.iter().enumerate() {
+            ret.set_vcd_ng_bit(value.len() - 1 - i, b);
+        }
+        Ok(ret)
+    }
+
+    fn vcd_ng_bit(&self, i: usize) -> Value {
+        let b = T::one() << i;
+        if self.x & b != T::zero() {
+            Value::X
+        } else if self.z & b != T::zero() {
+            Value::Z
+        } else if self.v & b != T::zero() {
+            Value::V1
+        } else {
+            Value::V0
+        }
+    }
+
+    fn set_vcd_ng_bit(&mut self, i: usize, value: Value) {
+        let b = T::one() << i;
+        match value {
+            Value::V0 => (),
+            Value::V1 => self.v = self.v | b,
+            Value::X => self.x = self.x | b,
+            Value::Z => self.z = self.z | b,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vcd_ng() {
+        let sv: Sv4State<u8> = "8'b0000_1xz0".parse().unwrap();
+        let vec = sv.to_vcd_ng_vector(4);
+
+        assert_eq!(
+            vec.iter().collect::<Vec<_>>(),
+            vec![Value::V1, Value::X, Value::Z, Value::V0]
+        );
+        assert_eq!(Sv4State::from_vcd_ng_vector(&vec), Ok(sv));
+        assert_eq!(Sv4State::<u8>::all_z().to_vcd_ng_scalar(), Value::Z);
+        assert_eq!(
+            Sv4State::<u8>::from_vcd_ng_scalar(Value::X),
+            Sv4State::new(0, 0, 1)
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_vcd_ng_vector(&VecValue::repeat(Value::V0, 9)),
+            Err(ParseError::Overflow)
+        );
+    }
+}
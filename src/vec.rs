@@ -0,0 +1,431 @@
+//! Width-tracked multi-word values
+
+use crate::logic::{Bit4, SvLogic};
+use crate::{read_dpi_bits, svLogicVecVal, write_dpi_bits, Sv4State};
+use alloc::{vec, vec::Vec};
+use core::fmt::{Debug, Write};
+use num_traits::{FromPrimitive, PrimInt};
+
+/// 4-state value of an arbitrary width
+///
+/// `words[i]` has the bits from `i * payload_width`, and bits above `width`
+/// are always 0.
+#[derive(Clone, Debug)]
+pub struct Sv4Vec<T: Copy + Debug> {
+    words: Vec<Sv4State<T>>,
+    width: usize,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4Vec<T> {
+    /// Create from the lower `width` bits of `words`
+    ///
+    /// Missing words are 0.
+    pub fn new(words: &[Sv4State<T>], width: usize) -> Self {
+        let mut ret = Sv4Vec {
+            words: vec![Sv4State::zeros(); width.div_ceil(Self::payload_width())],
+            width,
+        };
+        for (dst, src) in ret.words.iter_mut().zip(words.iter()) {
+            *dst = src.normalize();
+        }
+        ret.mask();
+        ret
+    }
+
+    /// Create from the lower `width` bits of `value`
+    pub fn from_value(value: Sv4State<T>, width: usize) -> Self {
+        Self::new(&[value], width.min(Self::payload_width()))
+    }
+
+    /// All bits of `width` bits are x
+    pub fn all_x(width: usize) -> Self {
+        Self::new(
+            &vec![Sv4State::all_x(); width.div_ceil(Self::payload_width())],
+            width,
+        )
+    }
+
+    /// All bits of `width` bits are 0
+    pub fn zeros(width: usize) -> Self {
+        Self::new(&[], width)
+    }
+
+    fn payload_width() -> usize {
+        T::zero().count_zeros() as usize
+    }
+
+    fn mask(&mut self) {
+        let rest = self.width % Self::payload_width();
+        if let (Some(last), true) = (self.words.last_mut(), rest != 0) {
+            let mask = Sv4State::<T>::width_mask(rest as u32);
+            last.v = last.v & mask;
+            last.z = last.z & mask;
+            last.x = last.x & mask;
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn words(&self) -> &[Sv4State<T>] {
+        &self.words
+    }
+
+    pub fn into_words(self) -> Vec<Sv4State<T>> {
+        self.words
+    }
+
+    /// Bit `i`, which is 0 beyond the width
+    pub fn bit(&self, i: usize) -> SvLogic {
+        if i >= self.width {
+            return SvLogic::Zero;
+        }
+        let payload_width = Self::payload_width();
+        self.words[i / payload_width].bit(i % payload_width)
+    }
+
+    /// Set bit `i`, which is ignored beyond the width
+    pub fn set_bit(&mut self, i: usize, value: SvLogic) {
+        if i < self.width {
+            let payload_width = Self::payload_width();
+            self.words[i / payload_width].set_bit(i % payload_width, value);
+        }
+    }
+
+    /// Whether all bits are 0 or 1
+    pub fn is_known(&self) -> bool {
+        self.words.iter().all(|w| w.is_known())
+    }
+
+    /// Concatenation `{parts[0], parts[1], ...}`
+    ///
+    /// `parts[0]` is placed at the most significant side.
+    pub fn concat(parts: &[Sv4Vec<T>]) -> Self {
+        let width = parts.iter().map(|p| p.width).sum();
+        let mut ret = Self::zeros(width);
+        let mut lsb = 0;
+        for p in parts.iter().rev() {
+            for i in 0..p.width {
+                ret.set_bit(lsb + i, p.bit(i));
+            }
+            lsb += p.width;
+        }
+        ret
+    }
+
+    /// Replication `{n{self}}`
+    pub fn replicate(&self, n: usize) -> Self {
+        Self::concat(&vec![self.clone(); n])
+    }
+
+    /// Same bits in words of another payload type
+    pub fn repack<U: Copy + Debug + PrimInt>(&self) -> Sv4Vec<U> {
+        let mut ret = Sv4Vec::zeros(self.width);
+        for i in 0..self.width {
+            ret.set_bit(i, self.bit(i));
+        }
+        ret
+    }
+
+    /// Part-select `[msb:lsb]`
+    ///
+    /// Bits beyond the width are x, and `msb < lsb` gives an empty value.
+    pub fn select(&self, msb: usize, lsb: usize) -> Self {
+        if msb < lsb {
+            return Self::zeros(0);
+        }
+        let mut ret = self.slice(lsb, msb - lsb + 1);
+        for i in self.width.max(lsb)..=msb {
+            ret.set_bit(i - lsb, SvLogic::X);
+        }
+        ret
+    }
+
+    /// Assign `value` to part-select `[msb:lsb]`
+    ///
+    /// `value` is truncated or zero-extended to the selection, and bits beyond
+    /// the width are ignored.
+    pub fn insert(&mut self, msb: usize, lsb: usize, value: &Sv4Vec<T>) {
+        for i in lsb..=msb {
+            self.set_bit(i, value.bit(i - lsb));
+        }
+    }
+
+    /// Bits `lsb..lsb + width`
+    fn slice(&self, lsb: usize, width: usize) -> Self {
+        let mut ret = Self::zeros(width);
+        for i in 0..width {
+            ret.set_bit(i, self.bit(lsb + i));
+        }
+        ret
+    }
+
+    /// Remove the most significant bits equal to `bit`
+    ///
+    /// The narrowed value and the number of removed bits are returned.
+    pub fn trim_leading(&self, bit: Bit4) -> (Self, usize) {
+        let n = (0..self.width)
+            .rev()
+            .take_while(|i| self.bit(*i) == bit)
+            .count();
+        (self.slice(0, self.width - n), n)
+    }
+
+    /// Remove the least significant bits equal to `bit`
+    ///
+    /// The remaining bits are shifted to the least significant side. The
+    /// narrowed value and the number of removed bits are returned.
+    pub fn trim_trailing(&self, bit: Bit4) -> (Self, usize) {
+        let n = (0..self.width).take_while(|i| self.bit(*i) == bit).count();
+        (self.slice(n, self.width - n), n)
+    }
+
+    /// Write to DPI arguments of a wide signal split into several
+    ///
+    /// This is the inverse of [`Sv4Vec::from_dpi_args`]: `args[0]` gets the
+    /// most significant bits. Each argument is a buffer and its width, and
+    /// bits beyond the width of `self` are 0.
+    pub fn to_dpi_args(&self, args: &mut [(&mut [svLogicVecVal], usize)]) {
+        let payload_width = Self::payload_width();
+        let mut lsb = args.iter().map(|(_, w)| *w).sum::<usize>();
+        for (data, width) in args.iter_mut() {
+            lsb -= *width;
+            let part = self.slice(lsb, *width);
+            for (i, word) in part.words.iter().enumerate() {
+                let n = payload_width.min(*width - i * payload_width);
+                write_dpi_bits(data, i * payload_width, n as u32, word);
+            }
+        }
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4Vec<T> {
+    /// Convert from DPI canonical representation of a `width`-bit argument
+    ///
+    /// Bits beyond `data` are x.
+    pub fn from_dpi(data: &[svLogicVecVal], width: usize) -> Self {
+        let payload_width = Self::payload_width();
+        let words: Vec<Sv4State<T>> = (0..width.div_ceil(payload_width))
+            .map(|i| {
+                let n = payload_width.min(width - i * payload_width);
+                read_dpi_bits(data, i * payload_width, n as u32)
+            })
+            .collect();
+        Self::new(&words, width)
+    }
+
+    /// Convert from DPI arguments of a wide signal split into several
+    ///
+    /// Each argument is a buffer and its width, and the result is the
+    /// concatenation `{args[0], args[1], ...}`, whose sub-fields can be
+    /// unpacked by [`CacheLineCodec`](crate::cache_line::CacheLineCodec).
+    pub fn from_dpi_args(args: &[(&[svLogicVecVal], usize)]) -> Self {
+        let parts: Vec<_> = args
+            .iter()
+            .map(|(data, width)| Self::from_dpi(data, *width))
+            .collect();
+        Self::concat(&parts)
+    }
+}
+
+/// Case equality of values of the same width
+impl<T: Copy + Debug + PrimInt> PartialEq for Sv4Vec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.words == other.words
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Eq for Sv4Vec<T> {}
+
+/// Binary digits from the most significant bit
+impl<T: Copy + Debug + PrimInt> core::fmt::Binary for Sv4Vec<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let chars = crate::format::unknown_chars();
+        for i in (0..self.width).rev() {
+            f.write_char(chars.get(self.bit(i).to_char()))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Re-chunk the multi-word value `values` into words of another payload
+    /// type
+    ///
+    /// `values[i]` has the bits from `i * payload_width` in both sides, and
+    /// unused bits of the last word are 0.
+    pub fn repack<U: Copy + Debug + PrimInt>(values: &[Self]) -> Vec<Sv4State<U>> {
+        let width = values.len() * T::zero().count_zeros() as usize;
+        Sv4Vec::new(values, width).repack().into_words()
+    }
+
+    /// Part-select `[msb:lsb]` of the multi-word value `values`
+    ///
+    /// `values[i]` has the bits from `i * payload_width`, and the selection is
+    /// returned in the same layout. Bits beyond `values` are x.
+    pub fn select(values: &[Self], msb: usize, lsb: usize) -> Vec<Self> {
+        let width = values.len() * T::zero().count_zeros() as usize;
+        Sv4Vec::new(values, width).select(msb, lsb).into_words()
+    }
+
+    /// Assign `value` to part-select `[msb:lsb]` of the multi-word value
+    /// `values`
+    ///
+    /// `value` is truncated or zero-extended to the selection, and bits beyond
+    /// `values` are ignored.
+    pub fn insert(values: &mut [Self], msb: usize, lsb: usize, value: &[Self]) {
+        let payload_width = T::zero().count_zeros() as usize;
+        let mut dst = Sv4Vec::new(values, values.len() * payload_width);
+        dst.insert(msb, lsb, &Sv4Vec::new(value, value.len() * payload_width));
+        values.copy_from_slice(dst.words());
+    }
+
+    /// Remove the most significant bits of the lower `width` bits equal to `bit`
+    ///
+    /// The value and the number of removed bits are returned.
+    pub fn trim_leading(&self, width: u32, bit: Bit4) -> (Self, u32) {
+        let (v, n) = Sv4Vec::from_value(*self, width as usize).trim_leading(bit);
+        (
+            v.words().first().copied().unwrap_or_else(Self::zeros),
+            n as u32,
+        )
+    }
+
+    /// Remove the least significant bits of the lower `width` bits equal to
+    /// `bit`, and shift the remaining bits to the least significant side
+    ///
+    /// The value and the number of removed bits are returned.
+    pub fn trim_trailing(&self, width: u32, bit: Bit4) -> (Self, u32) {
+        let (v, n) = Sv4Vec::from_value(*self, width as usize).trim_trailing(bit);
+        (
+            v.words().first().copied().unwrap_or_else(Self::zeros),
+            n as u32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sv(s: &str) -> Sv4State<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn concat() {
+        let a = Sv4Vec::from_value(sv("8'b0000_01xz"), 4);
+        let b = Sv4Vec::from_value(sv("8'b1111_1011"), 8);
+        let c = Sv4Vec::from_value(sv("8'b0000_0001"), 1);
+
+        let d = Sv4Vec::concat(&[a.clone(), b, c]);
+        assert_eq!(d.width(), 13);
+        assert_eq!(format!("{:b}", d), "01xz111110111");
+        assert_eq!(d.words(), &[sv("8'b1111_0111"), sv("8'b000_01xz1")]);
+        assert_eq!(format!("{:b}", a.replicate(3)), "01xz01xz01xz");
+        assert_eq!(a.replicate(0), Sv4Vec::zeros(0));
+        assert_eq!(format!("{:b}", Sv4Vec::<u8>::all_x(10)), "xxxxxxxxxx");
+        assert!(!d.is_known());
+    }
+
+    #[test]
+    fn dpi_args() {
+        let hi = [svLogicVecVal {
+            aval: 0x1_2345,
+            bval: 0x1_0000,
+        }];
+        let lo = [
+            svLogicVecVal {
+                aval: 0x89ab_cdef,
+                bval: 0,
+            },
+            svLogicVecVal { aval: 0x3, bval: 0 },
+        ];
+        let wide = Sv4Vec::<u8>::from_dpi_args(&[(&hi, 17), (&lo, 34)]);
+        assert_eq!(wide.width(), 51);
+        assert_eq!(
+            wide.select(50, 34),
+            Sv4Vec::new(&[sv("8'h45"), sv("8'h23"), sv("8'bx")], 17)
+        );
+        assert_eq!(wide.select(33, 0), Sv4Vec::from_dpi(&lo, 34));
+        assert_eq!(
+            Sv4Vec::<u8>::from_dpi(&lo, 72).select(71, 64),
+            Sv4Vec::all_x(8)
+        );
+
+        let mut out_hi = [svLogicVecVal { aval: 0, bval: 0 }];
+        let mut out_lo = [
+            svLogicVecVal { aval: 0, bval: 0 },
+            svLogicVecVal { aval: 0, bval: 0 },
+        ];
+        wide.to_dpi_args(&mut [(&mut out_hi, 17), (&mut out_lo, 34)]);
+        assert_eq!(
+            Sv4Vec::<u8>::from_dpi_args(&[(&out_hi, 17), (&out_lo, 34)]),
+            wide
+        );
+    }
+
+    #[test]
+    fn trim() {
+        let a = Sv4Vec::from_value(sv("8'bzzz1_0x00"), 8);
+
+        let (b, n) = a.trim_leading(Bit4::Z);
+        assert_eq!((format!("{:b}", b), n), (String::from("10x00"), 3));
+        let (b, n) = a.trim_trailing(Bit4::Zero);
+        assert_eq!((format!("{:b}", b), n), (String::from("zzz10x"), 2));
+        let (b, n) = a.trim_leading(Bit4::One);
+        assert_eq!((b, n), (a.clone(), 0));
+        let (b, n) = Sv4Vec::<u8>::all_x(12).trim_trailing(Bit4::X);
+        assert_eq!((b, n), (Sv4Vec::zeros(0), 12));
+
+        assert_eq!(
+            sv("8'b0000_0x10").trim_leading(8, Bit4::Zero),
+            (sv("8'b0000_0x10"), 5)
+        );
+        assert_eq!(
+            sv("8'b0000_0x10").trim_trailing(6, Bit4::Zero),
+            (sv("8'b0000_00x1"), 1)
+        );
+    }
+
+    #[test]
+    fn select_insert() {
+        let mut values = [sv("8'b1010_0101"), sv("8'b0000_xz11")];
+
+        assert_eq!(Sv4State::select(&values, 9, 6), vec![sv("8'b0000_1110")]);
+        assert_eq!(Sv4State::select(&values, 17, 10), vec![sv("8'bxx00_00xz")]);
+        assert!(Sv4State::select(&values, 3, 4).is_empty());
+
+        Sv4State::insert(&mut values, 11, 4, &[sv("8'b0001_1z00")]);
+        assert_eq!(values, [sv("8'b1z00_0101"), sv("8'b0000_0001")]);
+        Sv4State::insert(&mut values, 17, 14, &[Sv4State::all_x()]);
+        assert_eq!(values, [sv("8'b1z00_0101"), sv("8'bxx00_0001")]);
+
+        let mut v = Sv4Vec::from_value(sv("8'b1111_0000"), 8);
+        v.insert(5, 2, &Sv4Vec::from_value(sv("8'b0000_0101"), 4));
+        assert_eq!(format!("{:b}", v), "11010100");
+        assert_eq!(format!("{:b}", v.select(9, 6)), "xx11");
+    }
+
+    #[test]
+    fn repack() {
+        let values = [sv("8'b1010_0101"), sv("8'b0000_xz11"), sv("8'hff")];
+        let wide: Vec<Sv4State<u32>> = Sv4State::repack(&values);
+
+        assert_eq!(
+            wide,
+            vec!["32'b1111_1111_0000_xz11_1010_0101".parse().unwrap()]
+        );
+        assert_eq!(Sv4State::<u32>::repack::<u8>(&wide)[..3], values);
+        let v = Sv4Vec::new(&values, 20).repack::<u16>();
+        assert_eq!(v.width(), 20);
+        assert_eq!(
+            v.words(),
+            [
+                "16'b0000_xz11_1010_0101".parse().unwrap(),
+                "16'hf".parse().unwrap()
+            ]
+        );
+    }
+}
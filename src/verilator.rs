@@ -0,0 +1,95 @@
+//! Verilator value layouts
+//!
+//! Verilator gives 2-state values as `CData`/`SData`/`IData`/`QData`
+//! integers or `WData` arrays of 32-bit words from the least significant word.
+//! Flows tracking unknown bits pass a value and an unknown-mask pair of the
+//! same layout.
+
+use crate::words::PlaneWords;
+use crate::{svLogicVecVal, Sv4State};
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Convert from a `WData` array of `width` bits
+    ///
+    /// The value is split into payload-width values from the least significant
+    /// bit, and has no x/z bits.
+    pub fn from_verilator_wide(words: &[u32], width: u32) -> Vec<Self> {
+        Self::from_verilator_masked(words, &[], width)
+    }
+
+    /// Convert from a `QData` value of `width` bits
+    pub fn from_verilator_quad(value: u64, width: u32) -> Vec<Self> {
+        Self::from_verilator_wide(&[value as u32, (value >> 32) as u32], width.min(64))
+    }
+
+    /// Convert from a value and unknown-mask pair of `WData` layout
+    ///
+    /// Bits set in `mask` are x. Missing words of `mask` are 0, and missing
+    /// words of `words` are x.
+    pub fn from_verilator_masked(words: &[u32], mask: &[u32], width: u32) -> Vec<Self> {
+        Self::from_plane_words(PlaneWords::with_mask(words, mask), width as usize)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Convert to a `WData` array of `width` bits
+    ///
+    /// x/z bits are 0, and bits above `width` of the last word are 0 as
+    /// Verilator expects.
+    pub fn to_verilator_wide(values: &[Self], width: u32) -> Vec<u32> {
+        Self::to_verilator_masked(values, width).0
+    }
+
+    /// Convert to a `QData` value of `width` bits
+    pub fn to_verilator_quad(values: &[Self], width: u32) -> u64 {
+        let words = Self::to_verilator_wide(values, width.min(64));
+        let word = |i: usize| words.get(i).copied().unwrap_or(0) as u64;
+        word(0) | (word(1) << 32)
+    }
+
+    /// Convert to a value and unknown-mask pair of `WData` layout
+    ///
+    /// Bits of the mask are set where the value is x or z, and the value is
+    /// 0 there.
+    pub fn to_verilator_masked(values: &[Self], width: u32) -> (Vec<u32>, Vec<u32>) {
+        let mut data: Vec<svLogicVecVal> = (0..width.div_ceil(32))
+            .map(|_| svLogicVecVal { aval: 0, bval: 0 })
+            .collect();
+        Self::to_dpi_into(values, &mut data);
+        let rest = width % 32;
+        if let (Some(last), true) = (data.last_mut(), rest != 0) {
+            last.aval &= (1 << rest) - 1;
+            last.bval &= (1 << rest) - 1;
+        }
+        data.iter().map(|w| (w.aval & !w.bval, w.bval)).unzip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verilator() {
+        let sv = Sv4State::<u32>::from_verilator_wide(&[0x89abcdef, 0xffff_ff12], 40);
+        assert_eq!(sv, vec![Sv4State::known(0x89abcdef), Sv4State::known(0x12)]);
+        assert_eq!(Sv4State::to_verilator_wide(&sv, 36), vec![0x89abcdef, 0x2]);
+
+        let sv = Sv4State::<u16>::from_verilator_quad(0x1234_5678_9abc, 48);
+        assert_eq!(sv.len(), 3);
+        assert_eq!(Sv4State::to_verilator_quad(&sv, 48), 0x1234_5678_9abc);
+
+        let sv = Sv4State::<u8>::from_verilator_masked(&[0x1234], &[0x00f0], 16);
+        assert_eq!(
+            sv,
+            vec![Sv4State::new(0x04, 0, 0xf0), Sv4State::known(0x12)]
+        );
+        let sv = [Sv4State::<u8>::new(0x01, 0x02, 0x04)];
+        assert_eq!(
+            Sv4State::to_verilator_masked(&sv, 8),
+            (vec![0x01], vec![0x06])
+        );
+    }
+}
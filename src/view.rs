@@ -0,0 +1,191 @@
+//! Borrowed views of bit ranges
+
+use crate::{Sv4State, SvLogic};
+use core::fmt::{Debug, Write};
+use core::ops::{Bound, RangeBounds};
+use num_traits::PrimInt;
+
+/// Bits `[msb:lsb]` of a borrowed [`Sv4State`]
+#[derive(Copy, Clone, Debug)]
+pub struct Sv4View<'a, T: Copy + Debug> {
+    value: &'a Sv4State<T>,
+    lsb: u32,
+    width: u32,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// View of bits in `range`
+    ///
+    /// Part-select `[15:8]` is `val.view(8..=15)` or `val.view(8..16)`. The
+    /// range is clamped to the payload width.
+    pub fn view<R: RangeBounds<u32>>(&self, range: R) -> Sv4View<'_, T> {
+        let payload_width = T::zero().count_zeros();
+        let lsb = match range.start_bound() {
+            Bound::Included(x) => *x,
+            Bound::Excluded(x) => x.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => x.saturating_add(1),
+            Bound::Excluded(x) => *x,
+            Bound::Unbounded => payload_width,
+        };
+        let lsb = lsb.min(payload_width);
+        Sv4View {
+            value: self,
+            lsb,
+            width: end.min(payload_width).saturating_sub(lsb),
+        }
+    }
+}
+
+impl<'a, T: Copy + Debug + PrimInt> Sv4View<'a, T> {
+    pub fn lsb(&self) -> u32 {
+        self.lsb
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Bits of the view moved to bit 0
+    pub fn get(&self) -> Sv4State<T> {
+        if self.width == 0 {
+            return Sv4State::known(T::zero());
+        }
+        let value = self.value.normalize();
+        let lsb = self.lsb as usize;
+        Sv4State::new(value.v >> lsb, value.z >> lsb, value.x >> lsb).zero_extend(self.width)
+    }
+
+    /// Bit `i` of the view
+    ///
+    /// Bits beyond the width are 0.
+    pub fn bit(&self, i: u32) -> SvLogic {
+        if i >= self.width {
+            SvLogic::Zero
+        } else {
+            self.value.bit((self.lsb + i) as usize)
+        }
+    }
+
+    /// Whether all bits of the view are 0 or 1
+    pub fn is_known(&self) -> bool {
+        self.get().is_known()
+    }
+
+    /// Bits of the view as an integer if they are all known
+    pub fn value(&self) -> Option<T> {
+        let value = self.get();
+        if value.is_known() {
+            Some(value.v)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T: Copy + Debug + PrimInt> PartialEq for Sv4View<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.get() == other.get()
+    }
+}
+
+/// Compare with a value whose bits above the width of the view are 0
+impl<'a, T: Copy + Debug + PrimInt> PartialEq<Sv4State<T>> for Sv4View<'a, T> {
+    fn eq(&self, other: &Sv4State<T>) -> bool {
+        self.get() == *other
+    }
+}
+
+/// Write `len` characters of `chars` padded by the width, fill and alignment
+/// of `f`, like [`core::fmt::Formatter::pad`] without an intermediate string
+fn pad_chars(
+    f: &mut core::fmt::Formatter<'_>,
+    len: usize,
+    chars: impl Iterator<Item = char>,
+) -> core::fmt::Result {
+    let pad = f.width().map_or(0, |w| w.saturating_sub(len));
+    let (pre, post) = match f.align() {
+        Some(core::fmt::Alignment::Right) => (pad, 0),
+        Some(core::fmt::Alignment::Center) => (pad / 2, pad - pad / 2),
+        _ => (0, pad),
+    };
+    let fill = f.fill();
+    for _ in 0..pre {
+        f.write_char(fill)?;
+    }
+    for c in chars {
+        f.write_char(c)?;
+    }
+    for _ in 0..post {
+        f.write_char(fill)?;
+    }
+    Ok(())
+}
+
+/// `width` digits from the most significant bit
+impl<'a, T: Copy + Debug + PrimInt> core::fmt::Binary for Sv4View<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let unknown = crate::format::unknown_chars();
+        let chars = (0..self.width)
+            .rev()
+            .map(|i| unknown.get(self.bit(i).to_char()));
+        pad_chars(f, self.width as usize, chars)
+    }
+}
+
+/// `width / 4` digits (rounded up) from the most significant digit
+///
+/// Digits are classified as `LowerHex` of [`Sv4State`], where z takes
+/// precedence over x.
+impl<'a, T: Copy + Debug + PrimInt> core::fmt::LowerHex for Sv4View<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let value = self.get();
+        let unknown = crate::format::unknown_chars();
+        let digits = self.width.div_ceil(4) as usize;
+        let chars = (0..digits).rev().map(|i| {
+            let mask = Sv4State::<T>::width_mask((self.width as usize - i * 4).min(4) as u32);
+            let (v, z, x) = (
+                (value.v >> (i * 4)) & mask,
+                (value.z >> (i * 4)) & mask,
+                (value.x >> (i * 4)) & mask,
+            );
+            match crate::unknown_digit(z, x, mask) {
+                Some(c) => unknown.get(c),
+                None => core::char::from_digit(v.to_u32().unwrap_or(0), 16).unwrap_or('?'),
+            }
+        });
+        pad_chars(f, digits, chars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view() {
+        let sv: Sv4State<u16> = "16'b1010_0x1z_0011_zzzz".parse().unwrap();
+        let field = sv.view(8..=15);
+
+        assert_eq!((field.lsb(), field.width()), (8, 8));
+        assert_eq!(field.get(), "16'b1010_0x1z".parse().unwrap());
+        assert_eq!(field.bit(2), SvLogic::X);
+        assert!(!field.is_known());
+        assert_eq!(format!("{:b}", field), "10100x1z");
+        assert_eq!(format!("{:x}", field), "aZ");
+        assert_eq!(format!("{:x}", sv.view(..)), format!("{:x}", sv));
+        assert_eq!(format!("{:x}", sv.view(..)), format!("{}", sv));
+        assert_eq!(format!("{:x}", sv.view(..6)), "3z");
+        assert_eq!(format!("{:x}", sv.view(2..7)), "0Z");
+        assert_eq!(format!("{:>4x}|{:_<4b}", field, sv.view(4..6)), "  aZ|11__");
+
+        assert_eq!(sv.view(4..8).value(), Some(0x3));
+        assert_eq!(sv.view(4..8), Sv4State::known(0x3));
+        assert_eq!(sv.view(4..8), sv.view(4..=7));
+        assert_ne!(sv.view(4..8), sv.view(4..9));
+        assert_eq!(sv.view(12..).width(), 4);
+        assert_eq!(sv.view(20..24).width(), 0);
+    }
+}
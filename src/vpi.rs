@@ -0,0 +1,221 @@
+//! VPI value representations
+
+#![allow(non_camel_case_types)]
+
+use crate::literal::ParseError;
+use crate::{svLogicVecVal, Sv4State};
+use num_traits::{FromPrimitive, PrimInt};
+use std::fmt::Debug;
+
+/// Type for vector values defined by IEEE 1800-2017 38.14 (`vpiVectorVal`)
+///
+/// The encoding of `aval`/`bval` is the same as [`svLogicVecVal`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct s_vpi_vecval {
+    pub aval: i32,
+    pub bval: i32,
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Convert from a VPI vector value of `width` bits (`vpiSize`)
+    ///
+    /// The value is split into payload-width values from the least significant
+    /// bit. Bits beyond `data` are x.
+    pub fn from_vpi(data: &[s_vpi_vecval], width: u32) -> Vec<Self> {
+        let data: Vec<svLogicVecVal> = data
+            .iter()
+            .map(|w| svLogicVecVal {
+                aval: w.aval as u32,
+                bval: w.bval as u32,
+            })
+            .collect();
+        Self::decode_range(&data, 0..width as usize)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// Convert to a VPI vector value of `width` bits (`vpiSize`)
+    ///
+    /// `values[i]` is placed from bit `i * payload_width`, and bits not covered
+    /// by `values` are 0.
+    pub fn to_vpi(values: &[Self], width: u32) -> Vec<s_vpi_vecval> {
+        let mut data: Vec<svLogicVecVal> = (0..width.div_ceil(32))
+            .map(|_| svLogicVecVal { aval: 0, bval: 0 })
+            .collect();
+        Self::to_dpi_into(values, &mut data);
+        let rest = width % 32;
+        if let (Some(last), true) = (data.last_mut(), rest != 0) {
+            last.aval &= (1 << rest) - 1;
+            last.bval &= (1 << rest) - 1;
+        }
+        data.iter()
+            .map(|w| s_vpi_vecval {
+                aval: w.aval as i32,
+                bval: w.bval as i32,
+            })
+            .collect()
+    }
+}
+
+/// VPI string value formats
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VpiStrFormat {
+    /// `vpiBinStrVal`
+    BinStr,
+    /// `vpiOctStrVal`
+    OctStr,
+    /// `vpiHexStrVal`
+    HexStr,
+    /// `vpiDecStrVal`
+    DecStr,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4State<T> {
+    /// VPI string value of the lower `width` bits
+    ///
+    /// Digits are written from the most significant digit without prefix and
+    /// separators, and the most significant digit covers only the remaining
+    /// bits. A digit whose bits are all x/z is `x`/`z`, and a digit whose bits
+    /// are partially x/z is `X`/`Z`, classified as `LowerHex` of
+    /// [`Sv4State`].
+    /// `DecStr` applies the digit rule to the whole value, and gives the
+    /// unsigned decimal value if it is fully known.
+    pub fn to_vpi_str(&self, format: VpiStrFormat, width: u32) -> String {
+        let value = self.normalize();
+        let width = width.clamp(1, T::zero().count_zeros()) as usize;
+        let bits = match format {
+            VpiStrFormat::BinStr => 1,
+            VpiStrFormat::OctStr => 3,
+            VpiStrFormat::HexStr => 4,
+            VpiStrFormat::DecStr => width,
+        };
+
+        let mut ret = String::new();
+        for i in (0..width.div_ceil(bits)).rev() {
+            let shift = i * bits;
+            let mask = Sv4State::<T>::width_mask(bits.min(width - shift) as u32);
+            let v = (value.v >> shift) & mask;
+            let z = (value.z >> shift) & mask;
+            let x = (value.x >> shift) & mask;
+            if let Some(c) = crate::unknown_digit(z, x, mask) {
+                ret.push(c);
+            } else if format == VpiStrFormat::DecStr {
+                ret.push_str(&v.to_u128().unwrap_or(0).to_string());
+            } else {
+                let d = v.to_u32().unwrap_or(0);
+                ret.push(std::char::from_digit(d, 16).unwrap_or('?'));
+            }
+        }
+        ret
+    }
+}
+
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Parse a VPI string value of `width` bits
+    ///
+    /// `X`/`Z` digits are read as all bits x/z. Strings shorter than `width`
+    /// are extended like SystemVerilog literals.
+    pub fn from_vpi_str(s: &str, format: VpiStrFormat, width: u32) -> Result<Self, ParseError> {
+        let (base, digits) = match format {
+            VpiStrFormat::BinStr => ('b', "01"),
+            VpiStrFormat::OctStr => ('o', "01234567"),
+            VpiStrFormat::HexStr => ('h', "0123456789abcdefABCDEF"),
+            VpiStrFormat::DecStr => ('d', "0123456789"),
+        };
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let unknown = |c: char| "xXzZ".contains(c);
+        let valid = if format == VpiStrFormat::DecStr {
+            s.chars().all(|c| digits.contains(c)) || (s.len() == 1 && s.chars().all(unknown))
+        } else {
+            s.chars().all(|c| digits.contains(c) || unknown(c))
+        };
+        if !valid {
+            let c = s
+                .chars()
+                .find(|c| !digits.contains(*c) && !unknown(*c))
+                .unwrap_or('x');
+            return Err(ParseError::InvalidDigit(c));
+        }
+        format!("{}'{}{}", width, base, s).parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vpi() {
+        let data = [
+            s_vpi_vecval {
+                aval: 0x89abcdef_u32 as i32,
+                bval: 0x0000ffff,
+            },
+            s_vpi_vecval {
+                aval: 0x5,
+                bval: 0xa,
+            },
+        ];
+
+        let sv = Sv4State::<u32>::from_vpi(&data, 36);
+        assert_eq!(
+            sv,
+            vec![
+                Sv4State::new(0x89ab0000, 0x00003210, 0x0000cdef),
+                Sv4State::new(0x5, 0xa, 0),
+            ]
+        );
+        assert_eq!(Sv4State::to_vpi(&sv, 36), data.to_vec());
+        assert_eq!(
+            Sv4State::to_vpi(&[Sv4State::<u16>::all_x()], 12),
+            vec![s_vpi_vecval {
+                aval: 0xfff,
+                bval: 0xfff,
+            }]
+        );
+    }
+
+    #[test]
+    fn vpi_str() {
+        let sv: Sv4State<u16> = "16'b0000_1010_x0xx_01z1".parse().unwrap();
+
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::BinStr, 12), "1010x0xx01z1");
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::OctStr, 14), "05XXZ");
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::HexStr, 13), "0aXZ");
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::DecStr, 16), "Z");
+        let sv = Sv4State::<u16>::new(0, 0x0ff0, 0x000f);
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::HexStr, 12), "zzx");
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::DecStr, 8), "Z");
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::DecStr, 4), "x");
+        let sv = Sv4State::<u16>::known(1234);
+        assert_eq!(sv.to_vpi_str(VpiStrFormat::DecStr, 16), "1234");
+
+        assert_eq!(
+            Sv4State::<u16>::from_vpi_str("1x0z", VpiStrFormat::BinStr, 4),
+            Ok(Sv4State::new(0b1000, 0b0001, 0b0100))
+        );
+        assert_eq!(
+            Sv4State::<u16>::from_vpi_str("aZ", VpiStrFormat::HexStr, 8),
+            Ok(Sv4State::new(0xa0, 0x0f, 0))
+        );
+        assert_eq!(
+            Sv4State::<u16>::from_vpi_str("1234", VpiStrFormat::DecStr, 16),
+            Ok(Sv4State::known(1234))
+        );
+        assert_eq!(
+            Sv4State::<u16>::from_vpi_str("x", VpiStrFormat::DecStr, 12),
+            Ok(Sv4State::all_x_width(12))
+        );
+        assert_eq!(
+            Sv4State::<u16>::from_vpi_str("1_0", VpiStrFormat::BinStr, 4),
+            Err(ParseError::InvalidDigit('_'))
+        );
+        assert_eq!(
+            Sv4State::<u16>::from_vpi_str("", VpiStrFormat::OctStr, 4),
+            Err(ParseError::Empty)
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Payload types wider than 128 bits by `ruint` (`ruint` feature)
+//!
+//! `ruint::Uint` implements the `num-traits` traits, so
+//! `Sv4State<Uint<BITS, LIMBS>>` works like the primitive payload types,
+//! including literals, formatting and DPI conversion.
+
+pub use ruint::aliases::{U1024, U256, U512};
+pub use ruint::Uint;
+
+use crate::Sv4State;
+
+/// 4-state value of 256 bits
+pub type Sv4U256 = Sv4State<U256>;
+
+/// 4-state value of 512 bits
+pub type Sv4U512 = Sv4State<U512>;
+
+/// 4-state value of 1024 bits
+pub type Sv4U1024 = Sv4State<U1024>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{svLogicVecVal, Sv4Vec};
+
+    #[test]
+    fn wide() {
+        let hex = format!("x{}1ffff", "0".repeat(58));
+        let sv: Sv4U256 = format!("256'h{}", hex).parse().unwrap();
+        assert_eq!(sv.bit(0), crate::SvLogic::One);
+        assert_eq!(sv.bit(255), crate::SvLogic::X);
+        assert_eq!(format!("{:x}", sv), hex);
+
+        let sv = Sv4U256::known(U256::MAX);
+        assert_eq!(
+            sv.to_dec_string(256, false),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(sv.to_dec_string(256, true), "-1");
+        assert_eq!(
+            Sv4U256::known(U256::from(1u8) << 200).to_dec_string(201, true),
+            "-1606938044258990275541962092341162602522202993782792835301376"
+        );
+
+        let data: Vec<svLogicVecVal> = (0..16)
+            .map(|i| svLogicVecVal {
+                aval: if i == 15 { 0 } else { i },
+                bval: if i == 15 { 1 } else { 0 },
+            })
+            .collect();
+        let values = Sv4U512::from_dpi(&data);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].z, U512::from(1u8) << 480);
+        let mut out: Vec<svLogicVecVal> = (0..16)
+            .map(|_| svLogicVecVal { aval: 0, bval: 0 })
+            .collect();
+        Sv4U512::to_dpi_into(&values, &mut out);
+        assert!(data
+            .iter()
+            .zip(&out)
+            .all(|(a, b)| a.aval == b.aval && a.bval == b.bval));
+
+        let v = Sv4Vec::new(&values, 512).repack::<u64>();
+        assert_eq!(v.words()[7].z, 1 << 32);
+        assert_eq!((values[0] & Sv4U512::zeros()), Sv4U512::zeros());
+    }
+}
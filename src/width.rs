@@ -0,0 +1,156 @@
+//! Compile-time width checking by `typenum` (`typenum` feature)
+
+use crate::Sv4State;
+use num_traits::PrimInt;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::Add;
+use typenum::{IsGreaterOrEqual, IsLessOrEqual, Sum, True, Unsigned, U128, U16, U32, U64, U8};
+
+/// Payload types with the payload width as a type-level number
+pub trait PayloadWidth {
+    type Width: Unsigned;
+}
+
+impl PayloadWidth for u8 {
+    type Width = U8;
+}
+
+impl PayloadWidth for u16 {
+    type Width = U16;
+}
+
+impl PayloadWidth for u32 {
+    type Width = U32;
+}
+
+impl PayloadWidth for u64 {
+    type Width = U64;
+}
+
+impl PayloadWidth for u128 {
+    type Width = U128;
+}
+
+/// [`Sv4State`] tagged with the width `W`
+///
+/// Bits above `W` are always 0. Operations between values of different widths
+/// do not compile, and widths exceeding the payload width are rejected at
+/// compile time.
+#[derive(Copy, Clone, Debug)]
+pub struct Sv4Width<T: Copy + Debug, W> {
+    value: Sv4State<T>,
+    width: PhantomData<W>,
+}
+
+impl<T, W> Sv4Width<T, W>
+where
+    T: Copy + Debug + PrimInt + PayloadWidth,
+    W: Unsigned + IsLessOrEqual<T::Width, Output = True>,
+{
+    /// Create from the lower `W` bits of `value`
+    pub fn new(value: Sv4State<T>) -> Self {
+        let mask = Sv4State::<T>::width_mask(W::U32);
+        let value = value.normalize();
+        Sv4Width {
+            value: Sv4State {
+                v: value.v & mask,
+                z: value.z & mask,
+                x: value.x & mask,
+            },
+            width: PhantomData,
+        }
+    }
+
+    /// Width of the value
+    pub fn width(&self) -> u32 {
+        W::U32
+    }
+
+    pub fn value(&self) -> &Sv4State<T> {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> Sv4State<T> {
+        self.value
+    }
+
+    /// Extend to the wider width `W2` with 0
+    pub fn zero_extend<W2>(self) -> Sv4Width<T, W2>
+    where
+        W2: Unsigned + IsLessOrEqual<T::Width, Output = True> + IsGreaterOrEqual<W, Output = True>,
+    {
+        Sv4Width {
+            value: self.value,
+            width: PhantomData,
+        }
+    }
+
+    /// Truncate to the narrower width `W2`
+    pub fn truncate<W2>(self) -> Sv4Width<T, W2>
+    where
+        W2: Unsigned + IsLessOrEqual<W, Output = True> + IsLessOrEqual<T::Width, Output = True>,
+    {
+        Sv4Width::new(self.value)
+    }
+
+    /// Concatenation `{self, lo}`
+    pub fn concat<W2>(self, lo: Sv4Width<T, W2>) -> Sv4Width<T, Sum<W, W2>>
+    where
+        W: Add<W2>,
+        W2: Unsigned,
+        Sum<W, W2>: Unsigned + IsLessOrEqual<T::Width, Output = True>,
+    {
+        if W2::U32 >= T::zero().count_zeros() {
+            return Sv4Width {
+                value: lo.value,
+                width: PhantomData,
+            };
+        }
+        let shift = W2::USIZE;
+        Sv4Width {
+            value: Sv4State {
+                v: (self.value.v << shift) | lo.value.v,
+                z: (self.value.z << shift) | lo.value.z,
+                x: (self.value.x << shift) | lo.value.x,
+            },
+            width: PhantomData,
+        }
+    }
+}
+
+/// Case equality of values of the same width
+impl<T: Copy + Debug + PrimInt, W> PartialEq for Sv4Width<T, W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Copy + Debug + PrimInt, W> Eq for Sv4Width<T, W> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typenum::{U12, U4};
+
+    #[test]
+    fn width() {
+        let a = Sv4Width::<u16, U4>::new("16'hff_xa".parse().unwrap());
+        let b = Sv4Width::<u16, U8>::new(Sv4State::known(0x5a));
+
+        assert_eq!(a.width(), 4);
+        assert_eq!(*a.value(), Sv4State::known(0xa));
+        let c = a.concat(b);
+        assert_eq!(c.width(), 12);
+        assert_eq!(c.into_inner(), Sv4State::known(0xa5a));
+        let d: Sv4Width<u16, U12> = a.zero_extend();
+        assert_eq!(d.into_inner(), Sv4State::known(0x00a));
+        let e: Sv4Width<u16, U4> = b.truncate();
+        assert_eq!(e, a);
+        assert_eq!(
+            Sv4Width::<u8, U8>::new(Sv4State::all_z())
+                .concat(Sv4Width::<u8, typenum::U0>::new(Sv4State::zeros())),
+            Sv4Width::new(Sv4State::all_z())
+        );
+    }
+}
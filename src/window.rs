@@ -0,0 +1,223 @@
+//! Time windows restricting analyses of traces
+//!
+//! A [`Window`] is a set of time spans built by [`Window::after`],
+//! [`Window::between`] and [`Window::while_high`], and combined by
+//! [`Window::and`] and [`Window::or`]. [`Sv4Trace::window`] borrows a trace as
+//! a [`TraceView`], so excluding warm-up or reset windows doesn't copy the
+//! samples.
+
+use crate::trace::{Sample, Sv4Trace};
+use crate::xstats::XStats;
+use crate::{Sv4State, SvLogic};
+use num_traits::PrimInt;
+use std::fmt::Debug;
+
+/// Sorted and disjoint half-open spans `start..end` of sample times
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Window {
+    spans: Vec<(u64, u64)>,
+}
+
+impl Window {
+    /// All times
+    pub fn all() -> Self {
+        Self::between(0, u64::MAX)
+    }
+
+    /// Times at or after `time`
+    pub fn after(time: u64) -> Self {
+        Self::between(time, u64::MAX)
+    }
+
+    /// Times in `start..end`
+    pub fn between(start: u64, end: u64) -> Self {
+        let spans = if start < end {
+            vec![(start, end)]
+        } else {
+            Vec::new()
+        };
+        Window { spans }
+    }
+
+    /// Times where the least significant bit of `signal` is 1
+    ///
+    /// x/z values are not high.
+    pub fn while_high<T: Copy + Debug + PrimInt>(signal: &Sv4Trace<T>) -> Self {
+        let mut spans: Vec<(u64, u64)> = Vec::new();
+        let samples = signal.samples();
+        for (i, s) in samples.iter().enumerate() {
+            if s.value.bit(0) != SvLogic::One {
+                continue;
+            }
+            let end = samples.get(i + 1).map_or(u64::MAX, |n| n.time);
+            match spans.last_mut() {
+                Some(last) if last.1 == s.time => last.1 = end,
+                _ => spans.push((s.time, end)),
+            }
+        }
+        Window { spans }
+    }
+
+    pub fn spans(&self) -> &[(u64, u64)] {
+        &self.spans
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Whether `time` is in the window
+    pub fn contains(&self, time: u64) -> bool {
+        let index = self.spans.partition_point(|s| s.1 <= time);
+        self.spans.get(index).is_some_and(|s| s.0 <= time)
+    }
+
+    /// Times in both windows
+    pub fn and(&self, other: &Window) -> Self {
+        let mut spans = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.spans.get(i), other.spans.get(j)) {
+            let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+            if start < end {
+                spans.push((start, end));
+            }
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Window { spans }
+    }
+
+    /// Times in either window
+    pub fn or(&self, other: &Window) -> Self {
+        let mut all: Vec<(u64, u64)> = self.spans.iter().chain(&other.spans).copied().collect();
+        all.sort_unstable();
+        let mut spans: Vec<(u64, u64)> = Vec::new();
+        for (start, end) in all {
+            match spans.last_mut() {
+                Some(last) if last.1 >= start => last.1 = last.1.max(end),
+                _ => spans.push((start, end)),
+            }
+        }
+        Window { spans }
+    }
+}
+
+/// Borrowed view of a trace restricted to a [`Window`]
+#[derive(Clone, Debug)]
+pub struct TraceView<'a, T: Copy + Debug + PrimInt> {
+    trace: &'a Sv4Trace<T>,
+    window: Window,
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4Trace<T> {
+    /// View of the trace in `window`
+    pub fn window(&self, window: Window) -> TraceView<'_, T> {
+        TraceView {
+            trace: self,
+            window,
+        }
+    }
+}
+
+impl<'a, T: Copy + Debug + PrimInt> TraceView<'a, T> {
+    pub fn trace(&self) -> &'a Sv4Trace<T> {
+        self.trace
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Further restrict the view to `window`
+    pub fn and(&self, window: &Window) -> Self {
+        self.trace.window(self.window.and(window))
+    }
+
+    /// Value at `time`, or `None` outside the window
+    pub fn value_at(&self, time: u64) -> Option<Sv4State<T>> {
+        if self.window.contains(time) {
+            Some(self.trace.value_at(time))
+        } else {
+            None
+        }
+    }
+
+    /// Samples in the window
+    ///
+    /// Each span starts with a sample of the value at its start.
+    pub fn samples(&self) -> impl Iterator<Item = Sample<T>> + '_ {
+        let samples = self.trace.samples();
+        self.window.spans.iter().flat_map(move |&(start, end)| {
+            let from = samples.partition_point(|s| s.time <= start);
+            let to = samples.partition_point(|s| s.time < end);
+            let first = Sample {
+                time: start,
+                value: self.trace.value_at(start),
+            };
+            std::iter::once(first).chain(samples[from..to.max(from)].iter().copied())
+        })
+    }
+
+    /// x/z statistics of the window over `start..end`
+    pub fn x_stats(&self, start: u64, end: u64) -> XStats {
+        let mut ret = XStats::default();
+        for s in Window::between(start, end).and(&self.window).spans() {
+            ret.merge(&self.trace.x_stats(s.0, s.1));
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window() {
+        let mut rst_n = Sv4Trace::<u8>::new("rst_n");
+        rst_n.push(0, Sv4State::known(0));
+        rst_n.push(10, Sv4State::known(1));
+        rst_n.push(50, Sv4State::all_x());
+        rst_n.push(60, Sv4State::known(1));
+        let mut data = Sv4Trace::<u8>::new("data");
+        data.push(5, Sv4State::known(1));
+        data.push(20, Sv4State::all_x());
+        data.push(30, Sv4State::known(2));
+        data.push(70, Sv4State::known(3));
+
+        let active = Window::while_high(&rst_n);
+        assert_eq!(active.spans(), [(10, 50), (60, u64::MAX)]);
+        let window = active.and(&Window::between(0, 65));
+        assert_eq!(window.spans(), [(10, 50), (60, 65)]);
+        assert_eq!(window.or(&Window::between(45, 61)).spans(), [(10, 65)]);
+        assert!(window.contains(10) && !window.contains(50) && window.contains(64));
+        assert!(Window::after(5).and(&Window::between(0, 5)).is_empty());
+
+        let view = data.window(Window::after(15).and(&active));
+        let samples: Vec<_> = view.samples().map(|s| (s.time, s.value)).collect();
+        assert_eq!(
+            samples,
+            [
+                (15, Sv4State::known(1)),
+                (20, Sv4State::all_x()),
+                (30, Sv4State::known(2)),
+                (60, Sv4State::known(2)),
+                (70, Sv4State::known(3)),
+            ]
+        );
+        assert_eq!(view.value_at(55), None);
+        assert_eq!(view.value_at(25), Some(Sv4State::all_x()));
+        assert_eq!(
+            view.x_stats(0, 100),
+            XStats {
+                total: 75,
+                known: 65,
+                x: 10,
+                z: 0,
+            }
+        );
+    }
+}
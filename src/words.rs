@@ -0,0 +1,145 @@
+//! Typed word buffers of simulator interfaces
+//!
+//! DPI canonical buffers and Verilator value/mask buffers are both slices of
+//! 32-bit words, and passing one as the other decodes silently wrong values.
+//! [`CanonicalWords`] and [`PlaneWords`] tell them apart in signatures.
+
+#[cfg(feature = "alloc")]
+use crate::Sv4State;
+use crate::{svLogicVecVal, Sv4DpiView};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::fmt::Debug;
+#[cfg(feature = "alloc")]
+use num_traits::{FromPrimitive, PrimInt};
+
+/// Words of DPI canonical representation, interleaved as `aval`, `bval` of
+/// each 32-bit chunk like `svLogicVecVal[]`
+#[derive(Copy, Clone)]
+pub struct CanonicalWords<'a> {
+    data: &'a [svLogicVecVal],
+}
+
+impl<'a> CanonicalWords<'a> {
+    /// Wrap interleaved words, where a trailing odd word is ignored
+    pub fn new(words: &'a [u32]) -> Self {
+        // svLogicVecVal is repr(C) of two u32, so it has the same layout and
+        // alignment as a pair of words.
+        let data = unsafe {
+            core::slice::from_raw_parts(words.as_ptr() as *const svLogicVecVal, words.len() / 2)
+        };
+        CanonicalWords { data }
+    }
+
+    /// Number of 32-bit chunks
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_dpi(&self) -> &'a [svLogicVecVal] {
+        self.data
+    }
+
+    /// View of the lower `width` bits
+    pub fn view(&self, width: usize) -> Sv4DpiView<'a> {
+        Sv4DpiView::new(self.data, width)
+    }
+}
+
+impl<'a> From<&'a [svLogicVecVal]> for CanonicalWords<'a> {
+    fn from(data: &'a [svLogicVecVal]) -> Self {
+        CanonicalWords { data }
+    }
+}
+
+/// Words of a value and an unknown mask of the same layout, from the least
+/// significant word like Verilator `WData`
+///
+/// Bits set in the mask are x.
+#[derive(Copy, Clone, Debug)]
+pub struct PlaneWords<'a> {
+    value: &'a [u32],
+    mask: &'a [u32],
+}
+
+impl<'a> PlaneWords<'a> {
+    /// Wrap a 2-state value
+    pub fn new(value: &'a [u32]) -> Self {
+        PlaneWords { value, mask: &[] }
+    }
+
+    /// Wrap a value and its unknown mask, whose missing words are 0
+    pub fn with_mask(value: &'a [u32], mask: &'a [u32]) -> Self {
+        PlaneWords { value, mask }
+    }
+
+    pub fn value(&self) -> &'a [u32] {
+        self.value
+    }
+
+    pub fn mask(&self) -> &'a [u32] {
+        self.mask
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Copy + Debug + PrimInt + FromPrimitive> Sv4State<T> {
+    /// Convert from DPI canonical words of a `width`-bit value
+    ///
+    /// The values are the same as [`Sv4State::from_dpi_width`].
+    pub fn from_canonical_words(words: CanonicalWords<'_>, width: usize) -> Vec<Self> {
+        Self::from_dpi_width(words.as_dpi(), width)
+    }
+
+    /// Convert from value and mask words of a `width`-bit value
+    ///
+    /// The value is split into payload-width values from the least
+    /// significant bit. Missing words of the value are x.
+    pub fn from_plane_words(words: PlaneWords<'_>, width: usize) -> Vec<Self> {
+        let data: Vec<svLogicVecVal> = words
+            .value
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                let m = words.mask.get(i).copied().unwrap_or(0);
+                svLogicVecVal {
+                    aval: w | m,
+                    bval: m,
+                }
+            })
+            .collect();
+        Self::decode_range(&data, 0..width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words() {
+        let buf = [0x1234_5678u32, 0x0000_00f0, 0xff, 0, 0xdead];
+        let canonical = CanonicalWords::new(&buf);
+        assert_eq!(canonical.len(), 2);
+        assert_eq!(format!("{:x}", canonical.view(40)), "ff123456Z8");
+        assert_eq!(
+            Sv4State::<u16>::from_canonical_words(canonical, 40),
+            Sv4State::from_dpi_width(canonical.as_dpi(), 40)
+        );
+
+        let planes = PlaneWords::with_mask(&buf[..2], &buf[1..2]);
+        assert_eq!(
+            Sv4State::<u8>::from_plane_words(planes, 12),
+            vec![Sv4State::new(0x08, 0, 0xf0), Sv4State::known(0x6)]
+        );
+        assert_eq!(
+            Sv4State::<u8>::from_plane_words(PlaneWords::new(&buf[..1]), 40)[4],
+            Sv4State::all_x()
+        );
+    }
+}
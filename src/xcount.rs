@@ -0,0 +1,178 @@
+//! Instrumentation of operators producing x
+//!
+//! While enabled on the current thread, operators of [`Sv4State`] count how
+//! often they are evaluated and how often they produce x from x/z inputs,
+//! grouped by the label given by [`with_label`].
+//!
+//! [`Sv4State`]: crate::Sv4State
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// Counts of an operator under a label
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XCount {
+    pub label: String,
+    /// Operator like `&` or `+`
+    pub op: &'static str,
+    pub evaluations: u64,
+    /// Evaluations producing x
+    pub unknown: u64,
+}
+
+#[derive(Default)]
+struct State {
+    labels: Vec<String>,
+    counts: HashMap<(String, &'static str), (u64, u64)>,
+}
+
+thread_local! {
+    // checked by every operator, so kept apart from the state to skip
+    // borrowing it while disabled
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+/// Enable or disable the instrumentation of the current thread
+pub fn set_enabled(enabled: bool) {
+    ENABLED.with(|e| e.set(enabled));
+}
+
+/// Whether the instrumentation of the current thread is enabled
+pub fn is_enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+/// Pops the innermost label even if the labeled closure panics
+struct LabelGuard;
+
+impl Drop for LabelGuard {
+    fn drop(&mut self) {
+        STATE.with(|s| s.borrow_mut().labels.pop());
+    }
+}
+
+/// Run `f` with operators counted under `label`
+///
+/// Labels can be nested, and the innermost label is used. Operators outside
+/// any label are counted under the empty label.
+pub fn with_label<R, F: FnOnce() -> R>(label: &str, f: F) -> R {
+    STATE.with(|s| s.borrow_mut().labels.push(String::from(label)));
+    let _guard = LabelGuard;
+    f()
+}
+
+/// Counts sorted by the number of evaluations producing x in descending order
+pub fn counts() -> Vec<XCount> {
+    let mut ret: Vec<XCount> = STATE.with(|s| {
+        s.borrow()
+            .counts
+            .iter()
+            .map(|((label, op), (evaluations, unknown))| XCount {
+                label: label.clone(),
+                op,
+                evaluations: *evaluations,
+                unknown: *unknown,
+            })
+            .collect()
+    });
+    ret.sort_by(|a, b| {
+        b.unknown
+            .cmp(&a.unknown)
+            .then_with(|| a.label.cmp(&b.label))
+            .then_with(|| a.op.cmp(b.op))
+    });
+    ret
+}
+
+/// Clear the counts of the current thread
+pub fn reset() {
+    STATE.with(|s| s.borrow_mut().counts.clear());
+}
+
+/// Count an evaluation of `op`
+pub(crate) fn record(op: &'static str, unknown: bool) {
+    if !is_enabled() {
+        return;
+    }
+    STATE.with(|s| {
+        let mut s = s.borrow_mut();
+        let label = s.labels.last().cloned().unwrap_or_default();
+        let count = s.counts.entry((label, op)).or_insert((0, 0));
+        count.0 += 1;
+        if unknown {
+            count.1 += 1;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sv4State;
+
+    #[test]
+    fn xcount() {
+        let a = Sv4State::<u8>::known(0x0f);
+        let b: Sv4State<u8> = "8'b0000_xxxx".parse().unwrap();
+
+        let _ = a & b;
+        assert!(counts().is_empty());
+
+        set_enabled(true);
+        with_label("alu", || {
+            let _ = a & b;
+            let _ = a & 0xf0;
+            let _ = a + b;
+        });
+        with_label("dec", || !b);
+        let _ = b | 0x01;
+        set_enabled(false);
+
+        assert_eq!(
+            counts(),
+            vec![
+                XCount {
+                    label: String::from(""),
+                    op: "|",
+                    evaluations: 1,
+                    unknown: 1
+                },
+                XCount {
+                    label: String::from("alu"),
+                    op: "&",
+                    evaluations: 2,
+                    unknown: 1
+                },
+                XCount {
+                    label: String::from("alu"),
+                    op: "+",
+                    evaluations: 1,
+                    unknown: 1
+                },
+                XCount {
+                    label: String::from("dec"),
+                    op: "~",
+                    evaluations: 1,
+                    unknown: 1
+                },
+            ]
+        );
+        reset();
+        assert!(counts().is_empty());
+    }
+
+    #[test]
+    fn label_unwind() {
+        let b: Sv4State<u8> = "8'b0000_xxxx".parse().unwrap();
+
+        set_enabled(true);
+        let ret = std::panic::catch_unwind(|| with_label("boom", || panic!("boom")));
+        assert!(ret.is_err());
+        let _ = !b;
+        set_enabled(false);
+
+        assert_eq!(counts()[0].label, "");
+        assert_eq!(counts()[0].op, "~");
+    }
+}
@@ -0,0 +1,301 @@
+//! Duration-weighted x/z statistics over traces
+
+use crate::trace::Sv4Trace;
+use num_traits::PrimInt;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Write};
+
+/// Time spent by a signal in each state
+///
+/// A value containing both x and z bits is counted in both `x` and `z`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct XStats {
+    /// total observed time
+    pub total: u64,
+    /// time in fully known values
+    pub known: u64,
+    /// time in values containing x
+    pub x: u64,
+    /// time in values containing z
+    pub z: u64,
+}
+
+impl XStats {
+    /// Time in values containing x or z
+    ///
+    /// This is 0 if `known` exceeds `total`, like in inconsistent records.
+    pub fn unknown(&self) -> u64 {
+        self.total.saturating_sub(self.known)
+    }
+
+    /// Ratio of [`XStats::unknown`] to the total time
+    pub fn unknown_ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.unknown() as f64 / self.total as f64
+        }
+    }
+
+    /// Add the times of `other`, e.g. of the same signal in another shard
+    pub fn merge(&mut self, other: &XStats) {
+        self.total += other.total;
+        self.known += other.known;
+        self.x += other.x;
+        self.z += other.z;
+    }
+}
+
+/// Error of parsing [`XReport::from_text`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XReportError {
+    /// 1-based line number
+    pub line: usize,
+}
+
+impl std::fmt::Display for XReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: invalid x-report record", self.line)
+    }
+}
+
+impl std::error::Error for XReportError {}
+
+/// Collector of [`XStats`] by signal name
+///
+/// Reports of parallel simulation shards are serialized by
+/// [`XReport::to_text`], and combined by [`XReport::merge`] after
+/// [`XReport::from_text`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XReport {
+    stats: BTreeMap<String, XStats>,
+}
+
+impl XReport {
+    pub fn new() -> Self {
+        XReport::default()
+    }
+
+    /// Add `stats` of the signal `name`, merged if already added
+    pub fn add(&mut self, name: &str, stats: &XStats) {
+        self.stats
+            .entry(String::from(name))
+            .or_default()
+            .merge(stats);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&XStats> {
+        self.stats.get(name)
+    }
+
+    /// Signals and their statistics sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &XStats)> + '_ {
+        self.stats.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.stats.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+
+    /// Add all statistics of `other`
+    pub fn merge(&mut self, other: &XReport) {
+        for (name, stats) in &other.stats {
+            self.add(name, stats);
+        }
+    }
+
+    /// Text with one line of `total known x z name` separated by tabs per
+    /// signal
+    ///
+    /// Names must not contain newlines.
+    pub fn to_text(&self) -> String {
+        let mut ret = String::new();
+        for (name, x) in &self.stats {
+            let _ = writeln!(ret, "{}\t{}\t{}\t{}\t{}", x.total, x.known, x.x, x.z, name);
+        }
+        ret
+    }
+
+    /// Parse the text of [`XReport::to_text`]
+    ///
+    /// Empty lines are ignored, and duplicated names are merged.
+    pub fn from_text(s: &str) -> Result<Self, XReportError> {
+        let mut ret = XReport::new();
+        for (i, line) in s.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let error = XReportError { line: i + 1 };
+            let mut columns = line.splitn(5, '\t');
+            let mut time = || -> Result<u64, XReportError> {
+                columns
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or_else(|| error.clone())
+            };
+            let stats = XStats {
+                total: time()?,
+                known: time()?,
+                x: time()?,
+                z: time()?,
+            };
+            let name = columns.next().ok_or_else(|| error.clone())?;
+            if stats.known > stats.total || stats.x.max(stats.z) > stats.unknown() {
+                return Err(error);
+            }
+            ret.add(name, &stats);
+        }
+        Ok(ret)
+    }
+}
+
+impl<T: Copy + Debug + PrimInt> Sv4Trace<T> {
+    /// x/z statistics over `start..end`
+    ///
+    /// Each sample holds until the next sample, and the signal is all-x before
+    /// the first sample.
+    pub fn x_stats(&self, start: u64, end: u64) -> XStats {
+        let mut ret = XStats::default();
+        if end <= start {
+            return ret;
+        }
+
+        let mut add = |from: u64, to: u64, value: &crate::Sv4State<T>| {
+            let from = from.max(start);
+            let to = to.min(end);
+            if from >= to {
+                return;
+            }
+            let d = to - from;
+            ret.total += d;
+            if value.is_known() {
+                ret.known += d;
+            }
+            if value.x != T::zero() {
+                ret.x += d;
+            }
+            if value.z != T::zero() {
+                ret.z += d;
+            }
+        };
+
+        let samples = self.samples();
+        let first = samples.first().map_or(end, |s| s.time);
+        add(start, first, &crate::Sv4State::all_x());
+        for (i, s) in samples.iter().enumerate() {
+            let next = samples.get(i + 1).map_or(end, |n| n.time);
+            add(s.time, next, &s.value);
+        }
+        ret
+    }
+}
+
+/// x/z statistics of each trace over `start..end`
+pub fn x_stats<T: Copy + Debug + PrimInt>(
+    traces: &[&Sv4Trace<T>],
+    start: u64,
+    end: u64,
+) -> Vec<(String, XStats)> {
+    traces
+        .iter()
+        .map(|t| (t.name.clone(), t.x_stats(start, end)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sv4State;
+
+    #[test]
+    fn x_stats() {
+        let mut trace = Sv4Trace::<u8>::new("data");
+        trace.push(10, Sv4State::known(1));
+        trace.push(15, Sv4State::new(0, 0x01, 0x02));
+        trace.push(17, Sv4State::all_z());
+        trace.push(20, Sv4State::known(2));
+
+        let stats = trace.x_stats(0, 100);
+        assert_eq!(
+            stats,
+            XStats {
+                total: 100,
+                known: 85,
+                x: 12,
+                z: 5,
+            }
+        );
+        assert_eq!(stats.unknown(), 15);
+        assert_eq!(stats.unknown_ratio(), 0.15);
+
+        let stats = trace.x_stats(16, 18);
+        assert_eq!(
+            stats,
+            XStats {
+                total: 2,
+                known: 0,
+                x: 1,
+                z: 2,
+            }
+        );
+        assert_eq!(trace.x_stats(5, 5), XStats::default());
+
+        let bad = XStats {
+            total: 1,
+            known: 2,
+            x: 0,
+            z: 0,
+        };
+        assert_eq!((bad.unknown(), bad.unknown_ratio()), (0, 0.0));
+    }
+
+    #[test]
+    fn merge_shards() {
+        let a = XStats {
+            total: 100,
+            known: 90,
+            x: 10,
+            z: 0,
+        };
+        let b = XStats {
+            total: 50,
+            known: 20,
+            x: 10,
+            z: 25,
+        };
+        let mut shard0 = XReport::new();
+        shard0.add("data", &a);
+        shard0.add("valid", &a);
+        let mut shard1 = XReport::new();
+        shard1.add("data", &b);
+        shard1.add("addr\tlo", &b);
+
+        let mut merged = XReport::from_text(&shard0.to_text()).unwrap();
+        merged.merge(&XReport::from_text(&shard1.to_text()).unwrap());
+        assert_eq!(merged.len(), 3);
+        assert_eq!(
+            merged.get("data"),
+            Some(&XStats {
+                total: 150,
+                known: 110,
+                x: 20,
+                z: 25,
+            })
+        );
+        assert_eq!(merged.get("addr\tlo"), Some(&b));
+        assert_eq!(XReport::from_text(&merged.to_text()), Ok(merged));
+
+        assert_eq!(
+            XReport::from_text("1\t1\t0\t0\tok\n\n1\t0\t0\n"),
+            Err(XReportError { line: 3 })
+        );
+        assert_eq!(
+            XReport::from_text("1\t1\t1\t0\tbad"),
+            Err(XReportError { line: 1 })
+        );
+    }
+}